@@ -0,0 +1,157 @@
+//! `juggler add`: quick capture of a single todo.
+
+use std::path::Path;
+
+use indexmap::IndexMap;
+
+use super::notify_hooks;
+use crate::due::parse_due_argument;
+use crate::error::{JugglerError, Result};
+use crate::store::{TodoItem, load_todos, lock_store, store_todos_with_clock};
+use crate::time::SharedClock;
+use crate::ui::Todo;
+
+/// What to capture; `due` uses the CLI due syntax from `parse_due_argument`.
+/// Also the request body of `POST /todos` in `juggler serve`.
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NewTodo {
+    pub title: String,
+    pub due: Option<String>,
+    pub comment: Option<String>,
+}
+
+/// Append `new_todo` to the store and return its assigned `T<N>` id.
+pub fn add_todo(todos_file: &Path, new_todo: NewTodo, clock: SharedClock) -> Result<String> {
+    let title = new_todo.title.trim();
+    if title.is_empty() {
+        return Err(JugglerError::config("Todo title must not be empty"));
+    }
+    let due_date = new_todo
+        .due
+        .as_deref()
+        .map(|due| parse_due_argument(due, clock.now()))
+        .transpose()?;
+
+    let _lock = lock_store(todos_file)?;
+    let mut todos = load_todos(todos_file)?;
+    let before = todos.clone();
+    todos.push(Todo::from(TodoItem {
+        todo_id: None,
+        title: title.to_string(),
+        comment: new_todo
+            .comment
+            .filter(|comment| !comment.trim().is_empty()),
+        done: false,
+        due_date,
+        google_task_id: None,
+        blocked_by: Vec::new(),
+        log: Vec::new(),
+        created_at: Some(clock.now()),
+        completed_at: None,
+        snooze_count: 0,
+        fields: IndexMap::new(),
+        extra: toml::Table::new(),
+    }));
+    store_todos_with_clock(&mut todos, todos_file, clock)?;
+    notify_hooks(todos_file, &before, &todos);
+
+    todos
+        .last()
+        .and_then(|todo| todo.todo_id.clone())
+        .ok_or_else(|| JugglerError::Other("todo_id missing after save".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::due::Due;
+    use crate::time::test_clock;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn add_todo_appends_with_next_id_and_relative_due() {
+        let temp_dir = tempfile::TempDir::new().expect("create temp dir");
+        let todos_file = temp_dir.path().join("TODOs.toml");
+        let clock = test_clock();
+
+        let first = add_todo(
+            &todos_file,
+            NewTodo {
+                title: "Buy milk".to_string(),
+                ..NewTodo::default()
+            },
+            clock.clone(),
+        )
+        .expect("add first");
+        let second = add_todo(
+            &todos_file,
+            NewTodo {
+                title: "  Call dentist ".to_string(),
+                due: Some("3d".to_string()),
+                comment: Some("Ask about Friday".to_string()),
+            },
+            clock,
+        )
+        .expect("add second");
+
+        assert_eq!(first, "T1");
+        assert_eq!(second, "T2");
+        let todos = load_todos(&todos_file).expect("reload");
+        assert_eq!(todos[1].title, "Call dentist");
+        assert_eq!(todos[1].comment.as_deref(), Some("Ask about Friday"));
+        assert_eq!(
+            todos[1].due_date,
+            Some(Due::At(Utc.with_ymd_and_hms(2025, 1, 4, 0, 0, 0).unwrap()))
+        );
+    }
+
+    #[test]
+    fn add_todo_rejects_blank_title_and_bad_due_without_writing() {
+        let temp_dir = tempfile::TempDir::new().expect("create temp dir");
+        let todos_file = temp_dir.path().join("TODOs.toml");
+        let clock = test_clock();
+
+        assert!(add_todo(&todos_file, NewTodo::default(), clock.clone()).is_err());
+        let bad_due = NewTodo {
+            title: "x".to_string(),
+            due: Some("someday".to_string()),
+            comment: None,
+        };
+        assert!(add_todo(&todos_file, bad_due, clock).is_err());
+        assert!(!todos_file.exists());
+    }
+
+    #[test]
+    fn concurrent_adds_all_survive_and_archive_each_previous_file() {
+        let temp_dir = tempfile::TempDir::new().expect("create temp dir");
+        let todos_file = temp_dir.path().join("TODOs.toml");
+
+        let mut ids: Vec<String> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..8)
+                .map(|n| {
+                    let todos_file = &todos_file;
+                    scope.spawn(move || {
+                        let new_todo = NewTodo {
+                            title: format!("Capture {n}"),
+                            ..NewTodo::default()
+                        };
+                        add_todo(todos_file, new_todo, test_clock()).unwrap()
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        ids.sort_by_key(|id| crate::store::parse_todo_id(id));
+
+        let expected: Vec<String> = (1..=8).map(|n| format!("T{n}")).collect();
+        assert_eq!(ids, expected);
+        assert_eq!(load_todos(&todos_file).unwrap().len(), 8);
+        let archives = std::fs::read_dir(temp_dir.path())
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with("TODOs_"))
+            .count();
+        assert_eq!(archives, 7);
+    }
+}
@@ -0,0 +1,262 @@
+//! `juggler agenda`: a day-by-day plan of pending todos.
+//!
+//! Overdue todos come first, then one group per calendar day in the window
+//! (Today, Tomorrow, then weekday names), then todos without a due. Urgency
+//! uses the same thresholds as the TUI, and is shown in colour on a terminal.
+
+use chrono::{DateTime, Days, Duration, Utc};
+use chrono_tz::Tz;
+use crossterm::style::Stylize;
+
+use super::export::JsonTodo;
+use crate::due::{Due, today};
+use crate::store::parse_todo_id;
+use crate::ui::{DueDateUrgency, Todo};
+
+const DAY_HEADING_FORMAT: &str = "%A %Y-%m-%d";
+const TIME_FORMAT: &str = "%H:%M";
+
+struct Group<'a> {
+    heading: String,
+    todos: Vec<&'a Todo>,
+}
+
+/// Render the pending todos due within `days` calendar days (starting today)
+/// as a plain-text agenda. `color` adds ANSI styling for terminals, with
+/// todos within `due_soon` of their deadline highlighted.
+pub fn render_agenda(
+    todos: &[Todo],
+    days: u32,
+    now: DateTime<Utc>,
+    tz: Tz,
+    due_soon: Duration,
+    color: bool,
+) -> String {
+    let groups = agenda_groups(todos, days, now, tz);
+    let id_width = groups
+        .iter()
+        .flat_map(|group| &group.todos)
+        .filter_map(|todo| todo.todo_id.as_deref())
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+    let sections: Vec<String> = groups
+        .iter()
+        .filter(|group| !group.todos.is_empty())
+        .map(|group| {
+            let heading = if color {
+                group.heading.clone().bold().to_string()
+            } else {
+                group.heading.clone()
+            };
+            let mut lines = vec![heading];
+            lines.extend(
+                group
+                    .todos
+                    .iter()
+                    .map(|todo| agenda_line(todo, id_width, now, tz, due_soon, color)),
+            );
+            lines.join("\n")
+        })
+        .collect();
+
+    if sections.is_empty() {
+        return format!("Nothing due in the next {days} day(s).");
+    }
+    sections.join("\n\n")
+}
+
+/// The non-empty agenda groups as `[{"heading", "todos": [...]}]`, with todos
+/// in the JSON export shape.
+pub fn agenda_json(todos: &[Todo], days: u32, now: DateTime<Utc>, tz: Tz) -> serde_json::Value {
+    agenda_groups(todos, days, now, tz)
+        .into_iter()
+        .filter(|group| !group.todos.is_empty())
+        .map(|group| {
+            serde_json::json!({
+                "heading": group.heading,
+                "todos": group.todos.into_iter().map(JsonTodo::from).collect::<Vec<_>>(),
+            })
+        })
+        .collect()
+}
+
+/// Pending todos split into the overdue group, one group per day, and the
+/// undated group, each sorted by due then id. Groups may be empty.
+fn agenda_groups(todos: &[Todo], days: u32, now: DateTime<Utc>, tz: Tz) -> Vec<Group<'_>> {
+    let mut pending: Vec<&Todo> = todos.iter().filter(|todo| !todo.done).collect();
+    pending.sort_by_key(|todo| {
+        (
+            todo.due_date.map(|due| due.starts_at(tz)),
+            todo.todo_id.as_deref().and_then(parse_todo_id),
+        )
+    });
+
+    let start = today(now, tz);
+    let mut groups = vec![Group {
+        heading: "Overdue".to_string(),
+        todos: pending
+            .iter()
+            .copied()
+            .filter(|todo| todo.is_overdue(now, tz))
+            .collect(),
+    }];
+    for offset in 0..days {
+        let Some(date) = start.checked_add_days(Days::new(offset.into())) else {
+            break;
+        };
+        let heading = match offset {
+            0 => "Today".to_string(),
+            1 => "Tomorrow".to_string(),
+            _ => date.format(DAY_HEADING_FORMAT).to_string(),
+        };
+        groups.push(Group {
+            heading,
+            todos: pending
+                .iter()
+                .copied()
+                .filter(|todo| {
+                    !todo.is_overdue(now, tz)
+                        && todo.due_date.is_some_and(|due| due.date_in(tz) == date)
+                })
+                .collect(),
+        });
+    }
+    groups.push(Group {
+        heading: "No due date".to_string(),
+        todos: pending
+            .iter()
+            .copied()
+            .filter(|todo| todo.due_date.is_none())
+            .collect(),
+    });
+
+    groups
+}
+
+/// One indented todo line: id, a time column (local time for timed dues,
+/// the relative offset for overdue ones), and the title.
+fn agenda_line(
+    todo: &Todo,
+    id_width: usize,
+    now: DateTime<Utc>,
+    tz: Tz,
+    due_soon: Duration,
+    color: bool,
+) -> String {
+    let id = todo.todo_id.as_deref().unwrap_or_default();
+    let urgency = todo.due_date_urgency(now, tz, due_soon);
+    let when = match (todo.due_date, &urgency) {
+        (Some(_), Some(DueDateUrgency::Overdue)) => todo
+            .format_relative_time(now, tz)
+            .unwrap_or_default()
+            .trim()
+            .to_string(),
+        (Some(Due::At(instant)), _) => instant.with_timezone(&tz).format(TIME_FORMAT).to_string(),
+        _ => String::new(),
+    };
+    let line = format!("  {id:<id_width$}  {when:>5}  {}", todo.title);
+    if !color {
+        return line;
+    }
+    match urgency {
+        Some(DueDateUrgency::Overdue) => line.red().to_string(),
+        Some(DueDateUrgency::DueSoon) => line.yellow().to_string(),
+        _ => line,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::TodoItem;
+    use chrono::{NaiveDate, TimeZone};
+    use indexmap::IndexMap;
+
+    fn todo(id: &str, title: &str, due: Option<Due>, done: bool) -> Todo {
+        Todo::from(TodoItem {
+            todo_id: Some(id.to_string()),
+            title: title.to_string(),
+            comment: None,
+            done,
+            due_date: due,
+            google_task_id: None,
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra: toml::Table::new(),
+        })
+    }
+
+    fn now() -> DateTime<Utc> {
+        // A Tuesday.
+        Utc.with_ymd_and_hms(2025, 1, 7, 12, 0, 0).unwrap()
+    }
+
+    fn date(day: u32) -> Due {
+        Due::Date(NaiveDate::from_ymd_opt(2025, 1, day).unwrap())
+    }
+
+    fn fixture() -> Vec<Todo> {
+        vec![
+            todo(
+                "T1",
+                "Pay rent",
+                Some(Due::At(now() - Duration::hours(3))),
+                false,
+            ),
+            todo(
+                "T2",
+                "Standup",
+                Some(Due::At(now() + Duration::hours(2))),
+                false,
+            ),
+            todo("T3", "Groceries", Some(date(7)), false),
+            todo("T4", "Dentist", Some(date(8)), false),
+            todo("T5", "Review", Some(date(10)), false),
+            todo("T6", "Taxes", Some(date(20)), false),
+            todo("T7", "Read book", None, false),
+            todo("T8", "Shipped", Some(date(7)), true),
+            todo("T9", "Old errand", Some(date(5)), false),
+        ]
+    }
+
+    #[test]
+    fn render_agenda_groups_pending_todos_by_day() {
+        assert_eq!(
+            render_agenda(&fixture(), 7, now(), Tz::UTC, Duration::days(2), false),
+            "Overdue\n\
+             \x20 T9    -2d  Old errand\n\
+             \x20 T1    -3h  Pay rent\n\
+             \n\
+             Today\n\
+             \x20 T3         Groceries\n\
+             \x20 T2  14:00  Standup\n\
+             \n\
+             Tomorrow\n\
+             \x20 T4         Dentist\n\
+             \n\
+             Friday 2025-01-10\n\
+             \x20 T5         Review\n\
+             \n\
+             No due date\n\
+             \x20 T7         Read book"
+        );
+        assert_eq!(
+            render_agenda(&[], 3, now(), Tz::UTC, Duration::days(2), false),
+            "Nothing due in the next 3 day(s)."
+        );
+    }
+
+    #[test]
+    fn render_agenda_colors_by_urgency() {
+        let output = render_agenda(&fixture(), 1, now(), Tz::UTC, Duration::days(2), true);
+        assert!(output.contains(&"Overdue".bold().to_string()));
+        assert!(output.contains(&"  T1    -3h  Pay rent".red().to_string()));
+        assert!(output.contains(&"  T2  14:00  Standup".yellow().to_string()));
+        assert!(output.contains("\n  T7         Read book"));
+    }
+}
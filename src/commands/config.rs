@@ -0,0 +1,114 @@
+//! `juggler config`: read and change `config.toml`.
+//!
+//! `get` and `show` report effective values, so a flag or environment variable
+//! that shadows the file is visible; `set` only ever writes the file. All of
+//! them read the file without validating it, so they work on a config that
+//! stops every other command.
+
+use std::path::Path;
+
+use crate::error::{JugglerError, Result};
+use crate::settings::{ConfigFile, SettingKey, Settings};
+
+const MASKED_SECRET: &str = "********";
+
+/// The effective value of `key`, unmasked so scripts can read it.
+pub fn config_get(settings: &Settings, key: SettingKey) -> Result<String> {
+    settings
+        .get(key)
+        .map(ToString::to_string)
+        .ok_or_else(|| JugglerError::config(format!("'{key}' is not set")))
+}
+
+/// Validate `value`, store it under `key` in `file`, and save that to
+/// `path`. Other settings are not checked, so a bad one can be replaced.
+pub fn config_set(path: &Path, mut file: ConfigFile, key: SettingKey, value: &str) -> Result<()> {
+    file.set(key, value)?;
+    file.save(path)
+}
+
+/// Every setting with its effective value and where it came from. Secrets
+/// are masked.
+pub fn render_config(settings: &Settings) -> String {
+    let key_width = SettingKey::ALL
+        .iter()
+        .map(|key| key.name().len())
+        .max()
+        .unwrap_or_default();
+    settings
+        .entries()
+        .iter()
+        .map(|entry| {
+            let value = match entry.value.as_deref() {
+                None if entry.key == SettingKey::Timezone => "(system timezone)".to_string(),
+                None => "(unset)".to_string(),
+                Some(_) if entry.key.is_secret() => MASKED_SECRET.to_string(),
+                Some(value) => format!("{value:?}"),
+            };
+            format!(
+                "{:<key_width$}  {value}  ({})",
+                entry.key.name(),
+                entry.source
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// `show` for `--output json`: `[{"key", "value", "source"}]`, with secrets
+/// masked and unset values as `null`.
+pub fn config_json(settings: &Settings) -> serde_json::Value {
+    settings
+        .entries()
+        .iter()
+        .map(|entry| {
+            let value = entry.value.as_deref().map(|value| {
+                if entry.key.is_secret() {
+                    MASKED_SECRET
+                } else {
+                    value
+                }
+            });
+            serde_json::json!({
+                "key": entry.key.name(),
+                "value": value,
+                "source": entry.source.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::{Override, SettingSource};
+
+    #[test]
+    fn render_config_lists_sources_and_masks_secrets() {
+        let mut file = ConfigFile::default();
+        file.set(SettingKey::OAuthClientSecret, "hunter2").unwrap();
+        let overrides = [Override {
+            key: SettingKey::Editor,
+            value: "vim".to_string(),
+            source: SettingSource::Env("VISUAL"),
+        }];
+        let settings = Settings::resolve(&file, &overrides);
+
+        assert_eq!(
+            render_config(&settings),
+            "editor                            \"vim\"  (env VISUAL)\n\
+             timezone                          (system timezone)  (default)\n\
+             due_soon                          \"2d\"  (default)\n\
+             status_format                     \"{overdue}! {soon}~ {pending}\"  (default)\n\
+             google_tasks.list_name            \"juggler\"  (default)\n\
+             google_tasks.title_prefix         \"j:\"  (default)\n\
+             google_tasks.oauth_client_id      (unset)  (default)\n\
+             google_tasks.oauth_client_secret  ********  (config.toml)"
+        );
+        assert_eq!(
+            config_get(&settings, SettingKey::OAuthClientSecret).unwrap(),
+            "hunter2"
+        );
+        assert!(config_get(&settings, SettingKey::OAuthClientId).is_err());
+    }
+}
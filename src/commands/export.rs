@@ -0,0 +1,368 @@
+//! `juggler export`: the store in formats other tools and people can read.
+//!
+//! Formats:
+//! - `json`: lossless backup. An object with `format_version`, the
+//!   `custom_fields` schema, and a `todos` array carrying every stored key,
+//!   including unknown ones under `extra`.
+//! - `csv`: one header row, then `id,title,done,due,comment,blocked_by,
+//!   google_task_id` followed by one column per custom field. `blocked_by` is
+//!   space-separated.
+//! - `markdown`: `## Pending` and `## Done` sections of `- [ ]`/`- [x]` items,
+//!   with comments indented under their item.
+//! - `todotxt`: one line per todo, `x ` for done ones, and a `due:YYYY-MM-DD`
+//!   key in the configured timezone.
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use indexmap::IndexMap;
+
+use crate::due::Due;
+use crate::error::{JugglerError, Result};
+use crate::fields::FieldSchema;
+use crate::store::{LogEntry, TodoItem, parse_due_date};
+use crate::ui::Todo;
+
+/// Version of the JSON export document, bumped on incompatible changes.
+pub const JSON_EXPORT_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    #[default]
+    Json,
+    Csv,
+    Markdown,
+    #[value(name = "todotxt")]
+    TodoTxt,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct JsonExport {
+    pub format_version: u32,
+    #[serde(default)]
+    pub custom_fields: serde_json::Value,
+    pub todos: Vec<JsonTodo>,
+}
+
+/// One todo in the JSON export; optional keys are omitted when empty.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct JsonTodo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    #[serde(default)]
+    pub done: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub google_task_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blocked_by: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub log: Vec<LogEntry>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub snooze_count: u32,
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub fields: IndexMap<String, serde_json::Value>,
+    #[serde(default, skip_serializing_if = "toml::Table::is_empty")]
+    pub extra: toml::Table,
+}
+
+impl From<&Todo> for JsonTodo {
+    fn from(todo: &Todo) -> Self {
+        JsonTodo {
+            id: todo.todo_id.clone(),
+            title: todo.title.clone(),
+            comment: todo.comment.clone(),
+            done: todo.done,
+            due: todo.due_date.map(|due| due.to_storage_string()),
+            google_task_id: todo.google_task_id.clone(),
+            blocked_by: todo.blocked_by.clone(),
+            log: todo.log.clone(),
+            created_at: todo.created_at,
+            completed_at: todo.completed_at,
+            snooze_count: todo.snooze_count,
+            fields: todo
+                .fields
+                .iter()
+                .map(|(name, value)| (name.clone(), value.to_json()))
+                .collect(),
+            extra: todo.extra.clone(),
+        }
+    }
+}
+
+impl JsonTodo {
+    /// The stored todo this entry describes, with field values checked
+    /// against `field_schema`.
+    pub fn into_item(self, field_schema: &FieldSchema) -> Result<TodoItem> {
+        let fields: toml::Table = self
+            .fields
+            .into_iter()
+            .map(|(name, value)| {
+                toml::Value::try_from(value)
+                    .map(|value| (name, value))
+                    .map_err(JugglerError::from)
+            })
+            .collect::<Result<_>>()?;
+        Ok(TodoItem {
+            todo_id: self.id,
+            title: self.title,
+            comment: self.comment,
+            done: self.done,
+            due_date: self.due.as_deref().map(parse_due_date).transpose()?,
+            google_task_id: self.google_task_id,
+            blocked_by: self.blocked_by,
+            log: self.log,
+            created_at: self.created_at,
+            completed_at: self.completed_at,
+            snooze_count: self.snooze_count,
+            fields: field_schema.validate_table(&fields)?,
+            extra: self.extra,
+        })
+    }
+}
+
+/// Render `todos` (in store order) as `format`.
+pub fn render_export(
+    todos: &[Todo],
+    field_schema: &FieldSchema,
+    format: ExportFormat,
+    tz: Tz,
+) -> Result<String> {
+    match format {
+        ExportFormat::Json => render_json(todos, field_schema),
+        ExportFormat::Csv => render_csv(todos, field_schema),
+        ExportFormat::Markdown => Ok(render_markdown(todos, tz)),
+        ExportFormat::TodoTxt => Ok(render_todotxt(todos, tz)),
+    }
+}
+
+fn render_json(todos: &[Todo], field_schema: &FieldSchema) -> Result<String> {
+    let document = JsonExport {
+        format_version: JSON_EXPORT_FORMAT_VERSION,
+        custom_fields: serde_json::to_value(field_schema)?,
+        todos: todos.iter().map(JsonTodo::from).collect(),
+    };
+    Ok(serde_json::to_string_pretty(&document)? + "\n")
+}
+
+fn render_csv(todos: &[Todo], field_schema: &FieldSchema) -> Result<String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    let mut header = vec![
+        "id",
+        "title",
+        "done",
+        "due",
+        "comment",
+        "blocked_by",
+        "google_task_id",
+    ];
+    header.extend(field_schema.iter().map(|(name, _)| name));
+    writer.write_record(&header)?;
+
+    for todo in todos {
+        let mut record = vec![
+            todo.todo_id.clone().unwrap_or_default(),
+            todo.title.clone(),
+            todo.done.to_string(),
+            todo.due_date
+                .map(|due| due.to_storage_string())
+                .unwrap_or_default(),
+            todo.comment.clone().unwrap_or_default(),
+            todo.blocked_by.join(" "),
+            todo.google_task_id.clone().unwrap_or_default(),
+        ];
+        record.extend(field_schema.iter().map(|(name, _)| {
+            todo.fields
+                .get(name)
+                .map(ToString::to_string)
+                .unwrap_or_default()
+        }));
+        writer.write_record(&record)?;
+    }
+
+    let bytes = writer
+        .into_inner()
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+fn render_markdown(todos: &[Todo], tz: Tz) -> String {
+    let mut out = String::new();
+    for (heading, done) in [("Pending", false), ("Done", true)] {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&format!("## {heading}\n\n"));
+        let mut any = false;
+        for todo in todos.iter().filter(|todo| todo.done == done) {
+            any = true;
+            let checkbox = if todo.done { "[x]" } else { "[ ]" };
+            let mut details = vec![todo.todo_id.clone().unwrap_or_default()];
+            if let Some(due) = todo.due_date {
+                details.push(format!("due {}", human_due(due, tz)));
+            }
+            out.push_str(&format!(
+                "- {checkbox} {} ({})\n",
+                single_line(&todo.title),
+                details.join(", ")
+            ));
+            if let Some(comment) = todo.comment.as_deref().filter(|c| !c.trim().is_empty()) {
+                for line in comment.lines() {
+                    if line.is_empty() {
+                        out.push('\n');
+                    } else {
+                        out.push_str(&format!("  {line}\n"));
+                    }
+                }
+            }
+        }
+        if !any {
+            out.push_str("_None_\n");
+        }
+    }
+    out
+}
+
+fn render_todotxt(todos: &[Todo], tz: Tz) -> String {
+    todos
+        .iter()
+        .map(|todo| {
+            let mut line = String::new();
+            if todo.done {
+                line.push_str("x ");
+            }
+            line.push_str(&single_line(&todo.title));
+            if let Some(due) = todo.due_date {
+                line.push_str(&format!(" due:{}", due.date_in(tz).format("%Y-%m-%d")));
+            }
+            line.push('\n');
+            line
+        })
+        .collect()
+}
+
+fn human_due(due: Due, tz: Tz) -> String {
+    match due {
+        Due::Date(date) => date.format("%Y-%m-%d").to_string(),
+        Due::At(instant) => instant
+            .with_timezone(&tz)
+            .format("%Y-%m-%d %H:%M")
+            .to_string(),
+    }
+}
+
+fn single_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fields::FieldValue;
+    use crate::store::TodoItem;
+    use chrono::{NaiveDate, TimeZone, Utc};
+
+    fn schema() -> FieldSchema {
+        let table: toml::Value = toml::from_str("estimate = { type = \"number\" }\n").unwrap();
+        FieldSchema::from_toml(Some(&table)).unwrap()
+    }
+
+    fn fixture() -> Vec<Todo> {
+        let mut extra = toml::Table::new();
+        extra.insert("priority".to_string(), toml::Value::Integer(2));
+        let mut fields = IndexMap::new();
+        fields.insert("estimate".to_string(), FieldValue::Number(2.5));
+        vec![
+            Todo::from(TodoItem {
+                todo_id: Some("T1".to_string()),
+                title: "Call, \"dentist\"".to_string(),
+                comment: Some("line 1\nline 2".to_string()),
+                done: false,
+                due_date: Some(Due::At(
+                    Utc.with_ymd_and_hms(2025, 1, 7, 23, 30, 0).unwrap(),
+                )),
+                google_task_id: Some("g1".to_string()),
+                blocked_by: vec!["T2".to_string()],
+                log: vec![LogEntry {
+                    at: Utc.with_ymd_and_hms(2025, 1, 2, 9, 0, 0).unwrap(),
+                    text: "note".to_string(),
+                }],
+                created_at: None,
+                completed_at: None,
+                snooze_count: 0,
+                fields,
+                extra,
+            }),
+            Todo::from(TodoItem {
+                todo_id: Some("T2".to_string()),
+                title: "Renew passport".to_string(),
+                comment: None,
+                done: true,
+                due_date: Some(Due::Date(NaiveDate::from_ymd_opt(2025, 2, 1).unwrap())),
+                google_task_id: None,
+                blocked_by: Vec::new(),
+                log: Vec::new(),
+                created_at: None,
+                completed_at: None,
+                snooze_count: 0,
+                fields: IndexMap::new(),
+                extra: toml::Table::new(),
+            }),
+        ]
+    }
+
+    #[test]
+    fn json_export_keeps_every_stored_key() {
+        let json = render_export(&fixture(), &schema(), ExportFormat::Json, Tz::UTC).unwrap();
+        let document: JsonExport = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(document.format_version, JSON_EXPORT_FORMAT_VERSION);
+        assert_eq!(document.custom_fields["estimate"]["type"], "number");
+        let first = &document.todos[0];
+        assert_eq!(first.id.as_deref(), Some("T1"));
+        assert_eq!(first.due.as_deref(), Some("2025-01-07T23:30:00+00:00"));
+        assert_eq!(first.google_task_id.as_deref(), Some("g1"));
+        assert_eq!(first.blocked_by, vec!["T2"]);
+        assert_eq!(first.log, fixture()[0].log);
+        assert_eq!(first.fields["estimate"], serde_json::json!(2.5));
+        assert_eq!(first.extra["priority"], toml::Value::Integer(2));
+        assert_eq!(document.todos[1].due.as_deref(), Some("2025-02-01"));
+    }
+
+    #[test]
+    fn csv_export_quotes_values_and_adds_field_columns() {
+        let csv = render_export(&fixture(), &schema(), ExportFormat::Csv, Tz::UTC).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("id,title,done,due,comment,blocked_by,google_task_id,estimate")
+        );
+        assert_eq!(
+            lines.next(),
+            Some("T1,\"Call, \"\"dentist\"\"\",false,2025-01-07T23:30:00+00:00,\"line 1")
+        );
+        assert!(csv.contains("line 2\",T2,g1,2.5\n"));
+        assert!(csv.ends_with("T2,Renew passport,true,2025-02-01,,,,\n"));
+    }
+
+    #[test]
+    fn markdown_and_todotxt_use_the_configured_timezone() {
+        let tz: Tz = "Asia/Tokyo".parse().unwrap();
+
+        assert_eq!(
+            render_export(&fixture(), &schema(), ExportFormat::Markdown, tz).unwrap(),
+            "## Pending\n\n- [ ] Call, \"dentist\" (T1, due 2025-01-08 08:30)\n  line 1\n  line 2\n\n## Done\n\n- [x] Renew passport (T2, due 2025-02-01)\n"
+        );
+        assert_eq!(
+            render_export(&fixture(), &schema(), ExportFormat::TodoTxt, tz).unwrap(),
+            "Call, \"dentist\" due:2025-01-08\nx Renew passport due:2025-02-01\n"
+        );
+    }
+}
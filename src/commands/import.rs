@@ -0,0 +1,547 @@
+//! `juggler import`: bring todos over from other tools.
+//!
+//! Each format is parsed into storage-shaped `TodoItem`s without ids. Todos
+//! whose title and due match an existing (or earlier imported) todo are
+//! skipped, the rest get fresh `T<N>` ids from `assign_missing_todo_ids`, and
+//! `blocked_by` references between imported todos are rewritten to those ids.
+//! The store is archived before the merged list is written.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use indexmap::IndexMap;
+use log::warn;
+
+use super::notify_hooks;
+use crate::commands::export::{JSON_EXPORT_FORMAT_VERSION, JsonExport};
+use crate::due::Due;
+use crate::error::{JugglerError, Result};
+use crate::fields::FieldSchema;
+use crate::store::{
+    LogEntry, TodoItem, assign_missing_todo_ids, load_todos_and_schema, lock_store, parse_due_date,
+    store_todos_with_clock, validate_blocked_by,
+};
+use crate::time::SharedClock;
+use crate::ui::Todo;
+
+const TASKWARRIOR_TIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ImportFormat {
+    #[value(name = "todotxt")]
+    TodoTxt,
+    Taskwarrior,
+    Csv,
+    Json,
+}
+
+/// A parsed todo plus the id it had in the source, used to remap `blocked_by`.
+#[derive(Debug, Clone)]
+struct ImportedTodo {
+    source_id: Option<String>,
+    item: TodoItem,
+}
+
+/// What an import did (or, for a dry run, would do).
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    /// Newly added todos with their assigned ids.
+    pub added: Vec<Todo>,
+    /// Titles skipped because a todo with the same title and due exists.
+    pub duplicates: Vec<String>,
+}
+
+/// Import `input` in `format` into the store at `todos_file`.
+pub fn import_todos(
+    todos_file: &Path,
+    format: ImportFormat,
+    input: &str,
+    dry_run: bool,
+    clock: SharedClock,
+) -> Result<ImportReport> {
+    let _lock = (!dry_run).then(|| lock_store(todos_file)).transpose()?;
+    let (mut todos, field_schema) = load_todos_and_schema(todos_file)?;
+    let imported = parse_import(format, input, &field_schema)?;
+    let existing_count = todos.len();
+    let duplicates = merge_imported(&mut todos, imported, clock.now())?;
+
+    if !dry_run {
+        store_todos_with_clock(&mut todos, todos_file, clock)?;
+        notify_hooks(todos_file, &todos[..existing_count], &todos);
+    }
+    Ok(ImportReport {
+        added: todos.split_off(existing_count),
+        duplicates,
+    })
+}
+
+fn parse_import(
+    format: ImportFormat,
+    input: &str,
+    field_schema: &FieldSchema,
+) -> Result<Vec<ImportedTodo>> {
+    match format {
+        ImportFormat::TodoTxt => Ok(parse_todotxt(input)),
+        ImportFormat::Taskwarrior => parse_taskwarrior(input),
+        ImportFormat::Csv => parse_csv(input, field_schema),
+        ImportFormat::Json => parse_json(input, field_schema),
+    }
+}
+
+/// Append the non-duplicate `imported` todos to `todos` with fresh ids and
+/// return the titles of the skipped duplicates. Todos whose source has no
+/// creation time count as created `now`.
+fn merge_imported(
+    todos: &mut Vec<Todo>,
+    imported: Vec<ImportedTodo>,
+    now: DateTime<Utc>,
+) -> Result<Vec<String>> {
+    let dedupe_key = |title: &str, due: Option<Due>| {
+        (
+            title.trim().to_lowercase(),
+            due.map(|due| due.to_storage_string()),
+        )
+    };
+    // Index into `todos` of the todo each title/due pair resolves to.
+    let mut seen: HashMap<(String, Option<String>), usize> = todos
+        .iter()
+        .enumerate()
+        .map(|(index, todo)| (dedupe_key(&todo.title, todo.due_date), index))
+        .collect();
+    let linked_google_ids: HashSet<String> = todos
+        .iter()
+        .filter_map(|todo| todo.google_task_id.clone())
+        .collect();
+
+    let first_new = todos.len();
+    let mut duplicates = Vec::new();
+    let mut source_targets: Vec<(String, usize)> = Vec::new();
+    for ImportedTodo {
+        source_id,
+        mut item,
+    } in imported
+    {
+        let key = dedupe_key(&item.title, item.due_date);
+        if let Some(&target) = seen.get(&key) {
+            if let Some(source_id) = source_id {
+                source_targets.push((source_id, target));
+            }
+            duplicates.push(item.title);
+            continue;
+        }
+        if item
+            .google_task_id
+            .as_ref()
+            .is_some_and(|google_id| linked_google_ids.contains(google_id))
+        {
+            item.google_task_id = None;
+        }
+        item.created_at.get_or_insert(now);
+        seen.insert(key, todos.len());
+        if let Some(source_id) = source_id {
+            source_targets.push((source_id, todos.len()));
+        }
+        todos.push(Todo::from(item));
+    }
+
+    assign_missing_todo_ids(todos)?;
+
+    let id_map: HashMap<String, String> = source_targets
+        .into_iter()
+        .filter_map(|(source_id, target)| {
+            todos[target]
+                .todo_id
+                .clone()
+                .map(|todo_id| (source_id, todo_id))
+        })
+        .collect();
+    for todo in &mut todos[first_new..] {
+        let title = todo.title.clone();
+        todo.blocked_by = std::mem::take(&mut todo.blocked_by)
+            .into_iter()
+            .filter_map(|blocker| {
+                let mapped = id_map.get(&blocker).cloned();
+                if mapped.is_none() {
+                    warn!("Dropping blocked_by '{blocker}' on imported todo '{title}': not part of the import");
+                }
+                mapped
+            })
+            .collect();
+    }
+
+    validate_blocked_by(todos.iter().map(|todo| {
+        (
+            todo.todo_id.as_deref().unwrap_or_default(),
+            todo.blocked_by.as_slice(),
+        )
+    }))?;
+    Ok(duplicates)
+}
+
+fn new_item(title: String) -> TodoItem {
+    TodoItem {
+        todo_id: None,
+        title,
+        comment: None,
+        done: false,
+        due_date: None,
+        google_task_id: None,
+        blocked_by: Vec::new(),
+        log: Vec::new(),
+        created_at: None,
+        completed_at: None,
+        snooze_count: 0,
+        fields: IndexMap::new(),
+        extra: toml::Table::new(),
+    }
+}
+
+/// todo.txt: `x` marks done, a leading `(A)` priority and creation/completion
+/// dates are dropped, and `due:YYYY-MM-DD` becomes an all-day due. Projects,
+/// contexts, and other `key:value` tags stay in the title.
+fn parse_todotxt(input: &str) -> Vec<ImportedTodo> {
+    input
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let mut tokens = line.split_whitespace().peekable();
+            let done = tokens.next_if_eq(&"x").is_some();
+            tokens.next_if(|token| is_todotxt_priority(token));
+            while tokens.next_if(|token| is_todotxt_date(token)).is_some() {}
+
+            let mut due_date = None;
+            let mut words = Vec::new();
+            for token in tokens {
+                match token
+                    .strip_prefix("due:")
+                    .and_then(|raw| NaiveDate::parse_from_str(raw, "%Y-%m-%d").ok())
+                {
+                    Some(date) => due_date = Some(Due::Date(date)),
+                    None => words.push(token),
+                }
+            }
+
+            let mut item = new_item(words.join(" "));
+            item.done = done;
+            item.due_date = due_date;
+            ImportedTodo {
+                source_id: None,
+                item,
+            }
+        })
+        .filter(|imported| !imported.item.title.is_empty())
+        .collect()
+}
+
+fn is_todotxt_priority(token: &str) -> bool {
+    let bytes = token.as_bytes();
+    bytes.len() == 3 && bytes[0] == b'(' && bytes[1].is_ascii_uppercase() && bytes[2] == b')'
+}
+
+fn is_todotxt_date(token: &str) -> bool {
+    NaiveDate::parse_from_str(token, "%Y-%m-%d").is_ok()
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct TaskwarriorTask {
+    description: String,
+    #[serde(default)]
+    status: String,
+    due: Option<String>,
+    entry: Option<String>,
+    end: Option<String>,
+    #[serde(default)]
+    annotations: Vec<TaskwarriorAnnotation>,
+    project: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct TaskwarriorAnnotation {
+    entry: String,
+    description: String,
+}
+
+/// Taskwarrior `task export` output: deleted tasks are skipped, `entry`/`end`
+/// become the creation and completion times, annotations become log entries,
+/// and `project`/`tags` are kept as extra keys.
+fn parse_taskwarrior(input: &str) -> Result<Vec<ImportedTodo>> {
+    let tasks: Vec<TaskwarriorTask> = serde_json::from_str(input)?;
+    tasks
+        .into_iter()
+        .filter(|task| task.status != "deleted")
+        .map(|task| {
+            let mut item = new_item(task.description);
+            item.done = task.status == "completed";
+            item.due_date = task
+                .due
+                .as_deref()
+                .map(|raw| parse_taskwarrior_time(raw).map(Due::At))
+                .transpose()?;
+            item.created_at = task
+                .entry
+                .as_deref()
+                .map(parse_taskwarrior_time)
+                .transpose()?;
+            if item.done {
+                item.completed_at = task
+                    .end
+                    .as_deref()
+                    .map(parse_taskwarrior_time)
+                    .transpose()?;
+            }
+            item.log = task
+                .annotations
+                .into_iter()
+                .map(|annotation| {
+                    Ok(LogEntry {
+                        at: parse_taskwarrior_time(&annotation.entry)?,
+                        text: annotation.description,
+                    })
+                })
+                .collect::<Result<_>>()?;
+            if let Some(project) = task.project {
+                item.extra
+                    .insert("project".to_string(), toml::Value::String(project));
+            }
+            if !task.tags.is_empty() {
+                item.extra.insert(
+                    "tags".to_string(),
+                    toml::Value::Array(task.tags.into_iter().map(toml::Value::String).collect()),
+                );
+            }
+            Ok(ImportedTodo {
+                source_id: None,
+                item,
+            })
+        })
+        .collect()
+}
+
+fn parse_taskwarrior_time(raw: &str) -> Result<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(raw, TASKWARRIOR_TIME_FORMAT)
+        .map(|naive| naive.and_utc())
+        .map_err(|e| JugglerError::config(format!("Invalid Taskwarrior timestamp '{raw}': {e}")))
+}
+
+/// CSV with a header row as written by `juggler export --format csv`. Only
+/// `title` is required; columns naming custom fields are parsed with the
+/// store's schema and any other column is ignored.
+fn parse_csv(input: &str, field_schema: &FieldSchema) -> Result<Vec<ImportedTodo>> {
+    let mut reader = csv::Reader::from_reader(input.as_bytes());
+    let headers: Vec<String> = reader
+        .headers()?
+        .iter()
+        .map(|header| header.trim().to_lowercase())
+        .collect();
+    if !headers.iter().any(|header| header == "title") {
+        return Err(JugglerError::config("CSV import needs a 'title' column"));
+    }
+    for header in &headers {
+        let known = matches!(
+            header.as_str(),
+            "id" | "title" | "done" | "due" | "comment" | "blocked_by" | "google_task_id"
+        ) || field_schema.get(header).is_some();
+        if !known {
+            warn!("Ignoring CSV column '{header}'");
+        }
+    }
+
+    let mut imported = Vec::new();
+    for (row, record) in reader.records().enumerate() {
+        let record = record?;
+        let line = row + 2;
+        let mut source_id = None;
+        let mut item = new_item(String::new());
+        for (header, value) in headers.iter().zip(record.iter()) {
+            let value = value.trim();
+            if value.is_empty() {
+                continue;
+            }
+            match header.as_str() {
+                "id" => source_id = Some(value.to_string()),
+                "title" => item.title = value.to_string(),
+                "done" => item.done = parse_csv_bool(value, line)?,
+                "due" => item.due_date = Some(parse_due_date(value)?),
+                "comment" => item.comment = Some(value.to_string()),
+                "blocked_by" => {
+                    item.blocked_by = value.split_whitespace().map(str::to_string).collect()
+                }
+                "google_task_id" => item.google_task_id = Some(value.to_string()),
+                name if field_schema.get(name).is_some() => {
+                    let parsed = field_schema.parse_input(name, value)?;
+                    item.fields.insert(name.to_string(), parsed);
+                }
+                _ => {}
+            }
+        }
+        if item.title.is_empty() {
+            return Err(JugglerError::config(format!(
+                "CSV line {line} has an empty title"
+            )));
+        }
+        imported.push(ImportedTodo { source_id, item });
+    }
+    Ok(imported)
+}
+
+fn parse_csv_bool(value: &str, line: usize) -> Result<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "true" | "yes" | "1" | "x" => Ok(true),
+        "false" | "no" | "0" => Ok(false),
+        _ => Err(JugglerError::config(format!(
+            "CSV line {line}: invalid done value '{value}'"
+        ))),
+    }
+}
+
+/// A `juggler export --format json` document; every stored key is restored.
+fn parse_json(input: &str, field_schema: &FieldSchema) -> Result<Vec<ImportedTodo>> {
+    let document: JsonExport = serde_json::from_str(input)?;
+    if document.format_version != JSON_EXPORT_FORMAT_VERSION {
+        return Err(JugglerError::config(format!(
+            "Unsupported JSON export format_version={} (expected {JSON_EXPORT_FORMAT_VERSION})",
+            document.format_version
+        )));
+    }
+
+    document
+        .todos
+        .into_iter()
+        .map(|todo| {
+            let source_id = todo.id.clone();
+            let mut item = todo.into_item(field_schema)?;
+            item.todo_id = None;
+            Ok(ImportedTodo { source_id, item })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::add::{NewTodo, add_todo};
+    use crate::commands::export::{ExportFormat, render_export};
+    use crate::store::load_todos;
+    use crate::time::test_clock;
+    use chrono::TimeZone;
+    use chrono_tz::Tz;
+
+    fn titles(todos: &[Todo]) -> Vec<&str> {
+        todos.iter().map(|todo| todo.title.as_str()).collect()
+    }
+
+    #[test]
+    fn todotxt_lines_map_done_priority_dates_and_due() {
+        let imported = parse_todotxt(
+            "(A) 2025-01-01 Call mom +family due:2025-01-09\nx 2025-01-03 2025-01-01 Pay rent\n\n",
+        );
+
+        assert_eq!(imported.len(), 2);
+        assert_eq!(imported[0].item.title, "Call mom +family");
+        assert_eq!(
+            imported[0].item.due_date,
+            Some(Due::Date(NaiveDate::from_ymd_opt(2025, 1, 9).unwrap()))
+        );
+        assert!(!imported[0].item.done);
+        assert_eq!(imported[1].item.title, "Pay rent");
+        assert!(imported[1].item.done);
+    }
+
+    #[test]
+    fn taskwarrior_export_maps_status_due_and_annotations() {
+        let imported = parse_taskwarrior(
+            r#"[
+  {"uuid": "a", "description": "Write report", "status": "pending",
+   "due": "20250107T090000Z", "project": "work", "tags": ["q1"],
+   "annotations": [{"entry": "20250102T080000Z", "description": "draft sent"}]},
+  {"uuid": "b", "description": "Old", "status": "deleted"},
+  {"uuid": "c", "description": "Shipped", "status": "completed"}
+]"#,
+        )
+        .unwrap();
+
+        assert_eq!(imported.len(), 2);
+        let report = &imported[0].item;
+        assert_eq!(
+            report.due_date,
+            Some(Due::At(Utc.with_ymd_and_hms(2025, 1, 7, 9, 0, 0).unwrap()))
+        );
+        assert_eq!(report.log[0].text, "draft sent");
+        assert_eq!(report.extra["project"].as_str(), Some("work"));
+        assert!(imported[1].item.done);
+    }
+
+    #[test]
+    fn import_skips_duplicates_remaps_blockers_and_honors_dry_run() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let todos_file = temp_dir.path().join("TODOs.toml");
+        add_todo(
+            &todos_file,
+            NewTodo {
+                title: "Existing".to_string(),
+                ..NewTodo::default()
+            },
+            test_clock(),
+        )
+        .unwrap();
+        let csv = "id,title,due,blocked_by\n\
+                   X1,existing,,\n\
+                   X2,Second,2025-02-01,X1 X3\n\
+                   X3,Third,,\n\
+                   X4,Third,,\n";
+
+        let dry = import_todos(&todos_file, ImportFormat::Csv, csv, true, test_clock()).unwrap();
+        assert_eq!(titles(&dry.added), vec!["Second", "Third"]);
+        assert_eq!(dry.duplicates, vec!["existing", "Third"]);
+        assert_eq!(load_todos(&todos_file).unwrap().len(), 1);
+
+        let report =
+            import_todos(&todos_file, ImportFormat::Csv, csv, false, test_clock()).unwrap();
+        assert_eq!(report.added[0].todo_id.as_deref(), Some("T2"));
+        assert_eq!(report.added[0].blocked_by, vec!["T1", "T3"]);
+
+        let todos = load_todos(&todos_file).unwrap();
+        assert_eq!(titles(&todos), vec!["Existing", "Second", "Third"]);
+        let archives = std::fs::read_dir(temp_dir.path()).unwrap().count();
+        assert!(archives > 1, "previous store is archived before writing");
+    }
+
+    #[test]
+    fn json_export_round_trips_through_import() {
+        let source_dir = tempfile::TempDir::new().unwrap();
+        let source = source_dir.path().join("TODOs.toml");
+        for title in ["a", "b"] {
+            add_todo(
+                &source,
+                NewTodo {
+                    title: title.to_string(),
+                    due: Some("2025-03-01".to_string()),
+                    comment: Some("note".to_string()),
+                },
+                test_clock(),
+            )
+            .unwrap();
+        }
+        let mut todos = load_todos(&source).unwrap();
+        todos[1].blocked_by = vec!["T1".to_string()];
+        todos[1]
+            .extra
+            .insert("priority".to_string(), toml::Value::Integer(1));
+        let json =
+            render_export(&todos, &FieldSchema::default(), ExportFormat::Json, Tz::UTC).unwrap();
+
+        let target_dir = tempfile::TempDir::new().unwrap();
+        let target = target_dir.path().join("TODOs.toml");
+        import_todos(&target, ImportFormat::Json, &json, false, test_clock()).unwrap();
+
+        let restored = load_todos(&target).unwrap();
+        assert_eq!(restored.len(), 2);
+        assert_eq!(restored[1].blocked_by, vec!["T1"]);
+        assert_eq!(restored[1].comment.as_deref(), Some("note"));
+        assert_eq!(restored[1].due_date, todos[1].due_date);
+        assert_eq!(restored[1].extra["priority"], toml::Value::Integer(1));
+    }
+}
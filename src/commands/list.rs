@@ -0,0 +1,345 @@
+//! `juggler list`: filtered, sorted, machine-readable views of the store.
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
+
+use crate::error::{JugglerError, Result};
+use crate::query::{Query, blocked_ids};
+use crate::store::parse_todo_id;
+use crate::ui::{Todo, parse_relative_duration};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ListFormat {
+    #[default]
+    Table,
+    Json,
+    Tsv,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum SortKey {
+    /// Earliest due first; todos without a due come last.
+    #[default]
+    Due,
+    Id,
+    Title,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ListOptions {
+    /// Include completed todos alongside pending ones.
+    pub include_done: bool,
+    pub overdue: bool,
+    pub due_within: Option<Duration>,
+    /// Case-insensitive substring matched against title and comment.
+    pub grep: Option<String>,
+    /// A `--where` query; when given it decides whether done todos show.
+    pub query: Option<Query>,
+    pub sort: SortKey,
+}
+
+impl ListOptions {
+    fn matches(&self, todo: &Todo, blocked: &HashSet<String>, now: DateTime<Utc>, tz: Tz) -> bool {
+        if todo.done && !self.include_done && self.query.is_none() {
+            return false;
+        }
+        if let Some(query) = &self.query
+            && !query.matches(todo, blocked)
+        {
+            return false;
+        }
+        if self.overdue && !todo.is_overdue(now, tz) {
+            return false;
+        }
+        if let Some(window) = self.due_within {
+            let horizon = now + window;
+            if todo.due_date.is_none_or(|due| due.starts_at(tz) > horizon) {
+                return false;
+            }
+        }
+        if let Some(pattern) = &self.grep {
+            let pattern = pattern.to_lowercase();
+            let in_title = todo.title.to_lowercase().contains(&pattern);
+            let in_comment = todo
+                .comment
+                .as_deref()
+                .is_some_and(|comment| comment.to_lowercase().contains(&pattern));
+            if !in_title && !in_comment {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Parse a `--due-within` window in the relative duration syntax (`2d`, `12h`).
+pub fn parse_window(input: &str) -> Result<Duration> {
+    parse_relative_duration(input).ok_or_else(|| {
+        JugglerError::config(format!(
+            "Invalid duration '{input}' (expected e.g. 2d, 12h, 30m)"
+        ))
+    })
+}
+
+/// Keep the todos matching `options`, ordered by `options.sort`.
+pub fn select_todos(
+    todos: Vec<Todo>,
+    options: &ListOptions,
+    now: DateTime<Utc>,
+    tz: Tz,
+) -> Vec<Todo> {
+    let blocked = blocked_ids(&todos);
+    let mut selected: Vec<Todo> = todos
+        .into_iter()
+        .filter(|todo| options.matches(todo, &blocked, now, tz))
+        .collect();
+    let id_number = |todo: &Todo| todo.todo_id.as_deref().and_then(parse_todo_id);
+    match options.sort {
+        SortKey::Due => selected.sort_by_key(|todo| {
+            (
+                todo.due_date.is_none(),
+                todo.due_date.map(|due| due.starts_at(tz)),
+                id_number(todo),
+            )
+        }),
+        SortKey::Id => selected.sort_by_key(id_number),
+        SortKey::Title => selected.sort_by_key(|todo| (todo.title.to_lowercase(), id_number(todo))),
+    }
+    selected
+}
+
+/// One todo as `list --format json` and `--output json` print it.
+#[derive(Debug, serde::Serialize)]
+pub struct ListEntry<'a> {
+    id: &'a str,
+    title: &'a str,
+    done: bool,
+    due: Option<String>,
+    due_relative: Option<String>,
+    overdue: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    comment: Option<&'a str>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    blocked_by: &'a [String],
+}
+
+fn relative_due(todo: &Todo, now: DateTime<Utc>, tz: Tz) -> Option<String> {
+    todo.format_relative_time(now, tz)
+        .map(|text| text.trim().to_string())
+}
+
+pub fn list_entries(todos: &[Todo], now: DateTime<Utc>, tz: Tz) -> Vec<ListEntry<'_>> {
+    todos
+        .iter()
+        .map(|todo| ListEntry {
+            id: todo.todo_id.as_deref().unwrap_or_default(),
+            title: &todo.title,
+            done: todo.done,
+            due: todo.due_date.map(|due| due.to_storage_string()),
+            due_relative: relative_due(todo, now, tz),
+            overdue: todo.is_overdue(now, tz),
+            comment: todo.comment.as_deref(),
+            blocked_by: &todo.blocked_by,
+        })
+        .collect()
+}
+
+/// Render `todos` in the requested output format, one todo per line (or one
+/// JSON array).
+pub fn render_list(
+    todos: &[Todo],
+    format: ListFormat,
+    now: DateTime<Utc>,
+    tz: Tz,
+) -> Result<String> {
+    let id = |todo: &Todo| todo.todo_id.clone().unwrap_or_default();
+    match format {
+        ListFormat::Json => Ok(serde_json::to_string_pretty(&list_entries(todos, now, tz))?),
+        ListFormat::Tsv => Ok(todos
+            .iter()
+            .map(|todo| {
+                format!(
+                    "{}\t{}\t{}\t{}",
+                    id(todo),
+                    relative_due(todo, now, tz).unwrap_or_default(),
+                    if todo.done { "done" } else { "pending" },
+                    tsv_field(&todo.title)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")),
+        ListFormat::Table => {
+            let id_width = todos
+                .iter()
+                .map(|todo| id(todo).len())
+                .chain(std::iter::once("ID".len()))
+                .max()
+                .unwrap_or_default();
+            let mut lines = vec![format!("{:<id_width$}  {:>4}  TITLE", "ID", "DUE")];
+            for todo in todos {
+                let due = todo.format_relative_time(now, tz).unwrap_or_default();
+                let marker = if todo.done { "✓ " } else { "" };
+                lines.push(format!(
+                    "{:<id_width$}  {due:>4}  {marker}{}",
+                    id(todo),
+                    todo.title
+                ));
+            }
+            Ok(lines.join("\n"))
+        }
+    }
+}
+
+/// TSV has no quoting, so tabs and newlines inside a value become spaces.
+fn tsv_field(value: &str) -> String {
+    value.replace(['\t', '\n', '\r'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::due::Due;
+    use crate::store::TodoItem;
+    use chrono::TimeZone;
+    use indexmap::IndexMap;
+
+    fn todo(id: &str, title: &str, due: Option<Due>, done: bool) -> Todo {
+        Todo::from(TodoItem {
+            todo_id: Some(id.to_string()),
+            title: title.to_string(),
+            comment: None,
+            done,
+            due_date: due,
+            google_task_id: None,
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra: toml::Table::new(),
+        })
+    }
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 1, 7, 12, 0, 0).unwrap()
+    }
+
+    fn fixture() -> Vec<Todo> {
+        vec![
+            todo(
+                "T1",
+                "Deploy api",
+                Some(Due::At(now() + Duration::days(5))),
+                false,
+            ),
+            todo(
+                "T2",
+                "Pay rent",
+                Some(Due::At(now() - Duration::hours(3))),
+                false,
+            ),
+            todo("T3", "Read book", None, false),
+            todo(
+                "T4",
+                "Deploy web",
+                Some(Due::At(now() + Duration::hours(20))),
+                true,
+            ),
+        ]
+    }
+
+    fn ids(todos: &[Todo]) -> Vec<&str> {
+        todos
+            .iter()
+            .map(|todo| todo.todo_id.as_deref().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn select_todos_filters_and_sorts() {
+        let default = select_todos(fixture(), &ListOptions::default(), now(), Tz::UTC);
+        assert_eq!(ids(&default), vec!["T2", "T1", "T3"]);
+
+        let overdue = ListOptions {
+            overdue: true,
+            ..ListOptions::default()
+        };
+        assert_eq!(
+            ids(&select_todos(fixture(), &overdue, now(), Tz::UTC)),
+            vec!["T2"]
+        );
+
+        let within = ListOptions {
+            include_done: true,
+            due_within: Some(parse_window("2d").unwrap()),
+            ..ListOptions::default()
+        };
+        assert_eq!(
+            ids(&select_todos(fixture(), &within, now(), Tz::UTC)),
+            vec!["T2", "T4"]
+        );
+
+        let grep = ListOptions {
+            include_done: true,
+            grep: Some("DEPLOY".to_string()),
+            sort: SortKey::Title,
+            ..ListOptions::default()
+        };
+        assert_eq!(
+            ids(&select_todos(fixture(), &grep, now(), Tz::UTC)),
+            vec!["T1", "T4"]
+        );
+    }
+
+    #[test]
+    fn where_query_decides_whether_done_todos_show() {
+        let query = |input: &str| ListOptions {
+            query: Some(Query::parse(input, &Default::default(), now(), Tz::UTC).unwrap()),
+            ..ListOptions::default()
+        };
+
+        assert_eq!(
+            ids(&select_todos(
+                fixture(),
+                &query("\"deploy\" in title"),
+                now(),
+                Tz::UTC
+            )),
+            vec!["T4", "T1"]
+        );
+        assert_eq!(
+            ids(&select_todos(
+                fixture(),
+                &query("due < 1d and not done"),
+                now(),
+                Tz::UTC
+            )),
+            vec!["T2"]
+        );
+    }
+
+    #[test]
+    fn render_list_formats_table_tsv_and_json() {
+        let todos = select_todos(fixture(), &ListOptions::default(), now(), Tz::UTC);
+
+        assert_eq!(
+            render_list(&todos, ListFormat::Table, now(), Tz::UTC).unwrap(),
+            "ID   DUE  TITLE\nT2   -3h  Pay rent\nT1    5d  Deploy api\nT3        Read book"
+        );
+        assert_eq!(
+            render_list(&todos, ListFormat::Tsv, now(), Tz::UTC).unwrap(),
+            "T2\t-3h\tpending\tPay rent\nT1\t5d\tpending\tDeploy api\nT3\t\tpending\tRead book"
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&render_list(&todos, ListFormat::Json, now(), Tz::UTC).unwrap())
+                .unwrap();
+        assert_eq!(json[0]["id"], "T2");
+        assert_eq!(json[0]["overdue"], true);
+        assert_eq!(json[0]["due_relative"], "-3h");
+        assert_eq!(json[2]["due"], serde_json::Value::Null);
+    }
+}
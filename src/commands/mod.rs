@@ -0,0 +1,34 @@
+//! Non-interactive subcommands that operate on the TODO store.
+//!
+//! Each command loads the store, applies its change, and saves through
+//! `store_todos` so the CLI gets the same atomic-write and archive behavior as
+//! the TUI. Writing commands hold `store::lock_store` from load to save.
+//! After a successful save they run the `on-add`/`on-done`/`on-modify` hooks
+//! for whatever changed. Output formatting stays in `main`; these functions
+//! return data.
+
+pub mod add;
+pub mod agenda;
+pub mod config;
+pub mod export;
+pub mod import;
+pub mod list;
+pub mod mutate;
+pub mod session;
+pub mod status;
+pub mod sync;
+
+use std::path::Path;
+
+use log::warn;
+
+use crate::hooks::Hooks;
+use crate::ui::Todo;
+
+/// Run the lifecycle hooks for a change that is already saved, so a failing
+/// hook is only reported.
+fn notify_hooks(todos_file: &Path, before: &[Todo], after: &[Todo]) {
+    if let Err(e) = Hooks::for_todos_file(todos_file).notify_changes(before, after) {
+        warn!("{e}");
+    }
+}
@@ -0,0 +1,441 @@
+//! Id-addressed mutations: `done`, `undone`, `snooze`, `due`, `edit`, `rm`.
+//!
+//! All but `rm` go through the TUI reducer via `ui::apply_by_id`/`edit_by_id`.
+//! Every id is checked before anything is written, so a typo leaves the store
+//! untouched and the command exits non-zero. Instead of ids, the bulk commands
+//! accept a `--where` query, matched against the store under its lock.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use chrono_tz::Tz;
+
+use super::export::JsonTodo;
+use super::notify_hooks;
+use crate::due::parse_due_argument;
+use crate::error::{JugglerError, Result};
+use crate::query::{blocked_ids, parse_query};
+use crate::store::{load_todos, load_todos_and_schema, lock_store, store_todos_with_clock};
+use crate::time::SharedClock;
+use crate::ui::{BatchOp, Todo, TodoEditor, apply_by_id, edit_by_id, parse_relative_duration};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// The todos a bulk command applies to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Targets {
+    Ids(Vec<String>),
+    /// Every todo matching a `--where` query.
+    Matching(String),
+}
+
+impl Targets {
+    /// Listed ids, or every todo matching `query` when one is given.
+    pub fn new(ids: Vec<String>, query: Option<String>) -> Self {
+        match query {
+            Some(query) => Targets::Matching(query),
+            None => Targets::Ids(ids),
+        }
+    }
+
+    /// Load the store and resolve the targets against it.
+    fn load(
+        &self,
+        todos_file: &Path,
+        clock: &SharedClock,
+        tz: Tz,
+    ) -> Result<(Vec<Todo>, Vec<String>)> {
+        let (todos, field_schema) = load_todos_and_schema(todos_file)?;
+        let ids = match self {
+            Targets::Ids(ids) => {
+                ensure_known_ids(&todos, ids)?;
+                ids.clone()
+            }
+            Targets::Matching(query) => {
+                let query = parse_query(query, &field_schema, clock.now(), tz)?;
+                let blocked = blocked_ids(&todos);
+                todos
+                    .iter()
+                    .filter(|todo| query.matches(todo, &blocked))
+                    .filter_map(|todo| todo.todo_id.clone())
+                    .collect()
+            }
+        };
+        Ok((todos, ids))
+    }
+}
+
+/// What a bulk command changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mutation {
+    pub ids: Vec<String>,
+    /// The reducer's status message, if any.
+    pub message: Option<String>,
+}
+
+const NO_MATCHES: &str = "No todos match the query";
+
+/// Apply `op` to `targets` and save. A query matching nothing writes nothing.
+pub fn mutate_todos(
+    todos_file: &Path,
+    targets: &Targets,
+    op: BatchOp,
+    clock: SharedClock,
+    tz: Tz,
+) -> Result<Mutation> {
+    let _lock = lock_store(todos_file)?;
+    let (todos, ids) = targets.load(todos_file, &clock, tz)?;
+    if ids.is_empty() {
+        return Ok(Mutation {
+            ids,
+            message: Some(NO_MATCHES.to_string()),
+        });
+    }
+    let before = todos.clone();
+    let mut outcome = apply_by_id(todos, &ids, op, clock.now(), tz)?;
+    store_todos_with_clock(&mut outcome.todos, todos_file, clock)?;
+    notify_hooks(todos_file, &before, &outcome.todos);
+    Ok(Mutation {
+        ids,
+        message: outcome.message,
+    })
+}
+
+/// Edit one todo in `editor` and save the result. The editor runs without
+/// the store lock, so other writers are not held up while it is open; the
+/// edit is then applied to the store as it is now, unless the todo was
+/// changed or deleted in the meantime.
+pub fn edit_todo(
+    todos_file: &Path,
+    todo_id: &str,
+    editor: &dyn TodoEditor,
+    clock: SharedClock,
+    tz: Tz,
+) -> Result<Option<String>> {
+    let original = load_todos(todos_file)?
+        .into_iter()
+        .find(|todo| todo.todo_id.as_deref() == Some(todo_id))
+        .ok_or_else(|| JugglerError::not_found(format!("Unknown todo id '{todo_id}'")))?;
+    let edited = editor.edit_todo(&original)?;
+
+    let _lock = lock_store(todos_file)?;
+    let todos = load_todos(todos_file)?;
+    match todos
+        .iter()
+        .find(|todo| todo.todo_id.as_deref() == Some(todo_id))
+    {
+        None => {
+            return Err(JugglerError::Other(format!(
+                "{todo_id} was deleted by another juggler process while it was being edited"
+            )));
+        }
+        Some(current) if JsonTodo::from(current) != JsonTodo::from(&original) => {
+            return Err(JugglerError::Other(format!(
+                "{todo_id} was changed by another juggler process while it was being edited"
+            )));
+        }
+        Some(_) => {}
+    }
+    let before = todos.clone();
+    let mut outcome = edit_by_id(todos, todo_id, &EditedTodo(edited), clock.now(), tz)?;
+    store_todos_with_clock(&mut outcome.todos, todos_file, clock)?;
+    notify_hooks(todos_file, &before, &outcome.todos);
+    Ok(outcome.message)
+}
+
+/// Hands the reducer an edit that was made before the store was locked.
+struct EditedTodo(Todo);
+
+impl TodoEditor for EditedTodo {
+    fn edit_todo(&self, _todo: &Todo) -> Result<Todo> {
+        Ok(self.0.clone())
+    }
+
+    fn needs_terminal_restoration(&self) -> bool {
+        false
+    }
+}
+
+/// Delete `targets` and drop them from every remaining `blocked_by` list.
+/// Returns the deleted ids.
+pub fn remove_todos(
+    todos_file: &Path,
+    targets: &Targets,
+    clock: SharedClock,
+    tz: Tz,
+) -> Result<Vec<String>> {
+    let _lock = lock_store(todos_file)?;
+    let (todos, ids) = targets.load(todos_file, &clock, tz)?;
+    if ids.is_empty() {
+        return Ok(ids);
+    }
+
+    let before = todos.clone();
+    let mut remaining: Vec<Todo> = todos
+        .into_iter()
+        .filter(|todo| {
+            todo.todo_id
+                .as_ref()
+                .is_none_or(|todo_id| !ids.contains(todo_id))
+        })
+        .collect();
+    for todo in &mut remaining {
+        todo.blocked_by.retain(|blocker| !ids.contains(blocker));
+    }
+    store_todos_with_clock(&mut remaining, todos_file, clock)?;
+    notify_hooks(todos_file, &before, &remaining);
+    Ok(ids)
+}
+
+/// Parse a snooze amount, which must be a whole number of days (`1d`, `-2d`).
+pub fn parse_snooze_days(input: &str) -> Result<i64> {
+    parse_relative_duration(input)
+        .map(|duration| duration.num_seconds())
+        .filter(|seconds| seconds % SECONDS_PER_DAY == 0)
+        .map(|seconds| seconds / SECONDS_PER_DAY)
+        .ok_or_else(|| {
+            JugglerError::config(format!(
+                "Invalid snooze amount '{input}' (expected whole days such as 1d or -2d)"
+            ))
+        })
+}
+
+/// Parse the `due` command's value; `none` clears the due.
+pub fn parse_due_op(input: &str, clock: &SharedClock) -> Result<BatchOp> {
+    if input.trim().eq_ignore_ascii_case("none") {
+        return Ok(BatchOp::SetDue(None));
+    }
+    parse_due_argument(input, clock.now()).map(|due| BatchOp::SetDue(Some(due)))
+}
+
+fn ensure_known_ids(todos: &[Todo], ids: &[String]) -> Result<()> {
+    let known: HashSet<&str> = todos
+        .iter()
+        .filter_map(|todo| todo.todo_id.as_deref())
+        .collect();
+    let unknown: Vec<&str> = ids
+        .iter()
+        .map(String::as_str)
+        .filter(|todo_id| !known.contains(todo_id))
+        .collect();
+    if unknown.is_empty() {
+        Ok(())
+    } else {
+        Err(JugglerError::not_found(format!(
+            "Unknown todo id(s): {}",
+            unknown.join(", ")
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::add::{NewTodo, add_todo};
+    use crate::due::Due;
+    use crate::time::test_clock;
+    use chrono::{TimeZone, Utc};
+
+    fn store_with(titles: &[&str]) -> (tempfile::TempDir, std::path::PathBuf) {
+        let temp_dir = tempfile::TempDir::new().expect("create temp dir");
+        let todos_file = temp_dir.path().join("TODOs.toml");
+        for title in titles {
+            add_todo(
+                &todos_file,
+                NewTodo {
+                    title: title.to_string(),
+                    ..NewTodo::default()
+                },
+                test_clock(),
+            )
+            .expect("add todo");
+        }
+        (temp_dir, todos_file)
+    }
+
+    fn ids(values: &[&str]) -> Targets {
+        Targets::Ids(values.iter().map(ToString::to_string).collect())
+    }
+
+    #[test]
+    fn unknown_ids_fail_without_touching_the_store() {
+        let (_dir, todos_file) = store_with(&["a"]);
+        let before = std::fs::read_to_string(&todos_file).unwrap();
+
+        let err = mutate_todos(
+            &todos_file,
+            &ids(&["T1", "T9"]),
+            BatchOp::Done,
+            test_clock(),
+            Tz::UTC,
+        )
+        .expect_err("unknown id");
+        assert!(err.to_string().contains("T9"));
+        assert!(remove_todos(&todos_file, &ids(&["T5"]), test_clock(), Tz::UTC).is_err());
+        assert_eq!(std::fs::read_to_string(&todos_file).unwrap(), before);
+    }
+
+    #[test]
+    fn done_snooze_and_due_update_the_store() {
+        let (_dir, todos_file) = store_with(&["a", "b"]);
+        let clock = test_clock();
+
+        mutate_todos(
+            &todos_file,
+            &ids(&["T1"]),
+            BatchOp::Done,
+            clock.clone(),
+            Tz::UTC,
+        )
+        .unwrap();
+        let days = parse_snooze_days("2d").unwrap();
+        mutate_todos(
+            &todos_file,
+            &ids(&["T2"]),
+            BatchOp::Snooze { days },
+            clock.clone(),
+            Tz::UTC,
+        )
+        .unwrap();
+
+        let todos = load_todos(&todos_file).unwrap();
+        assert!(todos[0].done);
+        assert_eq!(
+            todos[1].due_date,
+            Some(Due::At(Utc.with_ymd_and_hms(2025, 1, 3, 0, 0, 0).unwrap()))
+        );
+
+        let clear = parse_due_op("none", &clock).unwrap();
+        mutate_todos(&todos_file, &ids(&["T2"]), clear, clock, Tz::UTC).unwrap();
+        assert_eq!(load_todos(&todos_file).unwrap()[1].due_date, None);
+        assert!(parse_snooze_days("3h").is_err());
+    }
+
+    /// Stands in for the external editor: another process saves while it
+    /// is "open", then it returns `title`.
+    struct EditDuringWrite<'a> {
+        todos_file: &'a Path,
+        concurrent: &'a dyn Fn(&Path),
+        title: &'static str,
+    }
+
+    impl TodoEditor for EditDuringWrite<'_> {
+        fn edit_todo(&self, todo: &Todo) -> Result<Todo> {
+            (self.concurrent)(self.todos_file);
+            let mut edited = todo.clone();
+            edited.title = self.title.to_string();
+            Ok(edited)
+        }
+
+        fn needs_terminal_restoration(&self) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn edit_keeps_writes_made_while_the_editor_is_open() {
+        let (_dir, todos_file) = store_with(&["a", "b"]);
+        let complete_other = |todos_file: &Path| {
+            // Takes the store lock, so it would time out if edit held it.
+            mutate_todos(
+                todos_file,
+                &ids(&["T2"]),
+                BatchOp::Done,
+                test_clock(),
+                Tz::UTC,
+            )
+            .unwrap();
+        };
+        let editor = EditDuringWrite {
+            todos_file: &todos_file,
+            concurrent: &complete_other,
+            title: "a, edited",
+        };
+
+        edit_todo(&todos_file, "T1", &editor, test_clock(), Tz::UTC).unwrap();
+
+        let todos = load_todos(&todos_file).unwrap();
+        assert_eq!(todos[0].title, "a, edited");
+        assert!(todos[1].done);
+    }
+
+    #[test]
+    fn edit_refuses_to_overwrite_a_todo_changed_while_the_editor_is_open() {
+        let (_dir, todos_file) = store_with(&["a"]);
+        let complete_it = |todos_file: &Path| {
+            mutate_todos(
+                todos_file,
+                &ids(&["T1"]),
+                BatchOp::Done,
+                test_clock(),
+                Tz::UTC,
+            )
+            .unwrap();
+        };
+        let editor = EditDuringWrite {
+            todos_file: &todos_file,
+            concurrent: &complete_it,
+            title: "a, edited",
+        };
+
+        let err = edit_todo(&todos_file, "T1", &editor, test_clock(), Tz::UTC).unwrap_err();
+
+        assert!(err.to_string().contains("T1 was changed"), "{err}");
+        let todos = load_todos(&todos_file).unwrap();
+        assert_eq!(todos[0].title, "a");
+        assert!(todos[0].done);
+    }
+
+    #[test]
+    fn remove_strips_blocked_by_references() {
+        let (_dir, todos_file) = store_with(&["a", "b"]);
+        let mut todos = load_todos(&todos_file).unwrap();
+        todos[1].blocked_by = vec!["T1".to_string()];
+        crate::store::store_todos(&mut todos, &todos_file).unwrap();
+
+        remove_todos(&todos_file, &ids(&["T1"]), test_clock(), Tz::UTC).unwrap();
+
+        let todos = load_todos(&todos_file).unwrap();
+        assert_eq!(todos.len(), 1);
+        assert_eq!(todos[0].todo_id.as_deref(), Some("T2"));
+        assert!(todos[0].blocked_by.is_empty());
+    }
+
+    #[test]
+    fn query_targets_apply_to_every_match_and_skip_the_write_when_none_match() {
+        let (_dir, todos_file) = store_with(&["deploy api", "deploy web", "read"]);
+        let matching = |query: &str| Targets::Matching(query.to_string());
+
+        let mutation = mutate_todos(
+            &todos_file,
+            &matching("\"deploy\" in title"),
+            BatchOp::Done,
+            test_clock(),
+            Tz::UTC,
+        )
+        .unwrap();
+        assert_eq!(mutation.ids, ["T1", "T2"]);
+
+        let before = std::fs::read_to_string(&todos_file).unwrap();
+        let mutation = mutate_todos(
+            &todos_file,
+            &matching("\"nothing\" in title"),
+            BatchOp::Done,
+            test_clock(),
+            Tz::UTC,
+        )
+        .unwrap();
+        assert!(mutation.ids.is_empty());
+        assert_eq!(std::fs::read_to_string(&todos_file).unwrap(), before);
+
+        let removed = remove_todos(&todos_file, &matching("done"), test_clock(), Tz::UTC).unwrap();
+        assert_eq!(removed, ["T1", "T2"]);
+        assert_eq!(load_todos(&todos_file).unwrap().len(), 1);
+        assert!(
+            remove_todos(&todos_file, &matching("done and"), test_clock(), Tz::UTC)
+                .unwrap_err()
+                .to_string()
+                .contains("column 9")
+        );
+    }
+}
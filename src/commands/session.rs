@@ -0,0 +1,281 @@
+//! Saving an interactive TUI session back to the store.
+//!
+//! The TUI edits a copy of the store loaded at startup and saves when it
+//! exits, often long after `add`, `serve`, `import`, or `sync --watch` have
+//! saved their own changes. So the save takes `store::lock_store`, reloads
+//! the file, and applies only the todos the session changed or created on top
+//! of what is there now.
+//!
+//! A todo changed both in the session and by someone else, or changed in the
+//! session but deleted elsewhere, is a conflict. Nothing is written then; the
+//! session's list is kept as a JSON export next to the store so it can be
+//! compared and brought back with `juggler import`.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use chrono_tz::Tz;
+use log::error;
+
+use super::export::{ExportFormat, JsonTodo, render_export};
+use crate::error::{JugglerError, Result};
+use crate::fields::FieldSchema;
+use crate::store::{
+    format_todo_id, load_todos, load_todos_and_schema, lock_store, parse_todo_id, store_todos,
+    write_file_atomically,
+};
+use crate::time::{SharedClock, system_clock};
+use crate::ui::Todo;
+
+/// The store as a TUI session loaded it.
+pub struct Session {
+    todos_file: PathBuf,
+    field_schema: FieldSchema,
+    loaded: Vec<Todo>,
+    clock: SharedClock,
+}
+
+impl Session {
+    pub fn load(todos_file: &Path) -> Result<Self> {
+        let (loaded, field_schema) = load_todos_and_schema(todos_file)?;
+        Ok(Self {
+            todos_file: todos_file.to_path_buf(),
+            field_schema,
+            loaded,
+            clock: system_clock(),
+        })
+    }
+
+    pub fn todos(&self) -> &[Todo] {
+        &self.loaded
+    }
+
+    pub fn field_schema(&self) -> &FieldSchema {
+        &self.field_schema
+    }
+
+    /// Merge `edited`, the session's final list, into the current store and
+    /// save it. On success `edited` holds what was saved. On failure the
+    /// session's list is written to a conflict copy.
+    pub fn save(&self, edited: &mut Vec<Todo>) -> Result<()> {
+        let result = lock_store(&self.todos_file).and_then(|_lock| {
+            let current = load_todos(&self.todos_file)?;
+            let mut merged = merge_session(&self.loaded, edited, current)?;
+            store_todos(&mut merged, &self.todos_file)?;
+            *edited = merged;
+            Ok(())
+        });
+        if let Err(e) = &result {
+            match self.write_conflict_copy(edited) {
+                Ok(copy) => error!(
+                    "Could not save this session ({e}); its todos were kept in {}",
+                    copy.display()
+                ),
+                Err(copy_error) => error!("Could not keep a copy of this session: {copy_error}"),
+            }
+        }
+        result
+    }
+
+    fn write_conflict_copy(&self, todos: &[Todo]) -> Result<PathBuf> {
+        let timestamp = self.clock.now().format("%Y-%m-%dT%H-%M-%S");
+        let copy = self
+            .todos_file
+            .with_file_name(format!("TODOs.conflict-{timestamp}.json"));
+        let content = render_export(todos, &self.field_schema, ExportFormat::Json, Tz::UTC)?;
+        write_file_atomically(&copy, &content)?;
+        Ok(copy)
+    }
+}
+
+/// Apply the session's changes from `base` to `ours` on top of `theirs`, the
+/// store as it is now. Todos the session created get fresh ids if theirs
+/// already uses them, and the session's `blocked_by` links follow.
+fn merge_session(base: &[Todo], ours: &[Todo], theirs: Vec<Todo>) -> Result<Vec<Todo>> {
+    let same = |a: &Todo, b: &Todo| JsonTodo::from(a) == JsonTodo::from(b);
+    let base_by_id: HashMap<&str, &Todo> = base
+        .iter()
+        .filter_map(|todo| Some((todo.todo_id.as_deref()?, todo)))
+        .collect();
+
+    let mut merged = theirs;
+    let mut changed_ids: HashSet<String> = HashSet::new();
+    let mut created: Vec<Todo> = Vec::new();
+    for todo in ours {
+        let Some((todo_id, original)) = todo
+            .todo_id
+            .as_deref()
+            .and_then(|id| Some((id, *base_by_id.get(id)?)))
+        else {
+            created.push(todo.clone());
+            continue;
+        };
+        if same(original, todo) {
+            continue;
+        }
+        let Some(current) = merged
+            .iter_mut()
+            .find(|current| current.todo_id.as_deref() == Some(todo_id))
+        else {
+            return Err(JugglerError::Other(format!(
+                "{todo_id} was changed in this session but deleted by another juggler process"
+            )));
+        };
+        if !same(current, original) && !same(current, todo) {
+            return Err(JugglerError::Other(format!(
+                "{todo_id} was changed both in this session and by another juggler process"
+            )));
+        }
+        *current = todo.clone();
+        changed_ids.insert(todo_id.to_string());
+    }
+
+    let mut next_number = merged
+        .iter()
+        .chain(&created)
+        .filter_map(|todo| todo.todo_id.as_deref().and_then(parse_todo_id))
+        .max()
+        .unwrap_or_default();
+    let taken: HashSet<String> = merged.iter().filter_map(|t| t.todo_id.clone()).collect();
+    let mut renamed: HashMap<String, String> = HashMap::new();
+    for todo in &mut created {
+        if let Some(todo_id) = todo.todo_id.take_if(|id| taken.contains(id)) {
+            next_number += 1;
+            let new_id = format_todo_id(next_number);
+            renamed.insert(todo_id, new_id.clone());
+            todo.todo_id = Some(new_id);
+        }
+    }
+    let follow_renames = |todo: &mut Todo| {
+        for blocker in &mut todo.blocked_by {
+            if let Some(new_id) = renamed.get(blocker.as_str()) {
+                *blocker = new_id.clone();
+            }
+        }
+    };
+    merged
+        .iter_mut()
+        .filter(|todo| {
+            todo.todo_id
+                .as_ref()
+                .is_some_and(|id| changed_ids.contains(id))
+        })
+        .chain(&mut created)
+        .for_each(follow_renames);
+    merged.extend(created);
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::add::{NewTodo, add_todo};
+    use crate::time::test_clock;
+
+    fn new_todo(title: &str) -> NewTodo {
+        NewTodo {
+            title: title.to_string(),
+            ..NewTodo::default()
+        }
+    }
+
+    fn titles(todos_file: &Path) -> Vec<(String, String, bool)> {
+        load_todos(todos_file)
+            .unwrap()
+            .into_iter()
+            .map(|todo| (todo.todo_id.unwrap(), todo.title, todo.done))
+            .collect()
+    }
+
+    #[test]
+    fn cli_writes_during_a_session_survive_its_save() {
+        let dir = tempfile::tempdir().unwrap();
+        let todos_file = dir.path().join("TODOs.toml");
+        add_todo(&todos_file, new_todo("Existing"), test_clock()).unwrap();
+        add_todo(&todos_file, new_todo("Other"), test_clock()).unwrap();
+
+        let session = Session::load(&todos_file).unwrap();
+        // While the TUI runs: one todo is added from the CLI.
+        add_todo(&todos_file, new_todo("Added from the CLI"), test_clock()).unwrap();
+        // In the TUI: T1 is completed and a todo is created, which the TUI
+        // numbered T3 like the CLI's addition.
+        let mut edited = session.todos().to_vec();
+        edited[0].done = true;
+        let mut created = edited[1].clone();
+        created.title = "Created in the TUI".to_string();
+        created.todo_id = Some("T3".to_string());
+        created.blocked_by = vec!["T1".to_string()];
+        edited.push(created);
+
+        session.save(&mut edited).unwrap();
+
+        assert_eq!(
+            titles(&todos_file),
+            vec![
+                ("T1".to_string(), "Existing".to_string(), true),
+                ("T2".to_string(), "Other".to_string(), false),
+                ("T3".to_string(), "Added from the CLI".to_string(), false),
+                ("T4".to_string(), "Created in the TUI".to_string(), false),
+            ]
+        );
+        assert_eq!(edited.len(), 4);
+    }
+
+    #[test]
+    fn created_todos_keep_links_to_each_other_when_renumbered() {
+        let dir = tempfile::tempdir().unwrap();
+        let todos_file = dir.path().join("TODOs.toml");
+        add_todo(&todos_file, new_todo("Existing"), test_clock()).unwrap();
+
+        let session = Session::load(&todos_file).unwrap();
+        add_todo(&todos_file, new_todo("Added from the CLI"), test_clock()).unwrap();
+        let mut edited = session.todos().to_vec();
+        let mut blocker = edited[0].clone();
+        blocker.title = "Blocker".to_string();
+        blocker.todo_id = Some("T2".to_string());
+        let mut blocked = blocker.clone();
+        blocked.title = "Blocked".to_string();
+        blocked.todo_id = Some("T3".to_string());
+        blocked.blocked_by = vec!["T2".to_string()];
+        edited.extend([blocker, blocked]);
+
+        session.save(&mut edited).unwrap();
+
+        let todos = load_todos(&todos_file).unwrap();
+        let blocked = todos.iter().find(|todo| todo.title == "Blocked").unwrap();
+        let blocker = todos.iter().find(|todo| todo.title == "Blocker").unwrap();
+        assert_eq!(blocker.todo_id.as_deref(), Some("T4"));
+        assert_eq!(blocked.blocked_by, vec!["T4".to_string()]);
+    }
+
+    #[test]
+    fn a_todo_changed_on_both_sides_is_kept_in_a_conflict_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let todos_file = dir.path().join("TODOs.toml");
+        add_todo(&todos_file, new_todo("Shared"), test_clock()).unwrap();
+
+        let session = Session::load(&todos_file).unwrap();
+        let mut current = load_todos(&todos_file).unwrap();
+        current[0].title = "Renamed elsewhere".to_string();
+        store_todos(&mut current, &todos_file).unwrap();
+
+        let mut edited = session.todos().to_vec();
+        edited[0].title = "Renamed in the TUI".to_string();
+        let err = session.save(&mut edited).unwrap_err().to_string();
+
+        assert!(err.contains("T1 was changed both in this session"), "{err}");
+        assert_eq!(titles(&todos_file)[0].1, "Renamed elsewhere");
+        let copy = std::fs::read_dir(dir.path())
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .find(|entry| {
+                entry
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with("TODOs.conflict-")
+            })
+            .expect("conflict copy written");
+        let content = std::fs::read_to_string(copy.path()).unwrap();
+        assert!(content.contains("Renamed in the TUI"));
+    }
+}
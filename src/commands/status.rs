@@ -0,0 +1,142 @@
+//! `juggler status`: a one-line count of overdue, due-soon, and pending todos
+//! for shell prompts and status bars.
+//!
+//! The store is read through `store::load_pending_dues`, so the command costs
+//! one file read and a partial parse. Urgency uses the same thresholds as the
+//! TUI.
+
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
+
+use crate::due::Due;
+use crate::error::{JugglerError, Result};
+use crate::ui::DueDateUrgency;
+
+/// Exit code when something is overdue, so prompt themes can colour it. It
+/// sits outside the error codes 1–8 so that a failure never reads as
+/// "overdue" or the other way round.
+pub const OVERDUE_EXIT_CODE: u8 = 10;
+
+/// Placeholders a status format may use, in the order they are documented.
+const PLACEHOLDERS: [&str; 3] = ["overdue", "soon", "pending"];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct StatusCounts {
+    pub overdue: usize,
+    pub due_soon: usize,
+    pub pending: usize,
+}
+
+impl StatusCounts {
+    fn value(&self, placeholder: &str) -> Option<usize> {
+        match placeholder {
+            "overdue" => Some(self.overdue),
+            "soon" => Some(self.due_soon),
+            "pending" => Some(self.pending),
+            _ => None,
+        }
+    }
+}
+
+/// Count the dues of pending todos by urgency.
+pub fn count_status(
+    dues: &[Option<Due>],
+    now: DateTime<Utc>,
+    tz: Tz,
+    due_soon: Duration,
+) -> StatusCounts {
+    let mut counts = StatusCounts {
+        pending: dues.len(),
+        ..StatusCounts::default()
+    };
+    for due in dues.iter().flatten() {
+        match DueDateUrgency::of(*due, now, tz, due_soon) {
+            DueDateUrgency::Overdue => counts.overdue += 1,
+            DueDateUrgency::DueSoon => counts.due_soon += 1,
+            DueDateUrgency::Normal => {}
+        }
+    }
+    counts
+}
+
+/// Fill `{overdue}`, `{soon}`, and `{pending}` in `format`; `{{` and `}}` are
+/// literal braces.
+pub fn render_status(format: &str, counts: &StatusCounts) -> Result<String> {
+    let invalid = |reason: String| {
+        JugglerError::config(format!("Invalid status format '{format}': {reason}"))
+    };
+    let mut rendered = String::with_capacity(format.len());
+    let mut rest = format;
+    while let Some(index) = rest.find(['{', '}']) {
+        rendered.push_str(&rest[..index]);
+        let tail = &rest[index..];
+        if let Some(escaped) = tail.strip_prefix("{{").or_else(|| tail.strip_prefix("}}")) {
+            rendered.push_str(&tail[..1]);
+            rest = escaped;
+            continue;
+        }
+        let Some(body) = tail.strip_prefix('{') else {
+            return Err(invalid(
+                "unmatched '}' (write '}}' for a literal brace)".to_string(),
+            ));
+        };
+        let Some(end) = body.find('}') else {
+            return Err(invalid("unclosed '{'".to_string()));
+        };
+        let placeholder = &body[..end];
+        let value = counts.value(placeholder).ok_or_else(|| {
+            invalid(format!(
+                "unknown placeholder {{{placeholder}}} (expected one of {})",
+                PLACEHOLDERS.map(|name| format!("{{{name}}}")).join(", ")
+            ))
+        })?;
+        rendered.push_str(&value.to_string());
+        rest = &body[end + 1..];
+    }
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, TimeZone};
+
+    #[test]
+    fn counts_overdue_and_due_soon_with_the_tui_thresholds() {
+        let now = Utc.with_ymd_and_hms(2025, 1, 7, 12, 0, 0).unwrap();
+        let dues = [
+            Some(Due::At(now - Duration::hours(1))),
+            // All-day dues stay on time until the day is over.
+            Some(Due::Date(NaiveDate::from_ymd_opt(2025, 1, 7).unwrap())),
+            Some(Due::At(now + Duration::days(5))),
+            None,
+        ];
+
+        let counts = count_status(&dues, now, Tz::UTC, Duration::days(2));
+        assert_eq!(
+            counts,
+            StatusCounts {
+                overdue: 1,
+                due_soon: 1,
+                pending: 4,
+            }
+        );
+        assert_eq!(
+            render_status(crate::config::DEFAULT_STATUS_FORMAT, &counts).unwrap(),
+            "1! 1~ 4"
+        );
+        assert_eq!(
+            render_status("{{{pending}}} left", &counts).unwrap(),
+            "{4} left"
+        );
+    }
+
+    #[test]
+    fn render_status_rejects_unknown_and_unbalanced_placeholders() {
+        let counts = StatusCounts::default();
+        for format in ["{late}", "{overdue", "overdue}"] {
+            assert!(render_status(format, &counts).is_err(), "{format}");
+        }
+    }
+}
@@ -0,0 +1,373 @@
+//! `juggler sync google-tasks`: publish the store to Google Tasks, once or
+//! continuously with `--watch`.
+//!
+//! Watch mode polls the store's modification time, like `serve`, so saves from
+//! the TUI, the CLI, the API, or a text editor all count. It syncs once the
+//! file has been quiet for `WATCH_DEBOUNCE`, and at least every `--interval`
+//! so remote edits are overwritten even when nothing changes locally.
+//!
+//! Every sync, one-shot, watched, or on TUI exit, runs without the store lock
+//! so other writers are not blocked on the network. Afterwards only the newly
+//! assigned `google_task_id`s are copied into the current file under
+//! `store::lock_store`, which keeps edits made while the sync was running.
+//! Rewrites from a `pre-sync` hook therefore only change what is uploaded;
+//! the store keeps its own values on every path.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime};
+
+use chrono_tz::Tz;
+use log::{error, info, warn};
+
+use crate::error::{JugglerError, Result};
+use crate::fields::FieldSchema;
+use crate::google_tasks::{GoogleOAuthClient, SyncReport, SyncTarget, sync_to_tasks_with_oauth};
+use crate::hooks::{HookEvent, Hooks};
+use crate::store::{load_todos, load_todos_and_schema, lock_store, modified_time, store_todos};
+use crate::ui::Todo;
+
+/// How long the store must stay unchanged after a save before it is synced,
+/// so a burst of edits becomes one sync.
+const WATCH_DEBOUNCE: Duration = Duration::from_secs(2);
+/// How often the store file is checked for changes.
+const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// First retry delay after a network or API failure; it doubles on each
+/// further failure, up to the sync interval.
+const RETRY_INITIAL_BACKOFF: Duration = Duration::from_secs(5);
+
+/// Sync `todos` to Google Tasks between the `pre-sync` and `post-sync` hooks.
+/// The pre-sync hook also runs for dry runs, so a veto shows up in previews.
+pub async fn sync_google_tasks(
+    todos: &mut [Todo],
+    oauth_client: &mut GoogleOAuthClient,
+    dry_run: bool,
+    timezone: Tz,
+    target: &SyncTarget,
+    hooks: &Hooks,
+    field_schema: &FieldSchema,
+) -> Result<SyncReport> {
+    hooks.filter(HookEvent::PreSync, todos, field_schema)?;
+    let report = sync_to_tasks_with_oauth(todos, oauth_client, dry_run, timezone, target).await?;
+    if !dry_run && let Err(e) = hooks.notify(HookEvent::PostSync, todos) {
+        error!("{e}");
+    }
+    Ok(report)
+}
+
+pub struct WatchOptions {
+    /// Longest time between syncs when the store does not change.
+    pub interval: Duration,
+    pub timezone: Tz,
+    pub target: SyncTarget,
+    pub hooks: Hooks,
+}
+
+/// Sync now, then again whenever the store changes or `interval` passes,
+/// until interrupted. Network failures, rate limits, and server errors
+/// (including failed token refreshes) are retried with backoff; a rejected
+/// refresh token ends the watch since it needs `juggler login`, and any
+/// other failure waits for the next change to the store.
+pub async fn watch_google_tasks(
+    todos_file: &Path,
+    oauth_client: &mut GoogleOAuthClient,
+    options: &WatchOptions,
+) -> Result<()> {
+    info!(
+        "Watching {} and syncing at least every {}s; press Ctrl-C to stop",
+        todos_file.display(),
+        options.interval.as_secs()
+    );
+    let interrupted = tokio::signal::ctrl_c();
+    tokio::pin!(interrupted);
+
+    let mut last_seen = modified_time(todos_file);
+    let mut changed_at: Option<Instant> = None;
+    // `None` while a permanent failure waits for the store to change.
+    let mut next_sync = Some(Instant::now());
+    let mut backoff: Option<Duration> = None;
+    loop {
+        let now = Instant::now();
+        let settled = changed_at.is_some_and(|at| now.duration_since(at) >= WATCH_DEBOUNCE);
+        if settled || next_sync.is_some_and(|at| now >= at) {
+            changed_at = None;
+            match sync_once(todos_file, oauth_client, options).await {
+                Ok((report, synced)) => {
+                    info!(
+                        "Synced: {} created, {} updated, {} deleted, {} unchanged",
+                        report.created, report.updated, report.deleted, report.unchanged
+                    );
+                    backoff = None;
+                    next_sync = Some(Instant::now() + options.interval);
+                    // Saves made while the sync ran are synced next.
+                    if synced.edited_meanwhile {
+                        changed_at = Some(Instant::now());
+                    }
+                    last_seen = synced.modified;
+                }
+                Err(e @ (JugglerError::LoginRequired(_) | JugglerError::Credential(_))) => {
+                    return Err(e);
+                }
+                Err(e) if e.is_transient() => {
+                    let delay = next_backoff(backoff, options.interval);
+                    warn!("Sync failed, retrying in {}s: {e}", delay.as_secs());
+                    backoff = Some(delay);
+                    next_sync = Some(Instant::now() + delay);
+                }
+                Err(e) => {
+                    error!("Sync failed; waiting for the next change: {e}");
+                    backoff = None;
+                    next_sync = None;
+                }
+            }
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(WATCH_POLL_INTERVAL) => {}
+            _ = &mut interrupted => {
+                info!("Stopped watching");
+                return Ok(());
+            }
+        }
+        let modified = modified_time(todos_file);
+        if modified != last_seen {
+            last_seen = modified;
+            changed_at = Some(Instant::now());
+        }
+    }
+}
+
+/// The store's state after a watch-mode sync.
+struct Synced {
+    modified: Option<SystemTime>,
+    /// Whether another writer saved between loading and persisting.
+    edited_meanwhile: bool,
+}
+
+async fn sync_once(
+    todos_file: &Path,
+    oauth_client: &mut GoogleOAuthClient,
+    options: &WatchOptions,
+) -> Result<(SyncReport, Synced)> {
+    let loaded_modified = modified_time(todos_file);
+    let (mut todos, field_schema) = load_todos_and_schema(todos_file)?;
+    let before = todos.clone();
+    let report = sync_google_tasks(
+        &mut todos,
+        oauth_client,
+        false,
+        options.timezone,
+        &options.target,
+        &options.hooks,
+        &field_schema,
+    )
+    .await?;
+
+    let _lock = lock_store(todos_file)?;
+    let edited_meanwhile = modified_time(todos_file) != loaded_modified;
+    merge_google_task_ids(todos_file, &assigned_google_task_ids(&before, &todos))?;
+    let synced = Synced {
+        modified: modified_time(todos_file),
+        edited_meanwhile,
+    };
+    Ok((report, synced))
+}
+
+/// Save the `google_task_id`s a sync assigned, going from `before` to
+/// `synced`, into the store as it is now. Only this step takes the lock.
+pub fn save_google_task_ids(todos_file: &Path, before: &[Todo], synced: &[Todo]) -> Result<()> {
+    let assigned = assigned_google_task_ids(before, synced);
+    if assigned.is_empty() {
+        return Ok(());
+    }
+    let _lock = lock_store(todos_file)?;
+    merge_google_task_ids(todos_file, &assigned)
+}
+
+/// Reload the store and save it with `assigned` applied; the caller holds
+/// the lock.
+fn merge_google_task_ids(
+    todos_file: &Path,
+    assigned: &HashMap<String, (Option<String>, String)>,
+) -> Result<()> {
+    if assigned.is_empty() {
+        return Ok(());
+    }
+    let mut current = load_todos(todos_file)?;
+    if apply_google_task_ids(&mut current, assigned) {
+        store_todos(&mut current, todos_file)?;
+    }
+    Ok(())
+}
+
+/// Todo ids whose `google_task_id` sync changed, with the value it replaced.
+fn assigned_google_task_ids(
+    before: &[Todo],
+    after: &[Todo],
+) -> HashMap<String, (Option<String>, String)> {
+    let previous: HashMap<&str, Option<&str>> = before
+        .iter()
+        .filter_map(|todo| Some((todo.todo_id.as_deref()?, todo.google_task_id.as_deref())))
+        .collect();
+    after
+        .iter()
+        .filter_map(|todo| {
+            let todo_id = todo.todo_id.as_deref()?;
+            let google_task_id = todo.google_task_id.as_deref()?;
+            let replaced = *previous.get(todo_id)?;
+            (replaced != Some(google_task_id)).then(|| {
+                (
+                    todo_id.to_string(),
+                    (replaced.map(str::to_string), google_task_id.to_string()),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Copy assigned ids onto the matching todos, skipping todos whose
+/// `google_task_id` was changed by someone else in the meantime. Returns
+/// whether anything changed.
+fn apply_google_task_ids(
+    todos: &mut [Todo],
+    assigned: &HashMap<String, (Option<String>, String)>,
+) -> bool {
+    let mut changed = false;
+    for todo in todos {
+        let Some((replaced, google_task_id)) =
+            todo.todo_id.as_deref().and_then(|id| assigned.get(id))
+        else {
+            continue;
+        };
+        if todo.google_task_id == *replaced {
+            todo.google_task_id = Some(google_task_id.clone());
+            changed = true;
+        }
+    }
+    changed
+}
+
+fn next_backoff(previous: Option<Duration>, interval: Duration) -> Duration {
+    previous
+        .map_or(RETRY_INITIAL_BACKOFF, |delay| delay * 2)
+        .min(interval.max(RETRY_INITIAL_BACKOFF))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indexmap::IndexMap;
+
+    fn todo(id: &str, title: &str, google_task_id: Option<&str>) -> Todo {
+        Todo {
+            title: title.to_string(),
+            comment: None,
+            expanded: false,
+            done: false,
+            selected: false,
+            due_date: None,
+            todo_id: Some(id.to_string()),
+            google_task_id: google_task_id.map(str::to_string),
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra: toml::Table::new(),
+        }
+    }
+
+    #[test]
+    fn assigned_ids_merge_into_a_store_edited_during_sync() {
+        let dir = tempfile::tempdir().unwrap();
+        let todos_file = dir.path().join("TODOs.toml");
+        let before = vec![
+            todo("T1", "New", None),
+            todo("T2", "Linked", Some("g2")),
+            todo("T3", "Gone remotely", Some("g3")),
+        ];
+        let after = vec![
+            todo("T1", "New", Some("g1")),
+            todo("T2", "Linked", Some("g2")),
+            todo("T3", "Gone remotely", Some("g3-recreated")),
+        ];
+        let assigned = assigned_google_task_ids(&before, &after);
+        assert_eq!(assigned.len(), 2);
+
+        // While sync ran: T1 was renamed, T3 unlinked by hand, T4 added.
+        let mut current = vec![
+            todo("T1", "Renamed", None),
+            todo("T2", "Linked", Some("g2")),
+            todo("T3", "Gone remotely", None),
+            todo("T4", "Added", None),
+        ];
+        store_todos(&mut current, &todos_file).unwrap();
+
+        let mut current = load_todos(&todos_file).unwrap();
+        assert!(apply_google_task_ids(&mut current, &assigned));
+        let ids: Vec<(&str, Option<&str>)> = current
+            .iter()
+            .map(|todo| (todo.title.as_str(), todo.google_task_id.as_deref()))
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                ("Renamed", Some("g1")),
+                ("Linked", Some("g2")),
+                ("Gone remotely", None),
+                ("Added", None),
+            ]
+        );
+        assert!(!apply_google_task_ids(&mut current, &assigned));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn pre_sync_rewrites_shape_the_upload_but_are_not_saved() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let todos_file = dir.path().join("TODOs.toml");
+        let hooks_dir = dir.path().join(crate::hooks::HOOKS_DIR_NAME);
+        std::fs::create_dir_all(&hooks_dir).unwrap();
+        let hook = hooks_dir.join(HookEvent::PreSync.name());
+        std::fs::write(
+            &hook,
+            "#!/bin/sh\nsed 's/\"title\":\"Local\"/\"title\":\"[work] Local\"/'\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut stored = vec![todo("T1", "Local", None)];
+        store_todos(&mut stored, &todos_file).unwrap();
+        let before = load_todos(&todos_file).unwrap();
+
+        // What sync_google_tasks does before uploading, then the id Google assigns.
+        let mut synced = before.clone();
+        Hooks::new(hooks_dir)
+            .filter(HookEvent::PreSync, &mut synced, &FieldSchema::default())
+            .unwrap();
+        assert_eq!(synced[0].title, "[work] Local");
+        synced[0].google_task_id = Some("g1".to_string());
+
+        // One-shot and TUI syncs save through here; watch mode merges the same
+        // assigned ids under its own lock.
+        save_google_task_ids(&todos_file, &before, &synced).unwrap();
+        let saved = load_todos(&todos_file).unwrap();
+        assert_eq!(saved[0].title, "Local");
+        assert_eq!(saved[0].google_task_id.as_deref(), Some("g1"));
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_interval() {
+        let interval = Duration::from_secs(30);
+        let mut delays = Vec::new();
+        let mut delay = None;
+        for _ in 0..5 {
+            delay = Some(next_backoff(delay, interval));
+            delays.push(delay.unwrap().as_secs());
+        }
+        assert_eq!(delays, vec![5, 10, 20, 30, 30]);
+    }
+}
@@ -0,0 +1,215 @@
+//! Shell completion and man page generation.
+//!
+//! Completions are dynamic: the registration script printed by
+//! `juggler completions <shell>` calls back into juggler (with `COMPLETE` set)
+//! on every TAB, so todo-id arguments complete to the ids in the current store,
+//! each shown with its title. Static parts such as subcommands and flags come
+//! straight from the clap definitions in `main`.
+//!
+//! The man page is rendered from the same definitions with `clap_mangen`.
+
+use std::ffi::OsString;
+use std::io::Write;
+use std::path::PathBuf;
+
+use clap_complete::CompletionCandidate;
+use clap_complete::env::Shells;
+
+use crate::config::{get_profile_dir, get_todos_file_path};
+use crate::error::{JugglerError, Result};
+use crate::store::{load_todos, parse_todo_id};
+use crate::ui::Todo;
+
+/// Environment variable the registration script sets when asking for candidates.
+pub const COMPLETE_ENV_VAR: &str = "COMPLETE";
+
+const BIN_NAME: &str = "juggler";
+
+/// Write the script that hooks juggler's dynamic completions into `shell`.
+pub fn write_registration(shell: clap_complete::Shell, out: &mut dyn Write) -> Result<()> {
+    let name = shell.to_string();
+    let shells = Shells::builtins();
+    let completer = shells
+        .completer(&name)
+        .ok_or_else(|| JugglerError::Other(format!("Unsupported shell '{name}'")))?;
+    completer.write_registration(COMPLETE_ENV_VAR, BIN_NAME, BIN_NAME, &completer_path(), out)?;
+    Ok(())
+}
+
+/// Render the roff man page for `command`.
+pub fn write_manpage(command: clap::Command, out: &mut dyn Write) -> Result<()> {
+    clap_mangen::Man::new(command).render(out)?;
+    Ok(())
+}
+
+/// Program the shell should run for candidates: the bare name when juggler was
+/// found on `PATH`, otherwise the absolute path of this executable.
+fn completer_path() -> String {
+    let invoked = std::env::args_os().next().map(PathBuf::from);
+    match invoked {
+        Some(path) if path.components().count() > 1 => std::env::current_exe()
+            .unwrap_or(path)
+            .to_string_lossy()
+            .into_owned(),
+        _ => BIN_NAME.to_string(),
+    }
+}
+
+/// Ids of pending todos, for commands such as `done` and `snooze`.
+pub fn pending_id_candidates() -> Vec<CompletionCandidate> {
+    id_candidates(&completion_todos(), |todo| !todo.done)
+}
+
+/// Ids of completed todos, for `undone`.
+pub fn done_id_candidates() -> Vec<CompletionCandidate> {
+    id_candidates(&completion_todos(), |todo| todo.done)
+}
+
+/// Ids of every todo, for `edit` and `rm`.
+pub fn all_id_candidates() -> Vec<CompletionCandidate> {
+    id_candidates(&completion_todos(), |_| true)
+}
+
+fn id_candidates(todos: &[Todo], keep: impl Fn(&Todo) -> bool) -> Vec<CompletionCandidate> {
+    let mut matching: Vec<&Todo> = todos.iter().filter(|todo| keep(todo)).collect();
+    matching.sort_by_key(|todo| todo.todo_id.as_deref().and_then(parse_todo_id));
+    matching
+        .into_iter()
+        .filter_map(|todo| {
+            todo.todo_id.as_ref().map(|todo_id| {
+                CompletionCandidate::new(todo_id).help(Some(todo.title.clone().into()))
+            })
+        })
+        .collect()
+}
+
+/// Todos from the store the command line being completed points at. Errors
+/// yield no candidates rather than noise in the shell.
+fn completion_todos() -> Vec<Todo> {
+    let args: Vec<OsString> = std::env::args_os().collect();
+    let profile = flag_argument(&args, "--profile").or_else(|| std::env::var_os("JUGGLER_PROFILE"));
+    let juggler_dir = match profile {
+        Some(profile) => match profile.to_str().map(get_profile_dir) {
+            Some(Ok(dir)) => Some(dir),
+            _ => return Vec::new(),
+        },
+        None => flag_argument(&args, "--juggler-dir").map(PathBuf::from),
+    };
+    get_todos_file_path(juggler_dir.as_deref())
+        .ok()
+        .and_then(|todos_file| load_todos(todos_file).ok())
+        .unwrap_or_default()
+}
+
+/// The value of `flag` (`--flag VALUE` or `--flag=VALUE`) on the command line
+/// being completed, if any.
+fn flag_argument(args: &[OsString], flag: &str) -> Option<OsString> {
+    let mut args = args.iter();
+    let mut found = None;
+    while let Some(arg) = args.next() {
+        if arg == flag {
+            found = args.next().cloned();
+        } else if let Some(value) = arg
+            .to_str()
+            .and_then(|arg| arg.strip_prefix(flag)?.strip_prefix('='))
+        {
+            found = Some(OsString::from(value));
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::TodoItem;
+    use indexmap::IndexMap;
+
+    fn todo(id: &str, title: &str, done: bool) -> Todo {
+        Todo::from(TodoItem {
+            todo_id: Some(id.to_string()),
+            title: title.to_string(),
+            comment: None,
+            done,
+            due_date: None,
+            google_task_id: None,
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra: toml::Table::new(),
+        })
+    }
+
+    #[test]
+    fn id_candidates_list_matching_ids_with_titles_in_id_order() {
+        let todos = vec![
+            todo("T10", "Taxes", false),
+            todo("T2", "Groceries", false),
+            todo("T3", "Shipped", true),
+        ];
+
+        let pending = id_candidates(&todos, |todo| !todo.done);
+        let described: Vec<(String, String)> = pending
+            .iter()
+            .map(|candidate| {
+                (
+                    candidate.get_value().to_string_lossy().into_owned(),
+                    candidate
+                        .get_help()
+                        .map(ToString::to_string)
+                        .unwrap_or_default(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            described,
+            vec![
+                ("T2".to_string(), "Groceries".to_string()),
+                ("T10".to_string(), "Taxes".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn flag_argument_reads_both_flag_forms() {
+        let args = |list: &[&str]| list.iter().map(OsString::from).collect::<Vec<_>>();
+
+        assert_eq!(
+            flag_argument(
+                &args(&[
+                    "juggler",
+                    "--",
+                    "juggler",
+                    "--juggler-dir",
+                    "/tmp/j",
+                    "done",
+                    "T"
+                ]),
+                "--juggler-dir"
+            ),
+            Some(OsString::from("/tmp/j"))
+        );
+        assert_eq!(
+            flag_argument(
+                &args(&["juggler", "--juggler-dir=/srv/j", "rm"]),
+                "--juggler-dir"
+            ),
+            Some(OsString::from("/srv/j"))
+        );
+        assert_eq!(
+            flag_argument(&args(&["juggler", "--profile=work", "rm"]), "--profile"),
+            Some(OsString::from("work"))
+        );
+        assert_eq!(
+            flag_argument(&args(&["juggler", "--profiles", "done"]), "--profile"),
+            None
+        );
+        assert_eq!(
+            flag_argument(&args(&["juggler", "done"]), "--juggler-dir"),
+            None
+        );
+    }
+}
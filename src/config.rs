@@ -3,12 +3,20 @@
 //! This module defines cross-cutting constants used by the CLI, TUI, OAuth
 //! flow, credential storage, and Google Tasks sync.
 //!
-//! It also provides helpers for resolving juggler's data directory and TODO
-//! file path, including CLI/env overrides.
+//! It also provides helpers for resolving juggler's data directory and the
+//! TODO and settings file paths, including CLI/env overrides and named
+//! profiles. The constants here are the defaults that `config.toml` (see
+//! `settings`) can override.
 
 pub const CREDENTIAL_KEYRING_ACCOUNT_GOOGLE_TASKS: &str = "google-tasks";
 pub const CREDENTIAL_KEYRING_SERVICE: &str = "juggler";
 
+pub const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// Directory under the base data directory that holds one directory per
+/// profile.
+pub const PROFILES_DIR_NAME: &str = "profiles";
+
 pub const DEFAULT_EDITOR: &str = "emacs";
 
 pub const DEFAULT_TOKEN_EXPIRY_SECS: u64 = 3600;
@@ -18,6 +26,12 @@ pub const COMMENT_INDENT: &str = "           ";
 
 pub const DUE_SOON_THRESHOLD_SECS: i64 = 172800;
 
+/// `juggler status` output: overdue, due soon, and pending counts.
+pub const DEFAULT_STATUS_FORMAT: &str = "{overdue}! {soon}~ {pending}";
+
+/// Number of days charted on the TUI stats screen.
+pub const STATS_SCREEN_DAYS: usize = 30;
+
 pub const GOOGLE_OAUTH_AUTHORIZE_URL: &str = "https://accounts.google.com/o/oauth2/v2/auth";
 
 pub const GOOGLE_OAUTH_TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
@@ -30,6 +44,9 @@ pub const GOOGLE_TASK_TITLE_PREFIX: &str = "j:";
 
 pub const GOOGLE_TASK_OWNERSHIP_MARKER: &str = "JUGGLER_META_OWNED_V1";
 
+/// Number of most recent log entries mirrored into Google task notes.
+pub const GOOGLE_TASK_NOTES_LOG_ENTRIES: usize = 3;
+
 pub const GOOGLE_TASKS_SCOPE: &str = "https://www.googleapis.com/auth/tasks";
 
 fn resolve_juggler_dir(
@@ -58,6 +75,48 @@ pub fn get_juggler_dir(
     resolve_juggler_dir(cli_override, env_override.as_deref(), dirs::home_dir())
 }
 
+/// Profile names become directory names and keyring accounts, so they are
+/// limited to ASCII letters, digits, `-`, and `_`.
+pub fn is_valid_profile_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn resolve_profile_dir(base_dir: std::path::PathBuf, profile: &str) -> std::path::PathBuf {
+    base_dir.join(PROFILES_DIR_NAME).join(profile)
+}
+
+/// Resolve the data directory of `profile`: `profiles/<NAME>` under the
+/// directory `get_juggler_dir` picks without a `--juggler-dir` flag.
+pub fn get_profile_dir(profile: &str) -> crate::error::Result<std::path::PathBuf> {
+    if !is_valid_profile_name(profile) {
+        return Err(crate::error::JugglerError::config(format!(
+            "Invalid profile name '{profile}'"
+        )));
+    }
+    Ok(resolve_profile_dir(get_juggler_dir(None)?, profile))
+}
+
+/// Keyring account holding the refresh token of `profile`. Without a profile
+/// it is the account juggler has always used, so existing logins keep working.
+/// The name does not include the base directory, so profiles of the same name
+/// under different `JUGGLER_DIR`s share a login.
+pub fn keyring_account(profile: Option<&str>) -> String {
+    match profile {
+        Some(profile) => format!("{CREDENTIAL_KEYRING_ACCOUNT_GOOGLE_TASKS}:{profile}"),
+        None => CREDENTIAL_KEYRING_ACCOUNT_GOOGLE_TASKS.to_string(),
+    }
+}
+
+/// Resolve the settings file path within the active juggler directory.
+pub fn get_config_file_path(
+    cli_override: Option<&std::path::Path>,
+) -> std::io::Result<std::path::PathBuf> {
+    get_juggler_dir(cli_override).map(|dir| dir.join(CONFIG_FILE_NAME))
+}
+
 /// Resolve the TODO storage file path within the active juggler directory.
 pub fn get_todos_file_path(
     cli_override: Option<&std::path::Path>,
@@ -118,4 +177,27 @@ mod tests {
 
         assert_eq!(todos_path, PathBuf::from("cli-dir").join("TODOs.toml"));
     }
+
+    #[test]
+    fn profiles_get_their_own_directory_and_keyring_account() {
+        let base_dir = PathBuf::from("env-dir");
+        assert_eq!(
+            resolve_profile_dir(base_dir.clone(), "work"),
+            base_dir.join("profiles").join("work")
+        );
+        assert_eq!(
+            keyring_account(None),
+            CREDENTIAL_KEYRING_ACCOUNT_GOOGLE_TASKS
+        );
+        assert_ne!(keyring_account(Some("work")), keyring_account(Some("home")));
+
+        for name in ["work", "home-2", "a_b"] {
+            assert!(is_valid_profile_name(name), "{name}");
+        }
+        for name in ["", "..", "a/b", "x y"] {
+            assert!(!is_valid_profile_name(name), "{name}");
+            let err = get_profile_dir(name).unwrap_err();
+            assert_eq!(err.exit_code(), 3, "{name}");
+        }
+    }
 }
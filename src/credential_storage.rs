@@ -11,7 +11,7 @@ use log::debug;
 use std::error::Error;
 use std::fmt;
 
-use crate::config::{CREDENTIAL_KEYRING_ACCOUNT_GOOGLE_TASKS, CREDENTIAL_KEYRING_SERVICE};
+use crate::config::CREDENTIAL_KEYRING_SERVICE;
 
 /// Errors returned by `CredentialStore` implementations.
 #[derive(Debug)]
@@ -41,20 +41,26 @@ pub trait CredentialStore: Send + Sync {
     fn delete_refresh_token(&self) -> Result<(), CredentialError>;
 }
 
-/// Keyring-backed credential store.
-pub struct KeyringCredentialStore;
+/// Keyring-backed credential store for one account (see
+/// `config::keyring_account`).
+pub struct KeyringCredentialStore {
+    account: String,
+}
 
 impl KeyringCredentialStore {
-    pub fn new() -> Self {
-        Self
+    pub fn new(account: impl Into<String>) -> Self {
+        Self {
+            account: account.into(),
+        }
+    }
+
+    pub fn account(&self) -> &str {
+        &self.account
     }
 
     fn make_entry(&self) -> Result<Entry, CredentialError> {
-        Entry::new(
-            CREDENTIAL_KEYRING_SERVICE,
-            CREDENTIAL_KEYRING_ACCOUNT_GOOGLE_TASKS,
-        )
-        .map_err(|e| CredentialError::Backend(e.to_string()))
+        Entry::new(CREDENTIAL_KEYRING_SERVICE, &self.account)
+            .map_err(|e| CredentialError::Backend(e.to_string()))
     }
 
     fn map_delete_error(error: keyring::Error) -> Result<(), CredentialError> {
@@ -69,7 +75,7 @@ impl CredentialStore for KeyringCredentialStore {
     fn store_refresh_token(&self, refresh_token: &str) -> Result<(), CredentialError> {
         debug!(
             "Keyring: storing refresh token (service={}, account={})...",
-            CREDENTIAL_KEYRING_SERVICE, CREDENTIAL_KEYRING_ACCOUNT_GOOGLE_TASKS
+            CREDENTIAL_KEYRING_SERVICE, self.account
         );
         let entry = self.make_entry()?;
         Entry::set_password(&entry, refresh_token)
@@ -79,7 +85,7 @@ impl CredentialStore for KeyringCredentialStore {
     fn get_refresh_token(&self) -> Result<String, CredentialError> {
         debug!(
             "Keyring: retrieving refresh token (service={}, account={})...",
-            CREDENTIAL_KEYRING_SERVICE, CREDENTIAL_KEYRING_ACCOUNT_GOOGLE_TASKS
+            CREDENTIAL_KEYRING_SERVICE, self.account
         );
         let entry = self.make_entry()?;
         match Entry::get_password(&entry) {
@@ -92,7 +98,7 @@ impl CredentialStore for KeyringCredentialStore {
     fn delete_refresh_token(&self) -> Result<(), CredentialError> {
         debug!(
             "Keyring: deleting refresh token (service={}, account={})...",
-            CREDENTIAL_KEYRING_SERVICE, CREDENTIAL_KEYRING_ACCOUNT_GOOGLE_TASKS
+            CREDENTIAL_KEYRING_SERVICE, self.account
         );
         let entry = self.make_entry()?;
         match Entry::delete_credential(&entry) {
@@ -0,0 +1,580 @@
+//! `juggler doctor`: environment diagnostics.
+//!
+//! Each check reports pass, warn, or fail, with a hint on how to fix anything
+//! that is not a pass. Checks that depend on an earlier one (the token refresh
+//! needs client credentials and a stored refresh token, the list lookup needs
+//! an access token) warn that they were not run instead of failing again.
+//!
+//! Unlike other commands, doctor runs before `config.toml` is applied, so a
+//! broken config file is reported as a failed check rather than aborting.
+
+use std::ffi::OsStr;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::{
+    CONFIG_FILE_NAME, GOOGLE_TASKS_BASE_URL, get_juggler_dir, get_todos_file_path,
+};
+use crate::credential_storage::{CredentialError, CredentialStore};
+use crate::error::{JugglerError, Result};
+use crate::google_tasks::{GoogleOAuthClient, GoogleOAuthCredentials, task_list_exists};
+use crate::settings::{ConfigFile, Override, SettingKey, Settings};
+use crate::store::load_todos;
+use crate::ui::{choose_editor_command, parse_editor_command};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckStatus::Pass => write!(f, "PASS"),
+            CheckStatus::Warn => write!(f, "WARN"),
+            CheckStatus::Fail => write!(f, "FAIL"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+    /// What to do about a warning or failure.
+    pub hint: Option<String>,
+}
+
+impl Check {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Pass,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn warn(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Warn,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Fail,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+}
+
+/// Run every check in order. `overrides` are the flag and environment values
+/// that shadow `config.toml`, as for any other command.
+pub async fn run_doctor(
+    juggler_dir: Option<&Path>,
+    overrides: &[Override],
+    cred_store: &dyn CredentialStore,
+) -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    let resolved_dir = get_juggler_dir(juggler_dir);
+    checks.push(check_juggler_dir(&resolved_dir));
+    let config = match &resolved_dir {
+        Ok(dir) => {
+            let (check, config) = check_config_file(&dir.join(CONFIG_FILE_NAME));
+            checks.push(check);
+            if let Ok(todos_file) = get_todos_file_path(juggler_dir) {
+                checks.push(check_store(&todos_file));
+            }
+            config
+        }
+        Err(_) => ConfigFile::default(),
+    };
+
+    let path_var = std::env::var_os("PATH");
+    checks.push(check_editor(
+        std::env::var("VISUAL").ok().as_deref(),
+        std::env::var("EDITOR").ok().as_deref(),
+        Settings::resolve(&config, &[]).editor(),
+        path_var.as_deref(),
+    ));
+
+    let (keyring_check, refresh_token) = check_keyring(cred_store);
+    checks.push(keyring_check);
+
+    let settings = Settings::resolve(&config, overrides);
+    let client_credentials = settings
+        .get(SettingKey::OAuthClientId)
+        .zip(settings.get(SettingKey::OAuthClientSecret));
+    checks.push(check_oauth_client(&settings));
+
+    let access_token = match (client_credentials, refresh_token) {
+        (Some((client_id, client_secret)), Some(refresh_token)) => {
+            let credentials = GoogleOAuthCredentials {
+                client_id: client_id.to_string(),
+                client_secret: client_secret.to_string(),
+                refresh_token,
+            };
+            let mut oauth_client = GoogleOAuthClient::new(credentials, reqwest::Client::new());
+            let (check, access_token) = check_refresh_token(&mut oauth_client).await;
+            checks.push(check);
+            access_token.map(|token| (oauth_client.client, token))
+        }
+        (None, _) => {
+            checks.push(Check::warn(
+                REFRESH_TOKEN_CHECK,
+                "Not checked without OAuth client credentials",
+                "Provide the client id and secret first (see the OAuth client check)",
+            ));
+            None
+        }
+        (Some(_), None) => {
+            checks.push(Check::warn(
+                REFRESH_TOKEN_CHECK,
+                "Not logged in",
+                "Run `juggler login` to use Google Tasks sync",
+            ));
+            None
+        }
+    };
+
+    let list_name = settings.sync_target().list_name;
+    checks.push(match access_token {
+        Some((client, token)) => {
+            check_task_list(&client, &token, GOOGLE_TASKS_BASE_URL, &list_name).await
+        }
+        None => Check::warn(
+            TASK_LIST_CHECK,
+            format!("Not checked whether the '{list_name}' list exists without an access token"),
+            "Fix the refresh token check first",
+        ),
+    });
+
+    checks
+}
+
+const REFRESH_TOKEN_CHECK: &str = "refresh token";
+const TASK_LIST_CHECK: &str = "task list";
+
+fn check_juggler_dir(resolved: &std::io::Result<PathBuf>) -> Check {
+    const NAME: &str = "juggler dir";
+    let dir = match resolved {
+        Ok(dir) => dir,
+        Err(e) => {
+            return Check::fail(
+                NAME,
+                format!("Could not resolve the data directory: {e}"),
+                "Pass --juggler-dir or set JUGGLER_DIR",
+            );
+        }
+    };
+    match fs::metadata(dir) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Check::pass(
+            NAME,
+            format!("{} (created on the first save)", dir.display()),
+        ),
+        Err(e) => Check::fail(
+            NAME,
+            format!("{}: {e}", dir.display()),
+            "Check the permissions of the directory and its parents",
+        ),
+        Ok(meta) if !meta.is_dir() => Check::fail(
+            NAME,
+            format!("{} is not a directory", dir.display()),
+            "Move the file away, or point --juggler-dir or JUGGLER_DIR elsewhere",
+        ),
+        Ok(_) => match tempfile::tempfile_in(dir) {
+            Ok(_) => Check::pass(NAME, dir.display().to_string()),
+            Err(e) => Check::fail(
+                NAME,
+                format!("{} is not writable: {e}", dir.display()),
+                format!("Make it writable, e.g. `chmod u+rwx {}`", dir.display()),
+            ),
+        },
+    }
+}
+
+fn check_config_file(config_file: &Path) -> (Check, ConfigFile) {
+    const NAME: &str = "config.toml";
+    if !config_file.exists() {
+        return (
+            Check::pass(NAME, "Not present; using defaults"),
+            ConfigFile::default(),
+        );
+    }
+    match ConfigFile::load(config_file) {
+        Ok(config) if let Err(e) = config.keymap() => (
+            Check::fail(
+                NAME,
+                format!("{}: {e}", config_file.display()),
+                "Fix the [keys] table; the TUI will not start until then",
+            ),
+            config,
+        ),
+        Ok(config) if !config.unknown_keys().is_empty() => (
+            Check::warn(
+                NAME,
+                format!(
+                    "{} has unknown keys: {}",
+                    config_file.display(),
+                    config.unknown_keys().join(", ")
+                ),
+                "Remove or rename them; juggler ignores them",
+            ),
+            config,
+        ),
+        Ok(config) => (
+            Check::pass(NAME, format!("{} parses", config_file.display())),
+            config,
+        ),
+        Err(e) => (
+            Check::fail(
+                NAME,
+                e.to_string(),
+                "Fix or remove the reported key; the remaining checks use defaults",
+            ),
+            ConfigFile::default(),
+        ),
+    }
+}
+
+fn check_store(todos_file: &Path) -> Check {
+    const NAME: &str = "TODOs.toml";
+    if !todos_file.exists() {
+        return Check::pass(NAME, "Not created yet");
+    }
+    match load_todos(todos_file) {
+        Ok(todos) => Check::pass(
+            NAME,
+            format!("{} parses ({} todos)", todos_file.display(), todos.len()),
+        ),
+        Err(e) => Check::fail(
+            NAME,
+            e.to_string(),
+            "Run `juggler fsck` to list every problem; `juggler fsck --repair` fixes ids and duplicate links",
+        ),
+    }
+}
+
+/// Resolve the editor the way the TUI does and look its program up on PATH.
+fn check_editor(
+    visual: Option<&str>,
+    editor: Option<&str>,
+    default: &str,
+    path_var: Option<&OsStr>,
+) -> Check {
+    const NAME: &str = "editor";
+    const HINT: &str = "set VISUAL or EDITOR, or run `juggler config set editor <COMMAND>`";
+    let command = choose_editor_command(visual, editor, default);
+    let program = match parse_editor_command(&command) {
+        Ok((program, _)) => program,
+        Err(e) => return Check::fail(NAME, e.to_string(), format!("Fix it: {HINT}")),
+    };
+    match find_executable(&program, path_var) {
+        Some(path) => Check::pass(NAME, format!("`{command}` runs {}", path.display())),
+        None if program == command => Check::fail(
+            NAME,
+            format!("`{program}` is not an executable on PATH"),
+            format!("Install {program}, or {HINT}"),
+        ),
+        None => Check::fail(
+            NAME,
+            format!("`{program}` (from `{command}`) is not an executable on PATH"),
+            format!("Install {program}, or {HINT}"),
+        ),
+    }
+}
+
+fn find_executable(program: &str, path_var: Option<&OsStr>) -> Option<PathBuf> {
+    let candidate = Path::new(program);
+    if candidate.components().count() > 1 {
+        return is_executable(candidate).then(|| candidate.to_path_buf());
+    }
+    let mut names = vec![program.to_string()];
+    if cfg!(windows) {
+        names.extend([".exe", ".cmd", ".bat"].map(|ext| format!("{program}{ext}")));
+    }
+    std::env::split_paths(path_var?)
+        .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+        .find(|path| is_executable(path))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Whether the keychain can be read, and the stored refresh token if any.
+fn check_keyring(cred_store: &dyn CredentialStore) -> (Check, Option<String>) {
+    const NAME: &str = "keyring";
+    match cred_store.get_refresh_token() {
+        Ok(token) => (
+            Check::pass(NAME, "Available; a refresh token is stored"),
+            Some(token),
+        ),
+        Err(CredentialError::NotFound) => (
+            Check::pass(NAME, "Available; no refresh token stored"),
+            None,
+        ),
+        Err(e) => (
+            Check::fail(
+                NAME,
+                e.to_string(),
+                "On Linux, run and unlock a Secret Service provider such as GNOME Keyring or KWallet; on macOS, unlock the login keychain",
+            ),
+            None,
+        ),
+    }
+}
+
+fn check_oauth_client(settings: &Settings) -> Check {
+    const NAME: &str = "OAuth client";
+    let keys = [
+        (
+            SettingKey::OAuthClientId,
+            "client id",
+            "--google-oauth-client-id",
+            "GOOGLE_OAUTH_CLIENT_ID",
+        ),
+        (
+            SettingKey::OAuthClientSecret,
+            "client secret",
+            "--google-oauth-client-secret",
+            "GOOGLE_OAUTH_CLIENT_SECRET",
+        ),
+    ];
+    let missing: Vec<_> = keys
+        .into_iter()
+        .filter(|(key, ..)| settings.get(*key).is_none())
+        .collect();
+    if missing.is_empty() {
+        let sources: Vec<String> = settings
+            .entries()
+            .iter()
+            .filter(|entry| keys.iter().any(|(key, ..)| *key == entry.key))
+            .map(|entry| format!("{} from {}", entry.key, entry.source))
+            .collect();
+        return Check::pass(NAME, sources.join(", "));
+    }
+    Check::warn(
+        NAME,
+        format!(
+            "Missing {}",
+            missing
+                .iter()
+                .map(|(_, label, ..)| *label)
+                .collect::<Vec<_>>()
+                .join(" and ")
+        ),
+        format!(
+            "Needed for login and sync: {}",
+            missing
+                .iter()
+                .map(|(key, _, flag, env_var)| format!(
+                    "pass {flag}, set {env_var}, or run `juggler config set {key} <VALUE>`"
+                ))
+                .collect::<Vec<_>>()
+                .join("; ")
+        ),
+    )
+}
+
+/// Exchange the refresh token for an access token, returned on success.
+async fn check_refresh_token(oauth_client: &mut GoogleOAuthClient) -> (Check, Option<String>) {
+    match oauth_client.get_access_token().await {
+        Ok(token) => (
+            Check::pass(REFRESH_TOKEN_CHECK, "Token refresh succeeded"),
+            Some(token),
+        ),
+        Err(e) => (
+            Check::fail(
+                REFRESH_TOKEN_CHECK,
+                e.to_string(),
+                "Run `juggler login` again; `invalid_client` means the client id and secret do not belong to the same OAuth client",
+            ),
+            None,
+        ),
+    }
+}
+
+async fn check_task_list(
+    client: &reqwest::Client,
+    access_token: &str,
+    base_url: &str,
+    list_name: &str,
+) -> Check {
+    match task_list_exists(client, access_token, base_url, list_name).await {
+        Ok(true) => Check::pass(TASK_LIST_CHECK, format!("'{list_name}' exists")),
+        Ok(false) => Check::fail(
+            TASK_LIST_CHECK,
+            format!("No '{list_name}' task list in Google Tasks"),
+            format!(
+                "Create a list named exactly '{list_name}' at https://tasks.google.com/, or run `juggler config set google_tasks.list_name <NAME>`"
+            ),
+        ),
+        Err(e) => Check::fail(
+            TASK_LIST_CHECK,
+            e.to_string(),
+            "Check the network connection and that the Google Tasks API is enabled for the OAuth client's project",
+        ),
+    }
+}
+
+/// One line per check with its hint underneath, then a tally.
+pub fn render_checks(checks: &[Check]) -> String {
+    let name_width = checks
+        .iter()
+        .map(|check| check.name.len())
+        .max()
+        .unwrap_or_default();
+    let mut lines = Vec::new();
+    for check in checks {
+        lines.push(format!(
+            "{}  {:<name_width$}  {}",
+            check.status, check.name, check.detail
+        ));
+        if let Some(hint) = &check.hint {
+            lines.push(format!("{:<4}  {:<name_width$}  hint: {hint}", "", ""));
+        }
+    }
+    let count = |status| checks.iter().filter(|check| check.status == status).count();
+    lines.push(format!(
+        "\n{} passed, {} warning(s), {} failed",
+        count(CheckStatus::Pass),
+        count(CheckStatus::Warn),
+        count(CheckStatus::Fail)
+    ));
+    lines.join("\n")
+}
+
+/// Fail when any check failed, so `doctor` exits non-zero for scripts.
+/// Warnings alone do not fail.
+pub fn ensure_no_failures(checks: &[Check]) -> Result<()> {
+    let failed = checks
+        .iter()
+        .filter(|check| check.status == CheckStatus::Fail)
+        .count();
+    if failed == 0 {
+        return Ok(());
+    }
+    Err(JugglerError::Other(format!("{failed} check(s) failed")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::test_clock;
+    use tempfile::TempDir;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[cfg(unix)]
+    #[test]
+    fn editor_check_finds_the_resolved_program_on_path() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let bin = TempDir::new().unwrap();
+        let nvim = bin.path().join("nvim");
+        fs::write(&nvim, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(&nvim, fs::Permissions::from_mode(0o755)).unwrap();
+        fs::write(bin.path().join("notes"), "not executable").unwrap();
+        let path_var = std::env::join_paths([bin.path()]).unwrap();
+
+        let check = check_editor(Some("  "), Some("nvim -u NONE"), "emacs", Some(&path_var));
+        assert_eq!(check.status, CheckStatus::Pass, "{check:?}");
+        assert!(check.detail.contains(&nvim.display().to_string()));
+
+        for (editor, default) in [(None, "emacs"), (Some("notes"), "emacs"), (None, "'vi")] {
+            let check = check_editor(None, editor, default, Some(&path_var));
+            assert_eq!(check.status, CheckStatus::Fail, "{editor:?} {default}");
+            assert!(check.hint.is_some());
+        }
+    }
+
+    #[test]
+    fn file_checks_report_missing_unparseable_and_blocked_paths() {
+        let dir = TempDir::new().unwrap();
+        let todos_file = dir.path().join("TODOs.toml");
+        let config_file = dir.path().join(CONFIG_FILE_NAME);
+
+        assert_eq!(check_store(&todos_file).status, CheckStatus::Pass);
+        assert_eq!(check_config_file(&config_file).0.status, CheckStatus::Pass);
+        assert_eq!(
+            check_juggler_dir(&Ok(dir.path().join("new"))).status,
+            CheckStatus::Pass
+        );
+
+        fs::write(&todos_file, "[todos.T1\n").unwrap();
+        fs::write(&config_file, "colour = \"red\"\n").unwrap();
+        assert_eq!(check_config_file(&config_file).0.status, CheckStatus::Warn);
+        fs::write(&config_file, "[keys]\nquit = \"e\"\n").unwrap();
+        assert_eq!(check_config_file(&config_file).0.status, CheckStatus::Fail);
+        fs::write(&config_file, "due_soon = \"-1d\"\n").unwrap();
+        assert_eq!(check_store(&todos_file).status, CheckStatus::Fail);
+        assert_eq!(check_config_file(&config_file).0.status, CheckStatus::Fail);
+        assert_eq!(
+            check_juggler_dir(&Ok(todos_file.clone())).status,
+            CheckStatus::Fail
+        );
+    }
+
+    #[tokio::test]
+    async fn google_checks_refresh_the_token_and_find_the_list() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "test_token",
+                "expires_in": 3600,
+                "token_type": "Bearer"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/tasks/v1/users/@me/lists"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [{ "id": "list_id", "title": "juggler" }]
+            })))
+            .mount(&server)
+            .await;
+
+        let credentials = GoogleOAuthCredentials {
+            client_id: "test-client-id".to_string(),
+            client_secret: "test-client-secret".to_string(),
+            refresh_token: "test_refresh_token".to_string(),
+        };
+        let mut oauth_client = GoogleOAuthClient::new_with_custom_oauth_url(
+            credentials,
+            reqwest::Client::new(),
+            format!("{}/token", server.uri()),
+            test_clock(),
+        );
+        let (check, token) = check_refresh_token(&mut oauth_client).await;
+        assert_eq!(check.status, CheckStatus::Pass);
+        let token = token.expect("access token");
+
+        let client = reqwest::Client::new();
+        let found = check_task_list(&client, &token, &server.uri(), "juggler").await;
+        assert_eq!(found.status, CheckStatus::Pass);
+        let missing = check_task_list(&client, &token, &server.uri(), "work").await;
+        assert_eq!(missing.status, CheckStatus::Fail);
+        assert!(missing.hint.unwrap().contains("google_tasks.list_name"));
+    }
+}
@@ -0,0 +1,250 @@
+//! Due dates with all-day and timed semantics.
+//!
+//! A todo is either due on a calendar day (`Due::Date`, stored as
+//! `"2025-01-07"`) or at an instant (`Due::At`, stored as RFC 3339). Date-only
+//! dues have no instant of their own: they are interpreted in the configured
+//! timezone, so "due tomorrow" stays on the same calendar day across DST changes
+//! and snoozes move it by whole days.
+//!
+//! The timezone is resolved once at startup from `--timezone`/`JUGGLER_TIMEZONE`
+//! or the system setting, and passed to the code that needs wall-clock dates.
+
+use std::fmt;
+
+use chrono::{DateTime, Days, NaiveDate, NaiveTime, SubsecRound, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use crate::error::{JugglerError, Result};
+use crate::ui::parse_relative_duration;
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Due {
+    /// Due at some point during this calendar day in the configured timezone.
+    Date(NaiveDate),
+    /// Due at an exact instant.
+    At(DateTime<Utc>),
+}
+
+impl Due {
+    /// Parse a stored or edited due value: `YYYY-MM-DD` for all-day dues,
+    /// RFC 3339 for timed ones.
+    pub fn parse(input: &str) -> Result<Self> {
+        let input = input.trim();
+        if let Ok(date) = NaiveDate::parse_from_str(input, DATE_FORMAT) {
+            return Ok(Due::Date(date));
+        }
+        DateTime::parse_from_rfc3339(input)
+            .map(|dt| Due::At(dt.with_timezone(&Utc)))
+            .map_err(|e| {
+                JugglerError::config(format!(
+                    "Invalid due_date value '{input}': {e} (expected YYYY-MM-DD or RFC 3339)"
+                ))
+            })
+    }
+
+    /// The calendar day this todo is due on, as seen in `tz`.
+    pub fn date_in(&self, tz: Tz) -> NaiveDate {
+        match self {
+            Due::Date(date) => *date,
+            Due::At(instant) => instant.with_timezone(&tz).date_naive(),
+        }
+    }
+
+    /// First instant of the due period, used for ordering.
+    pub fn starts_at(&self, tz: Tz) -> DateTime<Utc> {
+        match self {
+            Due::Date(date) => start_of_day(*date, tz),
+            Due::At(instant) => *instant,
+        }
+    }
+
+    /// Instant from which the todo counts as overdue. All-day dues stay on
+    /// time until the day is over.
+    pub fn deadline(&self, tz: Tz) -> DateTime<Utc> {
+        match self {
+            Due::Date(date) => start_of_day(next_day(*date), tz),
+            Due::At(instant) => *instant,
+        }
+    }
+
+    /// Move the due by whole calendar days in `tz`, keeping the wall-clock time
+    /// of timed dues.
+    pub fn shift_days(&self, days: i64, tz: Tz) -> Self {
+        match self {
+            Due::Date(date) => Due::Date(add_days(*date, days)),
+            Due::At(instant) => {
+                let local = instant.with_timezone(&tz).naive_local();
+                let shifted = add_days(local.date(), days).and_time(local.time());
+                Due::At(resolve_local(shifted, tz))
+            }
+        }
+    }
+
+    /// Canonical string written to `TODOs.toml` and the editor.
+    pub fn to_storage_string(self) -> String {
+        match self {
+            Due::Date(date) => date.format(DATE_FORMAT).to_string(),
+            Due::At(instant) => instant.to_rfc3339(),
+        }
+    }
+}
+
+impl fmt::Display for Due {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_storage_string())
+    }
+}
+
+/// Parse a due given on the command line: a relative offset from `now` in the
+/// TUI delay syntax (`3d`, `-2h`, `30m`) or an absolute stored value.
+pub fn parse_due_argument(input: &str, now: DateTime<Utc>) -> Result<Due> {
+    match parse_relative_duration(input) {
+        Some(offset) => Ok(Due::At((now + offset).trunc_subsecs(0))),
+        None => Due::parse(input).map_err(|_| {
+            JugglerError::config(format!(
+                "Invalid due '{}' (expected a relative offset like 3d, -2h, 30m, or YYYY-MM-DD, or RFC 3339)",
+                input.trim()
+            ))
+        }),
+    }
+}
+
+/// Today's date in `tz` at instant `now`.
+pub fn today(now: DateTime<Utc>, tz: Tz) -> NaiveDate {
+    now.with_timezone(&tz).date_naive()
+}
+
+/// Resolve the timezone used for date-only dues: an explicit IANA name when
+/// given, otherwise the system timezone, falling back to UTC.
+pub fn resolve_timezone(configured: Option<&str>) -> Result<Tz> {
+    if let Some(name) = configured.map(str::trim).filter(|name| !name.is_empty()) {
+        return name.parse::<Tz>().map_err(|_| {
+            JugglerError::config(format!(
+                "Unknown timezone '{name}'; expected an IANA name such as Europe/Stockholm"
+            ))
+        });
+    }
+
+    Ok(iana_time_zone::get_timezone()
+        .ok()
+        .and_then(|name| name.parse::<Tz>().ok())
+        .unwrap_or(Tz::UTC))
+}
+
+fn add_days(date: NaiveDate, days: i64) -> NaiveDate {
+    let magnitude = Days::new(days.unsigned_abs());
+    let shifted = if days >= 0 {
+        date.checked_add_days(magnitude)
+    } else {
+        date.checked_sub_days(magnitude)
+    };
+    shifted.unwrap_or(date)
+}
+
+fn next_day(date: NaiveDate) -> NaiveDate {
+    add_days(date, 1)
+}
+
+fn start_of_day(date: NaiveDate, tz: Tz) -> DateTime<Utc> {
+    resolve_local(date.and_time(NaiveTime::MIN), tz)
+}
+
+/// Map a wall-clock time to an instant, taking the earlier reading when DST
+/// makes it ambiguous and skipping forward over a DST gap.
+fn resolve_local(local: chrono::NaiveDateTime, tz: Tz) -> DateTime<Utc> {
+    let mut candidate = local;
+    for _ in 0..4 {
+        if let Some(resolved) = tz.from_local_datetime(&candidate).earliest() {
+            return resolved.with_timezone(&Utc);
+        }
+        candidate += chrono::Duration::minutes(30);
+    }
+    Utc.from_utc_datetime(&local)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn parse_distinguishes_all_day_and_timed_dues() {
+        assert_eq!(
+            Due::parse("2025-03-09").unwrap(),
+            Due::Date(date(2025, 3, 9))
+        );
+        assert_eq!(
+            Due::parse("2025-03-09T10:00:00+01:00").unwrap(),
+            Due::At(Utc.with_ymd_and_hms(2025, 3, 9, 9, 0, 0).unwrap())
+        );
+        assert!(Due::parse("tomorrow").is_err());
+        assert_eq!(
+            Due::Date(date(2025, 3, 9)).to_storage_string(),
+            "2025-03-09"
+        );
+    }
+
+    #[test]
+    fn all_day_dues_keep_their_calendar_day_across_dst() {
+        let tz: Tz = "America/New_York".parse().unwrap();
+        let due = Due::Date(date(2025, 3, 8));
+
+        // The US switches to DST on 2025-03-09, so this day is only 23 hours long.
+        let snoozed = due.shift_days(1, tz);
+        assert_eq!(snoozed, Due::Date(date(2025, 3, 9)));
+        assert_eq!(
+            snoozed.deadline(tz) - snoozed.starts_at(tz),
+            chrono::Duration::hours(23)
+        );
+        assert_eq!(
+            snoozed.starts_at(tz),
+            Utc.with_ymd_and_hms(2025, 3, 9, 5, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn timed_dues_shift_by_local_calendar_days() {
+        let tz: Tz = "Europe/Stockholm".parse().unwrap();
+        // 09:00 local on the day before the 2025-03-30 DST change.
+        let due = Due::At(Utc.with_ymd_and_hms(2025, 3, 29, 8, 0, 0).unwrap());
+
+        assert_eq!(
+            due.shift_days(1, tz),
+            Due::At(Utc.with_ymd_and_hms(2025, 3, 30, 7, 0, 0).unwrap())
+        );
+        assert_eq!(due.date_in(tz), date(2025, 3, 29));
+    }
+
+    #[test]
+    fn parse_due_argument_accepts_relative_and_absolute_values() {
+        let now = Utc.with_ymd_and_hms(2025, 3, 9, 12, 0, 0).unwrap();
+
+        assert_eq!(
+            parse_due_argument("3d", now).unwrap(),
+            Due::At(Utc.with_ymd_and_hms(2025, 3, 12, 12, 0, 0).unwrap())
+        );
+        assert_eq!(
+            parse_due_argument("-2h", now).unwrap(),
+            Due::At(Utc.with_ymd_and_hms(2025, 3, 9, 10, 0, 0).unwrap())
+        );
+        assert_eq!(
+            parse_due_argument("2025-04-01", now).unwrap(),
+            Due::Date(date(2025, 4, 1))
+        );
+        assert!(parse_due_argument("soon", now).is_err());
+    }
+
+    #[test]
+    fn resolve_timezone_rejects_unknown_names() {
+        assert_eq!(
+            resolve_timezone(Some("Asia/Tokyo")).unwrap(),
+            Tz::Asia__Tokyo
+        );
+        assert!(resolve_timezone(Some("Mars/Olympus")).is_err());
+    }
+}
@@ -1,10 +1,25 @@
 //! Application-wide error types and result alias.
 //!
 //! `JugglerError` collects failures from I/O, serialization, HTTP, OAuth,
-//! Google Tasks operations, and credential storage into one enum.
+//! Google Tasks operations, credential storage, and user hooks into one enum.
 //!
 //! Modules return the shared `Result<T>` alias so command handlers and runtime
 //! code can propagate errors through a consistent type.
+//!
+//! Each variant has a stable `code` for `--output json` and maps to one of
+//! the documented process exit codes:
+//!
+//! | Exit | Meaning | Variants |
+//! | --- | --- | --- |
+//! | 1 | other failure | `Other` |
+//! | 2 | invalid command line (reported by clap) | |
+//! | 3 | configuration or invalid input | `Config`, `NotFound` |
+//! | 4 | malformed data | `TomlDe`, `TomlSer`, `Json`, `Csv`, `Store` |
+//! | 5 | authentication | `OAuth`, `LoginRequired`, `Credential` |
+//! | 6 | Google API or network | `Http`, `GoogleTasks`, `Unavailable` |
+//! | 7 | local I/O, or the store is locked by another process | `Io`, `Locked` |
+//! | 8 | vetoed or failed by a hook | `Hook` |
+//! | 10 | not an error: `status` found overdue todos | |
 
 use std::io;
 
@@ -22,21 +37,48 @@ pub enum JugglerError {
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
 
+    #[error("CSV error: {0}")]
+    Csv(#[from] csv::Error),
+
     #[error("HTTP error: {0}")]
     Http(#[from] reqwest::Error),
 
     #[error("OAuth error: {0}")]
     OAuth(String),
 
+    /// Google refused the stored refresh token; only `juggler login` helps.
+    #[error("Login required: {0}")]
+    LoginRequired(String),
+
     #[error("Google Tasks API error: {0}")]
     GoogleTasks(String),
 
+    /// Google or the network failed in a way that may pass: a 429 or 5xx
+    /// response, or a token refresh that never reached the server.
+    #[error("Service unavailable: {0}")]
+    Unavailable(String),
+
     #[error("Credential error: {0}")]
     Credential(#[from] crate::credential_storage::CredentialError),
 
     #[error("Configuration error: {0}")]
     Config(String),
 
+    /// A todo id given on the command line or in an API path does not exist.
+    #[error("{0}")]
+    NotFound(String),
+
+    /// `TODOs.toml` parses as TOML but breaks the store format's rules.
+    #[error("TODO store error: {0}")]
+    Store(String),
+
+    /// Another juggler process held the store lock for the whole timeout.
+    #[error("{0}")]
+    Locked(String),
+
+    #[error("Hook error: {0}")]
+    Hook(String),
+
     #[error("{0}")]
     Other(String),
 }
@@ -51,13 +93,88 @@ impl JugglerError {
         JugglerError::OAuth(msg.into())
     }
 
+    pub fn login_required<S: Into<String>>(msg: S) -> Self {
+        JugglerError::LoginRequired(msg.into())
+    }
+
     pub fn google_tasks<S: Into<String>>(msg: S) -> Self {
         JugglerError::GoogleTasks(msg.into())
     }
 
+    pub fn unavailable<S: Into<String>>(msg: S) -> Self {
+        JugglerError::Unavailable(msg.into())
+    }
+
     pub fn config<S: Into<String>>(msg: S) -> Self {
         JugglerError::Config(msg.into())
     }
+
+    pub fn not_found<S: Into<String>>(msg: S) -> Self {
+        JugglerError::NotFound(msg.into())
+    }
+
+    pub fn locked<S: Into<String>>(msg: S) -> Self {
+        JugglerError::Locked(msg.into())
+    }
+
+    pub fn hook<S: Into<String>>(msg: S) -> Self {
+        JugglerError::Hook(msg.into())
+    }
+
+    /// Stable machine-readable name of the variant.
+    pub fn code(&self) -> &'static str {
+        match self {
+            JugglerError::Io(_) => "io",
+            JugglerError::TomlDe(_) => "toml_de",
+            JugglerError::TomlSer(_) => "toml_ser",
+            JugglerError::Json(_) => "json",
+            JugglerError::Csv(_) => "csv",
+            JugglerError::Http(_) => "http",
+            JugglerError::OAuth(_) => "oauth",
+            JugglerError::LoginRequired(_) => "login_required",
+            JugglerError::GoogleTasks(_) => "google_tasks",
+            JugglerError::Unavailable(_) => "unavailable",
+            JugglerError::Credential(_) => "credential",
+            JugglerError::Config(_) => "config",
+            JugglerError::NotFound(_) => "not_found",
+            JugglerError::Store(_) => "store",
+            JugglerError::Locked(_) => "locked",
+            JugglerError::Hook(_) => "hook",
+            JugglerError::Other(_) => "other",
+        }
+    }
+
+    /// Whether the same request may succeed later: network failures, rate
+    /// limits, and server errors, as opposed to rejected requests, bad input,
+    /// local data, or auth.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            JugglerError::Http(e) => !e.is_decode() && !e.is_builder(),
+            JugglerError::Unavailable(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Process exit code for this error; see the table in the module docs.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            JugglerError::Other(_) => 1,
+            JugglerError::Config(_) | JugglerError::NotFound(_) => 3,
+            JugglerError::TomlDe(_)
+            | JugglerError::TomlSer(_)
+            | JugglerError::Json(_)
+            | JugglerError::Csv(_)
+            | JugglerError::Store(_) => 4,
+            JugglerError::OAuth(_)
+            | JugglerError::LoginRequired(_)
+            | JugglerError::Credential(_) => 5,
+            JugglerError::Http(_) | JugglerError::GoogleTasks(_) | JugglerError::Unavailable(_) => {
+                6
+            }
+            JugglerError::Io(_) | JugglerError::Locked(_) => 7,
+            JugglerError::Hook(_) => 8,
+        }
+    }
 }
 
 pub type Result<T> = std::result::Result<T, JugglerError>;
@@ -123,4 +240,31 @@ mod tests {
         let err: JugglerError = String::from("owned string error").into();
         assert_eq!(err.to_string(), "owned string error");
     }
+
+    #[test]
+    fn error_categories_have_distinct_exit_codes() {
+        let io_err: JugglerError = io::Error::other("disk").into();
+        let errors = [
+            JugglerError::new("other"),
+            JugglerError::config("bad"),
+            JugglerError::oauth("expired"),
+            JugglerError::google_tasks("503"),
+            io_err,
+            JugglerError::hook("vetoed"),
+        ];
+        let codes: Vec<u8> = errors.iter().map(JugglerError::exit_code).collect();
+        assert_eq!(codes, vec![1, 3, 5, 6, 7, 8]);
+        assert!(!codes.contains(&crate::commands::status::OVERDUE_EXIT_CODE));
+        assert_eq!(errors[3].code(), "google_tasks");
+    }
+
+    #[test]
+    fn only_unavailable_and_network_errors_are_transient() {
+        assert!(JugglerError::unavailable("503 Service Unavailable").is_transient());
+        assert!(!JugglerError::google_tasks("No 'juggler' task list found").is_transient());
+        assert!(!JugglerError::login_required("invalid_grant").is_transient());
+        assert!(!JugglerError::oauth("invalid_client").is_transient());
+        assert_eq!(JugglerError::unavailable("429").exit_code(), 6);
+        assert_eq!(JugglerError::login_required("invalid_grant").exit_code(), 5);
+    }
 }
@@ -0,0 +1,417 @@
+//! User-defined custom fields on todos.
+//!
+//! The schema lives in the TODO store itself as a top-level `[custom_fields]`
+//! table, so the file stays self-describing for everyone who shares it:
+//!
+//! ```toml
+//! [custom_fields.ticket]
+//! type = "string"
+//! column = true
+//!
+//! [custom_fields.priority]
+//! type = "enum"
+//! values = ["low", "high"]
+//! ```
+//!
+//! Per-todo values are stored under `[todos.TN.fields]` and validated against
+//! the schema on load and after editing.
+
+use std::fmt;
+
+use chrono::{Datelike, NaiveDate};
+use indexmap::IndexMap;
+
+use crate::error::{JugglerError, Result};
+
+/// Top-level table name holding the schema in `TODOs.toml`.
+pub const CUSTOM_FIELDS_TABLE: &str = "custom_fields";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldType {
+    String,
+    Number,
+    Date,
+    Bool,
+    Enum,
+}
+
+impl fmt::Display for FieldType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            FieldType::String => "string",
+            FieldType::Number => "number",
+            FieldType::Date => "date",
+            FieldType::Bool => "bool",
+            FieldType::Enum => "enum",
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FieldDefinition {
+    #[serde(rename = "type")]
+    pub field_type: FieldType,
+    /// Allowed values for `enum` fields.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub values: Vec<String>,
+    /// Show this field as a column in the TUI.
+    #[serde(default)]
+    pub column: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue {
+    String(String),
+    Number(f64),
+    Date(NaiveDate),
+    Bool(bool),
+}
+
+impl fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldValue::String(value) => write!(f, "{value}"),
+            FieldValue::Number(value) => write!(f, "{value}"),
+            FieldValue::Date(value) => write!(f, "{}", value.format("%Y-%m-%d")),
+            FieldValue::Bool(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+impl FieldValue {
+    /// TOML encoding used on disk. Whole numbers are written as integers so
+    /// hand-written `estimate = 3` round-trips unchanged.
+    pub fn to_toml(&self) -> toml::Value {
+        match self {
+            FieldValue::String(value) => toml::Value::String(value.clone()),
+            FieldValue::Number(value) if value.fract() == 0.0 && value.abs() < 9.0e15 => {
+                toml::Value::Integer(*value as i64)
+            }
+            FieldValue::Number(value) => toml::Value::Float(*value),
+            FieldValue::Date(value) => toml::Value::Datetime(toml::value::Datetime {
+                date: Some(toml::value::Date {
+                    year: value.year() as u16,
+                    month: value.month() as u8,
+                    day: value.day() as u8,
+                }),
+                time: None,
+                offset: None,
+            }),
+            FieldValue::Bool(value) => toml::Value::Boolean(*value),
+        }
+    }
+
+    /// JSON encoding used by export; dates become `YYYY-MM-DD` strings, which
+    /// `validate_value` accepts back for date fields.
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            FieldValue::String(value) => serde_json::Value::String(value.clone()),
+            FieldValue::Number(value) => serde_json::Number::from_f64(*value)
+                .map(serde_json::Value::Number)
+                .unwrap_or(serde_json::Value::Null),
+            FieldValue::Date(_) => serde_json::Value::String(self.to_string()),
+            FieldValue::Bool(value) => serde_json::Value::Bool(*value),
+        }
+    }
+}
+
+/// The set of custom fields todos may carry, in declaration order.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+#[serde(transparent)]
+pub struct FieldSchema {
+    definitions: IndexMap<String, FieldDefinition>,
+}
+
+impl FieldSchema {
+    /// Build the schema from the raw `[custom_fields]` table, if present.
+    pub fn from_toml(table: Option<&toml::Value>) -> Result<Self> {
+        let Some(table) = table else {
+            return Ok(Self::default());
+        };
+
+        let definitions: IndexMap<String, FieldDefinition> =
+            table.clone().try_into().map_err(|e| {
+                JugglerError::config(format!("Invalid [{CUSTOM_FIELDS_TABLE}] schema: {e}"))
+            })?;
+
+        for (name, definition) in &definitions {
+            match definition.field_type {
+                FieldType::Enum if definition.values.is_empty() => {
+                    return Err(JugglerError::config(format!(
+                        "Custom field '{name}' has type enum but no values"
+                    )));
+                }
+                FieldType::Enum => {}
+                _ if !definition.values.is_empty() => {
+                    return Err(JugglerError::config(format!(
+                        "Custom field '{name}' lists values but has type {}",
+                        definition.field_type
+                    )));
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Self { definitions })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.definitions.is_empty()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&FieldDefinition> {
+        self.definitions.get(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &FieldDefinition)> {
+        self.definitions
+            .iter()
+            .map(|(name, definition)| (name.as_str(), definition))
+    }
+
+    /// Fields configured to render as TUI columns.
+    pub fn columns(&self) -> impl Iterator<Item = &str> {
+        self.iter()
+            .filter(|(_, definition)| definition.column)
+            .map(|(name, _)| name)
+    }
+
+    fn definition(&self, name: &str) -> Result<&FieldDefinition> {
+        self.get(name).ok_or_else(|| {
+            JugglerError::config(format!(
+                "Unknown custom field '{name}'; declare it under [{CUSTOM_FIELDS_TABLE}.{name}]"
+            ))
+        })
+    }
+
+    /// Validate a stored or edited TOML value for field `name`.
+    pub fn validate_value(&self, name: &str, value: &toml::Value) -> Result<FieldValue> {
+        let definition = self.definition(name)?;
+        let invalid = || {
+            JugglerError::config(format!(
+                "Invalid value {value} for custom field '{name}' (expected {})",
+                definition.field_type
+            ))
+        };
+
+        match (definition.field_type, value) {
+            (FieldType::String, toml::Value::String(s)) => Ok(FieldValue::String(s.clone())),
+            (FieldType::Number, toml::Value::Integer(n)) => Ok(FieldValue::Number(*n as f64)),
+            (FieldType::Number, toml::Value::Float(n)) => Ok(FieldValue::Number(*n)),
+            (FieldType::Bool, toml::Value::Boolean(b)) => Ok(FieldValue::Bool(*b)),
+            (FieldType::Date, toml::Value::Datetime(dt)) => match (dt.date, dt.time) {
+                (Some(date), None) => NaiveDate::from_ymd_opt(
+                    i32::from(date.year),
+                    u32::from(date.month),
+                    u32::from(date.day),
+                )
+                .map(FieldValue::Date)
+                .ok_or_else(invalid),
+                _ => Err(invalid()),
+            },
+            (FieldType::Date, toml::Value::String(s)) => parse_date(s).ok_or_else(invalid),
+            (FieldType::Enum, toml::Value::String(s)) => {
+                self.enum_value(name, definition, s.as_str())
+            }
+            _ => Err(invalid()),
+        }
+    }
+
+    /// Validate a `name`/`value` map, as stored under `[todos.TN.fields]`.
+    pub fn validate_table(&self, table: &toml::Table) -> Result<IndexMap<String, FieldValue>> {
+        table
+            .iter()
+            .map(|(name, value)| Ok((name.clone(), self.validate_value(name, value)?)))
+            .collect()
+    }
+
+    /// Parse a plain-text value for field `name`, as found in a CSV column.
+    pub fn parse_input(&self, name: &str, raw: &str) -> Result<FieldValue> {
+        let definition = self.definition(name)?;
+        let raw = raw.trim();
+        let invalid = || {
+            JugglerError::config(format!(
+                "Invalid value '{raw}' for custom field '{name}' (expected {})",
+                definition.field_type
+            ))
+        };
+
+        match definition.field_type {
+            FieldType::String => Ok(FieldValue::String(raw.to_string())),
+            FieldType::Number => raw
+                .parse::<f64>()
+                .ok()
+                .filter(|n| n.is_finite())
+                .map(FieldValue::Number)
+                .ok_or_else(invalid),
+            FieldType::Date => parse_date(raw).ok_or_else(invalid),
+            FieldType::Bool => match raw.to_ascii_lowercase().as_str() {
+                "true" | "yes" | "1" => Ok(FieldValue::Bool(true)),
+                "false" | "no" | "0" => Ok(FieldValue::Bool(false)),
+                _ => Err(invalid()),
+            },
+            FieldType::Enum => self.enum_value(name, definition, raw),
+        }
+    }
+
+    fn enum_value(
+        &self,
+        name: &str,
+        definition: &FieldDefinition,
+        raw: &str,
+    ) -> Result<FieldValue> {
+        if definition.values.iter().any(|allowed| allowed == raw) {
+            Ok(FieldValue::String(raw.to_string()))
+        } else {
+            Err(JugglerError::config(format!(
+                "Invalid value '{raw}' for custom field '{name}' (expected one of: {})",
+                definition.values.join(", ")
+            )))
+        }
+    }
+}
+
+/// Encode typed values back into a TOML table for persistence.
+pub fn fields_to_toml(fields: &IndexMap<String, FieldValue>) -> toml::Table {
+    fields
+        .iter()
+        .map(|(name, value)| (name.clone(), value.to_toml()))
+        .collect()
+}
+
+fn parse_date(raw: &str) -> Option<FieldValue> {
+    NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+        .ok()
+        .map(FieldValue::Date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema() -> FieldSchema {
+        let table: toml::Table = toml::from_str(
+            r#"
+[ticket]
+type = "string"
+column = true
+
+[estimate]
+type = "number"
+
+[review_on]
+type = "date"
+
+[billable]
+type = "bool"
+
+[priority]
+type = "enum"
+values = ["low", "high"]
+"#,
+        )
+        .expect("parse schema fixture");
+        FieldSchema::from_toml(Some(&toml::Value::Table(table))).expect("valid schema")
+    }
+
+    #[test]
+    fn schema_preserves_declaration_order_and_columns() {
+        let schema = schema();
+        let names: Vec<&str> = schema.iter().map(|(name, _)| name).collect();
+        assert_eq!(
+            names,
+            vec!["ticket", "estimate", "review_on", "billable", "priority"]
+        );
+        assert_eq!(schema.columns().collect::<Vec<_>>(), vec!["ticket"]);
+    }
+
+    #[test]
+    fn schema_rejects_enum_without_values_and_unknown_types() {
+        let enum_without_values: toml::Table =
+            toml::from_str("[priority]\ntype = \"enum\"\n").expect("parse");
+        let err = FieldSchema::from_toml(Some(&toml::Value::Table(enum_without_values)))
+            .expect_err("enum needs values");
+        assert!(err.to_string().contains("has type enum but no values"));
+
+        let unknown_type: toml::Table = toml::from_str("[size]\ntype = \"shoe\"\n").expect("parse");
+        assert!(FieldSchema::from_toml(Some(&toml::Value::Table(unknown_type))).is_err());
+    }
+
+    #[test]
+    fn validate_value_checks_types_enums_and_unknown_names() {
+        let schema = schema();
+
+        assert_eq!(
+            schema
+                .validate_value("estimate", &toml::Value::Integer(3))
+                .expect("integer number"),
+            FieldValue::Number(3.0)
+        );
+        assert_eq!(
+            schema
+                .validate_value("review_on", &toml::Value::String("2025-03-01".to_string()))
+                .expect("string date"),
+            FieldValue::Date(NaiveDate::from_ymd_opt(2025, 3, 1).unwrap())
+        );
+        assert!(
+            schema
+                .validate_value("ticket", &toml::Value::Integer(1))
+                .is_err()
+        );
+        assert!(
+            schema
+                .validate_value("priority", &toml::Value::String("urgent".to_string()))
+                .expect_err("not an allowed value")
+                .to_string()
+                .contains("expected one of: low, high")
+        );
+        assert!(
+            schema
+                .validate_value("customer", &toml::Value::String("acme".to_string()))
+                .expect_err("undeclared field")
+                .to_string()
+                .contains("Unknown custom field 'customer'")
+        );
+    }
+
+    #[test]
+    fn parse_input_converts_text_by_field_type() {
+        let schema = schema();
+
+        assert_eq!(
+            schema.parse_input("estimate", " 2.5 ").unwrap(),
+            FieldValue::Number(2.5)
+        );
+        assert_eq!(
+            schema.parse_input("billable", "yes").unwrap(),
+            FieldValue::Bool(true)
+        );
+        assert_eq!(
+            schema.parse_input("review_on", "2025-03-01").unwrap(),
+            FieldValue::Date(NaiveDate::from_ymd_opt(2025, 3, 1).unwrap())
+        );
+        assert!(schema.parse_input("priority", "urgent").is_err());
+        assert!(schema.parse_input("estimate", "NaN").is_err());
+    }
+
+    #[test]
+    fn values_round_trip_through_toml() {
+        let schema = schema();
+        let table: toml::Table = toml::from_str(
+            r#"
+ticket = "OPS-12"
+estimate = 2.5
+review_on = 2025-03-01
+billable = true
+priority = "high"
+"#,
+        )
+        .expect("parse values");
+
+        let values = schema.validate_table(&table).expect("valid values");
+        assert_eq!(fields_to_toml(&values), table);
+    }
+}
@@ -0,0 +1,1058 @@
+//! Integrity checking and repair for the TODO store.
+//!
+//! `load_todos` stops at the first problem it encounters. This module instead
+//! walks the raw TOML document, collects every problem together with its
+//! location, and classifies which problems can be fixed mechanically.
+//!
+//! Repairs are limited to changes that never discard user-authored content:
+//! renumbering invalid `T<N>` keys (and the `blocked_by` entries naming
+//! them), dropping `blocked_by` entries that name no todo, and dropping
+//! duplicate `google_task_id` links. The previous file is archived before a
+//! repaired version is written.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use indexmap::IndexMap;
+use toml::Spanned;
+
+use crate::error::{JugglerError, Result};
+use crate::fields::{CUSTOM_FIELDS_TABLE, FieldSchema};
+use crate::store::{
+    FORMAT_VERSION_CURRENT, JUGGLER_EDITION_CURRENT, archive_todos_file, format_todo_id,
+    lock_store, parse_due_date, parse_todo_id, validate_blocked_by, write_file_atomically,
+};
+use crate::time::SharedClock;
+
+const KNOWN_TOP_LEVEL_KEYS: [&str; 3] = ["metadata", "todos", CUSTOM_FIELDS_TABLE];
+const KNOWN_METADATA_KEYS: [&str; 2] = ["format_version", "juggler_edition"];
+const KNOWN_TODO_KEYS: [&str; 11] = [
+    "title",
+    "comment",
+    "done",
+    "due_date",
+    "google_task_id",
+    "blocked_by",
+    "log",
+    "created_at",
+    "completed_at",
+    "snooze_count",
+    "fields",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsckIssueKind {
+    Syntax,
+    Metadata,
+    InvalidTodoId,
+    DuplicateGoogleTaskId,
+    EmptyTitle,
+    InvalidDueDate,
+    InvalidFieldType,
+    InvalidCustomField,
+    InvalidDependency,
+    /// A `blocked_by` entry naming no todo; `--repair` drops it.
+    DanglingDependency,
+    UnknownField,
+}
+
+impl FsckIssueKind {
+    /// Whether `--repair` can fix this kind of problem without losing content.
+    pub fn is_repairable(&self) -> bool {
+        matches!(
+            self,
+            FsckIssueKind::InvalidTodoId
+                | FsckIssueKind::DuplicateGoogleTaskId
+                | FsckIssueKind::DanglingDependency
+        )
+    }
+
+    fn severity(&self) -> Severity {
+        match self {
+            FsckIssueKind::UnknownField => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+/// A single problem found in the store, located by TOML key path and, when
+/// known, the 1-based line of the enclosing table.
+#[derive(Debug, Clone)]
+pub struct FsckIssue {
+    pub location: String,
+    pub line: Option<usize>,
+    pub kind: FsckIssueKind,
+    pub message: String,
+}
+
+impl FsckIssue {
+    pub fn severity(&self) -> Severity {
+        self.kind.severity()
+    }
+}
+
+impl fmt::Display for FsckIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{} (line {line})", self.location)?,
+            None => write!(f, "{}", self.location)?,
+        }
+        write!(f, ": {}: {}", self.severity(), self.message)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FsckReport {
+    pub issues: Vec<FsckIssue>,
+}
+
+impl FsckReport {
+    pub fn error_count(&self) -> usize {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity() == Severity::Error)
+            .count()
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity() == Severity::Warning)
+            .count()
+    }
+
+    pub fn repairable_count(&self) -> usize {
+        self.issues
+            .iter()
+            .filter(|issue| issue.kind.is_repairable())
+            .count()
+    }
+
+    fn push(
+        &mut self,
+        location: impl Into<String>,
+        line: Option<usize>,
+        kind: FsckIssueKind,
+        message: impl Into<String>,
+    ) {
+        self.issues.push(FsckIssue {
+            location: location.into(),
+            line,
+            kind,
+            message: message.into(),
+        });
+    }
+}
+
+/// Result of a `--repair` run.
+#[derive(Debug, Clone, Default)]
+pub struct RepairOutcome {
+    pub archive_path: Option<PathBuf>,
+    pub repairs: Vec<String>,
+}
+
+/// Secondary parse of the document used only to recover source spans for
+/// todo tables; structure checks run against an untyped `toml::Table`.
+#[derive(serde::Deserialize)]
+struct TodoSpans {
+    #[serde(default)]
+    todos: IndexMap<String, Spanned<toml::Value>>,
+}
+
+/// Check the TODO file at `file_path`. A missing file is reported as clean,
+/// matching `load_todos` which treats it as an empty list.
+pub fn check_todos_file(file_path: &Path) -> Result<FsckReport> {
+    match fs::read_to_string(file_path) {
+        Ok(content) => Ok(check_todos_content(&content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(FsckReport::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn check_todos_content(content: &str) -> FsckReport {
+    let mut report = FsckReport::default();
+
+    let document: toml::Table = match toml::from_str(content) {
+        Ok(document) => document,
+        Err(e) => {
+            let line = e.span().map(|span| line_of(content, span.start));
+            report.push("<file>", line, FsckIssueKind::Syntax, e.message());
+            return report;
+        }
+    };
+
+    let todo_lines: HashMap<String, usize> = toml::from_str::<TodoSpans>(content)
+        .map(|spans| {
+            spans
+                .todos
+                .into_iter()
+                .map(|(key, value)| (key, line_of(content, value.span().start)))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    for key in document.keys() {
+        if !KNOWN_TOP_LEVEL_KEYS.contains(&key.as_str()) {
+            report.push(
+                key.as_str(),
+                None,
+                FsckIssueKind::UnknownField,
+                format!("unknown top-level key '{key}' is preserved but not interpreted"),
+            );
+        }
+    }
+
+    check_metadata(document.get("metadata"), &mut report);
+
+    let field_schema = match FieldSchema::from_toml(document.get(CUSTOM_FIELDS_TABLE)) {
+        Ok(schema) => Some(schema),
+        Err(e) => {
+            report.push(
+                CUSTOM_FIELDS_TABLE,
+                None,
+                FsckIssueKind::InvalidCustomField,
+                issue_message(e),
+            );
+            None
+        }
+    };
+
+    let todos = match document.get("todos") {
+        None => return report,
+        Some(toml::Value::Table(todos)) => todos,
+        Some(_) => {
+            report.push(
+                "todos",
+                None,
+                FsckIssueKind::InvalidFieldType,
+                "expected [todos] to be a table",
+            );
+            return report;
+        }
+    };
+
+    let mut google_task_owners: HashMap<&str, &str> = HashMap::new();
+    let mut dependencies: HashMap<&str, Vec<String>> = HashMap::new();
+    for (todo_id, value) in todos {
+        let location = format!("todos.{todo_id}");
+        let line = todo_lines.get(todo_id).copied();
+
+        if parse_todo_id(todo_id).is_none() {
+            report.push(
+                location.clone(),
+                line,
+                FsckIssueKind::InvalidTodoId,
+                format!("invalid todo id '{todo_id}'; expected format T<N> with N >= 1"),
+            );
+        }
+
+        let toml::Value::Table(record) = value else {
+            report.push(
+                location,
+                line,
+                FsckIssueKind::InvalidFieldType,
+                "expected todo entry to be a table",
+            );
+            continue;
+        };
+
+        match record.get("title") {
+            None => report.push(
+                format!("{location}.title"),
+                line,
+                FsckIssueKind::EmptyTitle,
+                "missing title",
+            ),
+            Some(toml::Value::String(title)) if title.trim().is_empty() => report.push(
+                format!("{location}.title"),
+                line,
+                FsckIssueKind::EmptyTitle,
+                "title is empty",
+            ),
+            Some(toml::Value::String(_)) => {}
+            Some(other) => report.push(
+                format!("{location}.title"),
+                line,
+                FsckIssueKind::InvalidFieldType,
+                format!("expected string, found {}", other.type_str()),
+            ),
+        }
+
+        check_optional_type(record, "comment", "string", &location, line, &mut report);
+        check_optional_type(record, "done", "boolean", &location, line, &mut report);
+        check_optional_type(
+            record,
+            "google_task_id",
+            "string",
+            &location,
+            line,
+            &mut report,
+        );
+
+        match record.get("due_date") {
+            Some(toml::Value::String(raw)) => {
+                if let Err(e) = parse_due_date(raw) {
+                    report.push(
+                        format!("{location}.due_date"),
+                        line,
+                        FsckIssueKind::InvalidDueDate,
+                        issue_message(e),
+                    );
+                }
+            }
+            Some(_) => {
+                check_optional_type(record, "due_date", "string", &location, line, &mut report)
+            }
+            None => {}
+        }
+
+        if let Some(toml::Value::String(google_task_id)) = record.get("google_task_id") {
+            if let Some(first_owner) = google_task_owners.get(google_task_id.as_str()) {
+                report.push(
+                    format!("{location}.google_task_id"),
+                    line,
+                    FsckIssueKind::DuplicateGoogleTaskId,
+                    format!(
+                        "google_task_id '{google_task_id}' is already linked to todos.{first_owner}"
+                    ),
+                );
+            } else {
+                google_task_owners.insert(google_task_id, todo_id);
+            }
+        }
+
+        match record.get("blocked_by") {
+            None => {}
+            Some(toml::Value::Array(blockers)) if blockers.iter().all(|b| b.is_str()) => {
+                let blockers = blockers
+                    .iter()
+                    .filter_map(|b| b.as_str().map(str::to_string))
+                    .collect();
+                dependencies.insert(todo_id.as_str(), blockers);
+            }
+            Some(_) => report.push(
+                format!("{location}.blocked_by"),
+                line,
+                FsckIssueKind::InvalidFieldType,
+                "expected an array of todo ids",
+            ),
+        }
+
+        match record.get("log") {
+            None => {}
+            Some(toml::Value::Array(entries)) => {
+                for (index, entry) in entries.iter().enumerate() {
+                    if let Some(problem) = log_entry_problem(entry) {
+                        report.push(
+                            format!("{location}.log[{index}]"),
+                            line,
+                            FsckIssueKind::InvalidFieldType,
+                            problem,
+                        );
+                    }
+                }
+            }
+            Some(other) => report.push(
+                format!("{location}.log"),
+                line,
+                FsckIssueKind::InvalidFieldType,
+                format!("expected array of tables, found {}", other.type_str()),
+            ),
+        }
+
+        for key in ["created_at", "completed_at"] {
+            match record.get(key) {
+                None => {}
+                Some(toml::Value::String(raw))
+                    if chrono::DateTime::parse_from_rfc3339(raw).is_ok() => {}
+                Some(other) => report.push(
+                    format!("{location}.{key}"),
+                    line,
+                    FsckIssueKind::InvalidFieldType,
+                    format!("expected RFC 3339 timestamp string, found {other}"),
+                ),
+            }
+        }
+        match record.get("snooze_count") {
+            None => {}
+            Some(toml::Value::Integer(count)) if u32::try_from(*count).is_ok() => {}
+            Some(other) => report.push(
+                format!("{location}.snooze_count"),
+                line,
+                FsckIssueKind::InvalidFieldType,
+                format!("expected non-negative integer, found {other}"),
+            ),
+        }
+
+        match (record.get("fields"), &field_schema) {
+            (Some(toml::Value::Table(fields)), Some(schema)) => {
+                for (name, value) in fields {
+                    if let Err(e) = schema.validate_value(name, value) {
+                        report.push(
+                            format!("{location}.fields.{name}"),
+                            line,
+                            FsckIssueKind::InvalidCustomField,
+                            issue_message(e),
+                        );
+                    }
+                }
+            }
+            (Some(toml::Value::Table(_)), None) | (None, _) => {}
+            (Some(other), _) => report.push(
+                format!("{location}.fields"),
+                line,
+                FsckIssueKind::InvalidFieldType,
+                format!("expected table, found {}", other.type_str()),
+            ),
+        }
+
+        for key in record.keys() {
+            if !KNOWN_TODO_KEYS.contains(&key.as_str()) {
+                report.push(
+                    format!("{location}.{key}"),
+                    line,
+                    FsckIssueKind::UnknownField,
+                    format!("unknown field '{key}' is preserved but not interpreted"),
+                );
+            }
+        }
+    }
+
+    // One issue per dangling reference; the cycle check then runs on the
+    // references that do resolve.
+    for todo_id in todos.keys() {
+        let Some(blockers) = dependencies.get_mut(todo_id.as_str()) else {
+            continue;
+        };
+        blockers.retain(|blocker| {
+            let known = todos.contains_key(blocker);
+            if !known {
+                report.push(
+                    format!("todos.{todo_id}.blocked_by"),
+                    todo_lines.get(todo_id).copied(),
+                    FsckIssueKind::DanglingDependency,
+                    format!("blocked by unknown todo '{blocker}'"),
+                );
+            }
+            known
+        });
+    }
+    let graph = todos.keys().map(|todo_id| {
+        let blockers = dependencies.get(todo_id.as_str());
+        (
+            todo_id.as_str(),
+            blockers.map(Vec::as_slice).unwrap_or_default(),
+        )
+    });
+    if let Err(e) = validate_blocked_by(graph) {
+        report.push(
+            "todos",
+            None,
+            FsckIssueKind::InvalidDependency,
+            issue_message(e),
+        );
+    }
+
+    report
+}
+
+fn check_metadata(metadata: Option<&toml::Value>, report: &mut FsckReport) {
+    let metadata = match metadata {
+        Some(toml::Value::Table(metadata)) => metadata,
+        Some(_) => {
+            report.push(
+                "metadata",
+                None,
+                FsckIssueKind::Metadata,
+                "expected [metadata] to be a table",
+            );
+            return;
+        }
+        None => {
+            report.push(
+                "metadata",
+                None,
+                FsckIssueKind::Metadata,
+                "missing [metadata] table",
+            );
+            return;
+        }
+    };
+
+    for (key, expected) in [
+        ("format_version", FORMAT_VERSION_CURRENT),
+        ("juggler_edition", JUGGLER_EDITION_CURRENT),
+    ] {
+        let location = format!("metadata.{key}");
+        match metadata.get(key) {
+            Some(toml::Value::Integer(value)) if *value == i64::from(expected) => {}
+            Some(toml::Value::Integer(value)) => report.push(
+                location,
+                None,
+                FsckIssueKind::Metadata,
+                format!("unsupported {key}={value} (expected {expected})"),
+            ),
+            Some(other) => report.push(
+                location,
+                None,
+                FsckIssueKind::Metadata,
+                format!("expected integer, found {}", other.type_str()),
+            ),
+            None => report.push(
+                location,
+                None,
+                FsckIssueKind::Metadata,
+                format!("missing {key}"),
+            ),
+        }
+    }
+
+    for key in metadata.keys() {
+        if !KNOWN_METADATA_KEYS.contains(&key.as_str()) {
+            report.push(
+                format!("metadata.{key}"),
+                None,
+                FsckIssueKind::UnknownField,
+                format!("unknown metadata key '{key}' will be dropped on the next save"),
+            );
+        }
+    }
+}
+
+fn check_optional_type(
+    record: &toml::Table,
+    key: &str,
+    expected: &str,
+    location: &str,
+    line: Option<usize>,
+    report: &mut FsckReport,
+) {
+    if let Some(value) = record.get(key)
+        && value.type_str() != expected
+    {
+        report.push(
+            format!("{location}.{key}"),
+            line,
+            FsckIssueKind::InvalidFieldType,
+            format!("expected {expected}, found {}", value.type_str()),
+        );
+    }
+}
+
+/// Describe what is wrong with a `log` entry, if anything.
+fn log_entry_problem(entry: &toml::Value) -> Option<String> {
+    let Some(entry) = entry.as_table() else {
+        return Some(format!("expected table, found {}", entry.type_str()));
+    };
+    match entry.get("at") {
+        Some(toml::Value::String(at)) => {
+            if let Err(e) = chrono::DateTime::parse_from_rfc3339(at) {
+                return Some(format!("invalid 'at' timestamp '{at}': {e}"));
+            }
+        }
+        _ => return Some("missing RFC 3339 'at' timestamp".to_string()),
+    }
+    match entry.get("text") {
+        Some(toml::Value::String(_)) => None,
+        _ => Some("missing string 'text'".to_string()),
+    }
+}
+
+fn issue_message(error: JugglerError) -> String {
+    match error {
+        JugglerError::Config(message) => message,
+        other => other.to_string(),
+    }
+}
+
+fn line_of(content: &str, offset: usize) -> usize {
+    content[..offset.min(content.len())].matches('\n').count() + 1
+}
+
+/// Apply all repairable fixes to the file at `file_path`.
+///
+/// The file is left untouched (and no archive is created) when there is
+/// nothing to repair. Syntax errors make the document unrepairable. The store
+/// lock is held from reading to writing.
+pub fn repair_todos_file_with_clock(file_path: &Path, clock: SharedClock) -> Result<RepairOutcome> {
+    if !file_path.exists() {
+        return Ok(RepairOutcome::default());
+    }
+    let _lock = lock_store(file_path)?;
+    let content = match fs::read_to_string(file_path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(RepairOutcome::default()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut document: toml::Table = toml::from_str(&content)?;
+    let repairs = match document.get_mut("todos") {
+        Some(toml::Value::Table(todos)) => repair_todos_table(todos),
+        _ => Vec::new(),
+    };
+
+    if repairs.is_empty() {
+        return Ok(RepairOutcome::default());
+    }
+
+    let archive_path = archive_todos_file(file_path, clock.as_ref())?;
+    let repaired_content = toml::to_string_pretty(&document)?;
+    write_file_atomically(file_path, &repaired_content)?;
+
+    Ok(RepairOutcome {
+        archive_path: Some(archive_path),
+        repairs,
+    })
+}
+
+fn repair_todos_table(todos: &mut toml::Table) -> Vec<String> {
+    let mut repairs = Vec::new();
+
+    let mut next_number = todos
+        .keys()
+        .filter_map(|key| parse_todo_id(key))
+        .max()
+        .unwrap_or(0)
+        .saturating_add(1);
+
+    let mut repaired = toml::Table::new();
+    let mut renumbered: HashMap<String, String> = HashMap::new();
+    let mut google_task_owners: HashMap<String, String> = HashMap::new();
+    for (todo_id, mut value) in std::mem::take(todos) {
+        let todo_id = if parse_todo_id(&todo_id).is_some() {
+            todo_id
+        } else {
+            let new_id = format_todo_id(next_number);
+            next_number = next_number.saturating_add(1);
+            repairs.push(format!("renumbered todos.{todo_id} to todos.{new_id}"));
+            renumbered.insert(todo_id, new_id.clone());
+            new_id
+        };
+
+        if let toml::Value::Table(record) = &mut value
+            && let Some(toml::Value::String(google_task_id)) = record.get("google_task_id")
+        {
+            if let Some(first_owner) = google_task_owners.get(google_task_id) {
+                repairs.push(format!(
+                    "removed google_task_id '{google_task_id}' from todos.{todo_id} (kept on todos.{first_owner})"
+                ));
+                record.remove("google_task_id");
+            } else {
+                google_task_owners.insert(google_task_id.clone(), todo_id.clone());
+            }
+        }
+
+        repaired.insert(todo_id, value);
+    }
+
+    // Point `blocked_by` at the new keys; a blocker that names no todo at all
+    // would keep the file from loading, so it goes.
+    let known: HashSet<String> = repaired.keys().cloned().collect();
+    for (todo_id, value) in &mut repaired {
+        let Some(toml::Value::Array(blockers)) = value
+            .as_table_mut()
+            .and_then(|record| record.get_mut("blocked_by"))
+        else {
+            continue;
+        };
+        blockers.retain_mut(|blocker| {
+            let Some(blocker_id) = blocker.as_str() else {
+                return true;
+            };
+            if let Some(new_id) = renumbered.get(blocker_id) {
+                *blocker = toml::Value::String(new_id.clone());
+                true
+            } else if known.contains(blocker_id) {
+                true
+            } else {
+                repairs.push(format!(
+                    "removed unknown blocker '{blocker_id}' from todos.{todo_id}.blocked_by"
+                ));
+                false
+            }
+        });
+    }
+
+    *todos = repaired;
+    repairs
+}
+
+/// Fail with a summary error when `report` still contains errors, so the
+/// `fsck` command exits non-zero for scripts.
+pub fn ensure_no_errors(report: &FsckReport, file_path: &Path) -> Result<()> {
+    let errors = report.error_count();
+    if errors == 0 {
+        return Ok(());
+    }
+    Err(JugglerError::Store(format!(
+        "{errors} error(s) found in {}",
+        file_path.display()
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::load_todos;
+    use crate::time::test_clock;
+    use tempfile::TempDir;
+
+    fn kinds(report: &FsckReport) -> Vec<FsckIssueKind> {
+        report.issues.iter().map(|issue| issue.kind).collect()
+    }
+
+    #[test]
+    fn clean_file_has_no_issues() {
+        let report = check_todos_content(
+            r#"[metadata]
+format_version = 1
+juggler_edition = 1
+
+[todos.T1]
+title = "Item 1"
+done = false
+due_date = "2031-01-08T09:00:00Z"
+google_task_id = "id-1"
+created_at = "2030-12-30T08:00:00Z"
+snooze_count = 2
+"#,
+        );
+
+        assert!(report.issues.is_empty(), "{:?}", report.issues);
+    }
+
+    #[test]
+    fn reports_every_problem_with_location() {
+        let report = check_todos_content(
+            r#"[metadata]
+format_version = 1
+juggler_edition = 2
+
+[todos.T1]
+title = "ok"
+google_task_id = "dup"
+
+[todos.bad]
+title = "  "
+done = "yes"
+due_date = "tomorrow"
+google_task_id = "dup"
+priority = 3
+"#,
+        );
+
+        assert_eq!(
+            kinds(&report),
+            vec![
+                FsckIssueKind::Metadata,
+                FsckIssueKind::InvalidTodoId,
+                FsckIssueKind::EmptyTitle,
+                FsckIssueKind::InvalidFieldType,
+                FsckIssueKind::InvalidDueDate,
+                FsckIssueKind::DuplicateGoogleTaskId,
+                FsckIssueKind::UnknownField,
+            ]
+        );
+        assert_eq!(report.error_count(), 6);
+        assert_eq!(report.warning_count(), 1);
+        assert_eq!(report.repairable_count(), 2);
+
+        let duplicate = &report.issues[5];
+        assert_eq!(duplicate.location, "todos.bad.google_task_id");
+        assert_eq!(duplicate.line, Some(9));
+        assert!(duplicate.message.contains("todos.T1"));
+        assert_eq!(
+            duplicate.to_string(),
+            "todos.bad.google_task_id (line 9): error: google_task_id 'dup' is already linked to todos.T1"
+        );
+    }
+
+    #[test]
+    fn reports_custom_field_values_that_do_not_match_schema() {
+        let report = check_todos_content(
+            r#"[metadata]
+format_version = 1
+juggler_edition = 1
+
+[custom_fields.estimate]
+type = "number"
+
+[todos.T1]
+title = "ok"
+
+[todos.T1.fields]
+estimate = "soon"
+ticket = "OPS-1"
+"#,
+        );
+
+        assert_eq!(
+            kinds(&report),
+            vec![
+                FsckIssueKind::InvalidCustomField,
+                FsckIssueKind::InvalidCustomField,
+            ]
+        );
+        assert_eq!(report.issues[0].location, "todos.T1.fields.estimate");
+        assert!(
+            report.issues[1]
+                .message
+                .contains("Unknown custom field 'ticket'")
+        );
+    }
+
+    #[test]
+    fn reports_dependency_cycles() {
+        let report = check_todos_content(
+            r#"[metadata]
+format_version = 1
+juggler_edition = 1
+
+[todos.T1]
+title = "a"
+blocked_by = ["T2"]
+
+[todos.T2]
+title = "b"
+blocked_by = ["T1"]
+"#,
+        );
+
+        assert_eq!(kinds(&report), vec![FsckIssueKind::InvalidDependency]);
+        assert!(report.issues[0].message.contains("T1 -> T2 -> T1"));
+    }
+
+    #[test]
+    fn reports_every_dangling_blocker_as_repairable() {
+        let report = check_todos_content(
+            r#"[metadata]
+format_version = 1
+juggler_edition = 1
+
+[todos.T1]
+title = "a"
+blocked_by = ["T7", "T2"]
+
+[todos.T2]
+title = "b"
+blocked_by = ["T9"]
+"#,
+        );
+
+        assert_eq!(
+            kinds(&report),
+            vec![
+                FsckIssueKind::DanglingDependency,
+                FsckIssueKind::DanglingDependency
+            ]
+        );
+        assert_eq!(report.issues[0].location, "todos.T1.blocked_by");
+        assert!(report.issues[0].message.contains("'T7'"));
+        assert_eq!(report.issues[1].location, "todos.T2.blocked_by");
+        assert_eq!(report.repairable_count(), 2);
+    }
+
+    #[test]
+    fn invalid_due_dates_and_failed_checks_read_as_store_problems() {
+        let report = check_todos_content(
+            r#"[metadata]
+format_version = 1
+juggler_edition = 1
+
+[todos.T1]
+title = "a"
+due_date = "tomorrow"
+"#,
+        );
+
+        assert_eq!(kinds(&report), vec![FsckIssueKind::InvalidDueDate]);
+        assert!(
+            !report.issues[0].message.starts_with("Configuration error"),
+            "{}",
+            report.issues[0].message
+        );
+        let err = ensure_no_errors(&report, Path::new("TODOs.toml")).unwrap_err();
+        assert_eq!(err.exit_code(), 4);
+    }
+
+    #[test]
+    fn reports_malformed_log_entries() {
+        let report = check_todos_content(
+            r#"[metadata]
+format_version = 1
+juggler_edition = 1
+
+[todos.T1]
+title = "a"
+
+[[todos.T1.log]]
+at = "2025-01-02T09:30:00Z"
+text = "ok"
+
+[[todos.T1.log]]
+at = "yesterday"
+text = "bad"
+"#,
+        );
+
+        assert_eq!(kinds(&report), vec![FsckIssueKind::InvalidFieldType]);
+        assert_eq!(report.issues[0].location, "todos.T1.log[1]");
+    }
+
+    #[test]
+    fn reports_malformed_bookkeeping_values() {
+        let report = check_todos_content(
+            r#"[metadata]
+format_version = 1
+juggler_edition = 1
+
+[todos.T1]
+title = "a"
+done = true
+created_at = "last week"
+completed_at = "2025-01-02T09:30:00Z"
+snooze_count = -1
+"#,
+        );
+
+        assert_eq!(
+            kinds(&report),
+            vec![
+                FsckIssueKind::InvalidFieldType,
+                FsckIssueKind::InvalidFieldType
+            ]
+        );
+        assert_eq!(report.issues[0].location, "todos.T1.created_at");
+        assert_eq!(report.issues[1].location, "todos.T1.snooze_count");
+    }
+
+    #[test]
+    fn reports_syntax_errors_with_line() {
+        let report = check_todos_content("[metadata]\nformat_version = \n");
+
+        assert_eq!(kinds(&report), vec![FsckIssueKind::Syntax]);
+        assert_eq!(report.issues[0].line, Some(2));
+    }
+
+    #[test]
+    fn reports_missing_metadata() {
+        let report = check_todos_content("[todos.T1]\ntitle = \"a\"\n");
+
+        assert_eq!(kinds(&report), vec![FsckIssueKind::Metadata]);
+        assert_eq!(report.issues[0].location, "metadata");
+    }
+
+    #[test]
+    fn repair_renumbers_invalid_ids_and_drops_duplicate_links_after_archiving() {
+        let temp_dir = TempDir::new().expect("create temp dir");
+        let todos_file = temp_dir.path().join("TODOs.toml");
+        fs::write(
+            &todos_file,
+            r#"[metadata]
+format_version = 1
+juggler_edition = 1
+
+[todos.T4]
+title = "first"
+google_task_id = "dup"
+
+[todos.T01]
+title = "second"
+google_task_id = "dup"
+"#,
+        )
+        .expect("write fixture");
+
+        let outcome =
+            repair_todos_file_with_clock(&todos_file, test_clock()).expect("repair succeeds");
+
+        assert_eq!(outcome.repairs.len(), 2);
+        let archive_path = outcome.archive_path.expect("archive created");
+        assert!(archive_path.exists());
+        assert!(
+            fs::read_to_string(&archive_path)
+                .expect("read archive")
+                .contains("[todos.T01]")
+        );
+
+        assert!(
+            check_todos_file(&todos_file)
+                .expect("recheck")
+                .issues
+                .is_empty()
+        );
+        let todos = load_todos(&todos_file).expect("repaired file loads");
+        assert_eq!(todos[0].todo_id.as_deref(), Some("T4"));
+        assert_eq!(todos[0].google_task_id.as_deref(), Some("dup"));
+        assert_eq!(todos[1].todo_id.as_deref(), Some("T5"));
+        assert_eq!(todos[1].title, "second");
+        assert!(todos[1].google_task_id.is_none());
+    }
+
+    #[test]
+    fn repair_points_blocked_by_at_renumbered_blockers() {
+        let temp_dir = TempDir::new().expect("create temp dir");
+        let todos_file = temp_dir.path().join("TODOs.toml");
+        fs::write(
+            &todos_file,
+            r#"[metadata]
+format_version = 1
+juggler_edition = 1
+
+[todos.T1]
+title = "blocked"
+blocked_by = ["blocker", "gone"]
+
+[todos.blocker]
+title = "blocker"
+"#,
+        )
+        .expect("write fixture");
+
+        let outcome =
+            repair_todos_file_with_clock(&todos_file, test_clock()).expect("repair succeeds");
+
+        assert_eq!(
+            outcome.repairs,
+            vec![
+                "renumbered todos.blocker to todos.T2".to_string(),
+                "removed unknown blocker 'gone' from todos.T1.blocked_by".to_string(),
+            ]
+        );
+        let todos = load_todos(&todos_file).expect("repaired file loads");
+        assert_eq!(todos[0].blocked_by, vec!["T2".to_string()]);
+        assert_eq!(todos[1].title, "blocker");
+    }
+
+    #[test]
+    fn repair_leaves_clean_file_untouched() {
+        let temp_dir = TempDir::new().expect("create temp dir");
+        let todos_file = temp_dir.path().join("TODOs.toml");
+        let content = "[metadata]\nformat_version = 1\njuggler_edition = 1\n";
+        fs::write(&todos_file, content).expect("write fixture");
+
+        let outcome =
+            repair_todos_file_with_clock(&todos_file, test_clock()).expect("repair succeeds");
+
+        assert!(outcome.archive_path.is_none());
+        assert!(outcome.repairs.is_empty());
+        assert_eq!(fs::read_to_string(&todos_file).expect("read"), content);
+    }
+}
@@ -5,20 +5,41 @@
 //! create/update/delete operations.
 //!
 //! It manages field mapping and `google_task_id` tracking, and supports dry-run
-//! execution that reports planned operations without applying writes.
+//! execution that reports planned operations without applying writes. The
+//! drift report runs the same comparison read-only, per todo.
 
+use chrono::NaiveDate;
+use chrono_tz::Tz;
 use log::info;
 use std::collections::HashMap;
 
 use crate::config::{
-    GOOGLE_TASK_OWNERSHIP_MARKER, GOOGLE_TASK_TITLE_PREFIX, GOOGLE_TASKS_BASE_URL,
-    GOOGLE_TASKS_LIST_NAME,
+    GOOGLE_TASK_NOTES_LOG_ENTRIES, GOOGLE_TASK_OWNERSHIP_MARKER, GOOGLE_TASK_TITLE_PREFIX,
+    GOOGLE_TASKS_BASE_URL, GOOGLE_TASKS_LIST_NAME,
 };
+use crate::due::Due;
 use crate::error::{JugglerError, Result};
 use crate::ui::Todo;
 
 pub use crate::oauth::{GoogleOAuthClient, GoogleOAuthCredentials};
 
+/// Which Google task list sync publishes to, and the prefix that marks
+/// juggler's task titles there.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncTarget {
+    pub list_name: String,
+    pub title_prefix: String,
+}
+
+impl Default for SyncTarget {
+    fn default() -> Self {
+        Self {
+            list_name: GOOGLE_TASKS_LIST_NAME.to_string(),
+            title_prefix: GOOGLE_TASK_TITLE_PREFIX.to_string(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
 struct GoogleTask {
     id: Option<String>,
@@ -96,12 +117,20 @@ fn display_opt(value: &Option<String>) -> &str {
 }
 
 async fn check_api_response(response: reqwest::Response) -> Result<reqwest::Response> {
-    if !response.status().is_success() {
-        return Err(JugglerError::google_tasks(format!(
-            "Google Tasks API request failed with status {}: {}",
-            response.status(),
+    let status = response.status();
+    if !status.is_success() {
+        let message = format!(
+            "Google Tasks API request failed with status {status}: {}",
             response.text().await.unwrap_or_default()
-        )));
+        );
+        // Rate limits and server errors pass; anything else is a rejected request.
+        return Err(
+            if status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+                JugglerError::unavailable(message)
+            } else {
+                JugglerError::google_tasks(message)
+            },
+        );
     }
     Ok(response)
 }
@@ -142,75 +171,40 @@ where
     Ok(all_items)
 }
 
-// Parse a Google API 'due' RFC3339 string into a full UTC DateTime
-fn parse_google_due(s: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+/// Calendar day of a Google Task `due` value.
+///
+/// Google Tasks stores `due` as a date-only field; the time component is discarded
+/// when setting or reading via the public API, which returns midnight UTC. See
+/// https://developers.google.com/workspace/tasks/reference/rest/v1/tasks (field `due`).
+fn parse_google_due_date(s: &str) -> Option<NaiveDate> {
     chrono::DateTime::parse_from_rfc3339(s)
         .ok()
-        .map(|dt| dt.with_timezone(&chrono::Utc))
+        .map(|dt| dt.date_naive())
 }
 
-// Google Tasks treats 'due' as a date-only field (midnight). Extract just the date.
-fn parse_google_due_date_naive(s: &str) -> Option<chrono::NaiveDate> {
-    parse_google_due(s).map(|dt| dt.date_naive())
+/// Encode a calendar day the way the Google Tasks API represents it.
+fn format_google_due(date: NaiveDate) -> String {
+    format!("{}T00:00:00.000Z", date.format("%Y-%m-%d"))
 }
 
-fn due_dates_same_day_utc(
-    google_due: &Option<String>,
-    todo_due: &Option<chrono::DateTime<chrono::Utc>>,
-) -> bool {
-    match (google_due, todo_due) {
-        (None, None) => true,
-        (Some(_), None) | (None, Some(_)) => false,
-        (Some(g), Some(t)) => parse_google_due_date_naive(g)
-            .map(|gd| gd == t.date_naive())
-            .unwrap_or(false),
-    }
+/// The day juggler publishes for a todo: all-day dues as-is, timed dues as
+/// their calendar day in `tz`.
+fn google_due_for(due: Option<Due>, tz: Tz) -> Option<String> {
+    due.map(|due| format_google_due(due.date_in(tz)))
 }
 
-/// Imprecise due-date equivalence tailored to Google Tasks API semantics.
-///
-/// Google Tasks stores `due` as a date-only field; the time component is discarded
-/// when setting or reading via the public API. See the official docs:
-/// https://developers.google.com/workspace/tasks/reference/rest/v1/tasks (field `due`).
-/// The Google Tasks UI may display time-of-day, but that precision is not exposed
-/// through the public API. As a result, the API typically returns midnight UTC (00:00:00Z)
-/// for `due`, while local data may carry intra-day times.
-///
-/// This function treats two dues as "equivalent" if they fall on the same UTC calendar day
-/// OR if their absolute time difference is under one minute. The small tolerance accommodates
-/// minor formatting or conversion differences without masking real date changes.
-fn due_dates_equivalent(
-    google_due: &Option<String>,
-    todo_due: &Option<chrono::DateTime<chrono::Utc>>,
-) -> bool {
-    use chrono::Duration;
-    match (google_due, todo_due) {
+/// Exact comparison of a remote `due` against the value juggler would send.
+fn google_dues_match(google_due: Option<&str>, desired_due: Option<&str>) -> bool {
+    match (google_due, desired_due) {
         (None, None) => true,
-        (Some(_), None) | (None, Some(_)) => false,
-        (Some(g), Some(t)) => {
-            if due_dates_same_day_utc(google_due, todo_due) {
-                return true;
-            }
-            if let Some(g_utc) = parse_google_due(g) {
-                let diff = t.signed_duration_since(g_utc).num_seconds().abs();
-                return diff < Duration::minutes(1).num_seconds();
-            }
-            false
+        (Some(google), Some(desired)) => {
+            let google_date = parse_google_due_date(google);
+            google_date.is_some() && google_date == parse_google_due_date(desired)
         }
+        _ => false,
     }
 }
 
-fn format_due_midnight_z(d: &Option<chrono::DateTime<chrono::Utc>>) -> Option<String> {
-    use chrono::{NaiveTime, SecondsFormat};
-    d.map(|dt| {
-        let date = dt.date_naive();
-        let ndt =
-            date.and_time(NaiveTime::from_hms_milli_opt(0, 0, 0, 0).expect("midnight is valid"));
-        let utc_dt = chrono::DateTime::<chrono::Utc>::from_naive_utc_and_offset(ndt, chrono::Utc);
-        utc_dt.to_rfc3339_opts(SecondsFormat::Millis, true)
-    })
-}
-
 fn notes_have_ownership_marker(notes: Option<&str>) -> bool {
     notes
         .map(|value| {
@@ -232,6 +226,29 @@ fn notes_with_ownership_marker(notes: Option<&str>) -> String {
     format!("{notes}\n\n{GOOGLE_TASK_OWNERSHIP_MARKER}")
 }
 
+/// The todo comment followed by its most recent log entries, oldest first.
+fn notes_body(todo: &Todo, tz: Tz) -> Option<String> {
+    let mut sections: Vec<String> = Vec::new();
+    if let Some(comment) = todo.comment.as_deref().filter(|c| !c.is_empty()) {
+        sections.push(comment.to_string());
+    }
+    let skip = todo.log.len().saturating_sub(GOOGLE_TASK_NOTES_LOG_ENTRIES);
+    let recent: Vec<String> = todo.log[skip..]
+        .iter()
+        .map(|entry| {
+            format!(
+                "{} {}",
+                entry.at.with_timezone(&tz).format("%Y-%m-%d %H:%M"),
+                entry.text
+            )
+        })
+        .collect();
+    if !recent.is_empty() {
+        sections.push(recent.join("\n"));
+    }
+    (!sections.is_empty()).then(|| sections.join("\n\n"))
+}
+
 struct DesiredTaskValues {
     title: String,
     notes: Option<String>,
@@ -239,16 +256,16 @@ struct DesiredTaskValues {
     due: Option<String>,
 }
 
-fn desired_task_values(todo: &Todo) -> DesiredTaskValues {
+fn desired_task_values(todo: &Todo, tz: Tz, target: &SyncTarget) -> DesiredTaskValues {
     DesiredTaskValues {
-        title: format!("{}{}", GOOGLE_TASK_TITLE_PREFIX, todo.title),
-        notes: Some(notes_with_ownership_marker(todo.comment.as_deref())),
+        title: format!("{}{}", target.title_prefix, todo.title),
+        notes: Some(notes_with_ownership_marker(notes_body(todo, tz).as_deref())),
         status: if todo.done {
             "completed"
         } else {
             "needsAction"
         },
-        due: format_due_midnight_z(&todo.due_date),
+        due: google_due_for(todo.due_date, tz),
     }
 }
 
@@ -267,18 +284,29 @@ async fn fetch_all_tasklists(
     .await
 }
 
-fn pick_juggler_list(all_tasklists: Vec<GoogleTaskList>) -> Result<GoogleTaskList> {
+fn pick_juggler_list(
+    all_tasklists: Vec<GoogleTaskList>,
+    list_name: &str,
+) -> Result<GoogleTaskList> {
     all_tasklists
         .into_iter()
-        .find(|list| list.title == GOOGLE_TASKS_LIST_NAME)
+        .find(|list| list.title == list_name)
         .ok_or_else(|| {
-            JugglerError::google_tasks(format!(
-                "No '{}' task list found in Google Tasks",
-                GOOGLE_TASKS_LIST_NAME
-            ))
+            JugglerError::google_tasks(format!("No '{list_name}' task list found in Google Tasks"))
         })
 }
 
+/// Whether the account has a task list named `list_name`.
+pub async fn task_list_exists(
+    client: &reqwest::Client,
+    access_token: &str,
+    base_url: &str,
+    list_name: &str,
+) -> Result<bool> {
+    let all_tasklists = fetch_all_tasklists(client, access_token, base_url).await?;
+    Ok(all_tasklists.iter().any(|list| list.title == list_name))
+}
+
 async fn fetch_all_tasks(
     client: &reqwest::Client,
     list_id: &str,
@@ -308,8 +336,9 @@ async fn create_google_task(
     access_token: &str,
     dry_run: bool,
     base_url: &str,
+    desired: DesiredTaskValues,
 ) -> Result<()> {
-    let new_task = GoogleTask::from_desired(None, desired_task_values(todo));
+    let new_task = GoogleTask::from_desired(None, desired);
 
     let create_url = format!("{base_url}/tasks/v1/lists/{list_id}/tasks");
 
@@ -339,76 +368,118 @@ async fn create_google_task(
     Ok(())
 }
 
+/// What a sync did, or would do in a dry run, to the remote list.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct SyncReport {
+    pub created: usize,
+    pub updated: usize,
+    pub deleted: usize,
+    pub unchanged: usize,
+    /// Remote tasks without juggler's ownership marker, left in place.
+    pub skipped: usize,
+}
+
+/// Push local todos to Google Tasks. `tz` decides which calendar day a timed
+/// due lands on, since Google only stores dates. The client is borrowed so a
+/// long-running caller reuses its cached access token across syncs.
 pub async fn sync_to_tasks_with_oauth(
     todos: &mut [Todo],
-    oauth_client: GoogleOAuthClient,
+    oauth_client: &mut GoogleOAuthClient,
     dry_run: bool,
-) -> Result<()> {
-    sync_to_tasks_with_oauth_and_base_url(todos, oauth_client, dry_run, GOOGLE_TASKS_BASE_URL).await
+    tz: Tz,
+    target: &SyncTarget,
+) -> Result<SyncReport> {
+    sync_to_tasks_with_oauth_and_base_url(
+        todos,
+        oauth_client,
+        dry_run,
+        GOOGLE_TASKS_BASE_URL,
+        tz,
+        target,
+    )
+    .await
 }
 
 async fn sync_to_tasks_with_oauth_and_base_url(
     todos: &mut [Todo],
-    mut oauth_client: GoogleOAuthClient,
+    oauth_client: &mut GoogleOAuthClient,
     dry_run: bool,
     base_url: &str,
-) -> Result<()> {
+    tz: Tz,
+    target: &SyncTarget,
+) -> Result<SyncReport> {
     let access_token = oauth_client.get_access_token().await?;
     let client = &oauth_client.client;
-    sync_to_tasks_with_base_url(todos, &access_token, dry_run, base_url, client).await
+    sync_to_tasks_with_base_url(todos, &access_token, dry_run, base_url, client, tz, target).await
+}
+
+/// A remote task field that sync compares and rewrites.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskField {
+    Title,
+    Notes,
+    Status,
+    Due,
+}
+
+impl TaskField {
+    const ALL: [TaskField; 4] = [
+        TaskField::Title,
+        TaskField::Notes,
+        TaskField::Status,
+        TaskField::Due,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TaskField::Title => "title",
+            TaskField::Notes => "notes",
+            TaskField::Status => "status",
+            TaskField::Due => "due",
+        }
+    }
+}
+
+/// The fields where `google_task` differs from what juggler would send.
+fn task_diffs(google_task: &GoogleTask, desired: &DesiredTaskValues) -> Vec<TaskField> {
+    TaskField::ALL
+        .into_iter()
+        .filter(|field| match field {
+            TaskField::Title => google_task.title != desired.title,
+            TaskField::Notes => google_task.notes.as_deref() != desired.notes.as_deref(),
+            TaskField::Status => {
+                (google_task.status == "completed") != (desired.status == "completed")
+            }
+            TaskField::Due => {
+                !google_dues_match(google_task.due.as_deref(), desired.due.as_deref())
+            }
+        })
+        .collect()
 }
 
 fn log_task_diffs(
     google_task: &GoogleTask,
-    todo: &Todo,
+    updated_task: &GoogleTask,
     task_id: &str,
-    desired_title: &str,
-    desired_notes: &Option<String>,
-    desired_status: &str,
-    desired_due: &Option<String>,
+    diffs: &[TaskField],
 ) {
     info!("Detected changes for Google Task (ID: {}):", task_id);
-    if google_task.title == desired_title {
-        info!(" - title: not changed");
-    } else {
-        info!(" - title: changed to: '{}'", desired_title);
-    }
-    if &google_task.notes == desired_notes {
-        info!(" - notes: not changed");
-    } else {
-        info!(" - notes: changed to: {}", display_opt(desired_notes));
-    }
-    if (google_task.status == "completed") == todo.done {
-        info!(" - status: not changed");
-    } else {
-        info!(" - status: changed to: '{}'", desired_status);
-    }
-    if due_dates_equivalent(&google_task.due, &todo.due_date) {
-        info!(" - due: not changed");
-    } else {
-        let google_due_str = google_task.due.as_deref().unwrap_or("<none>");
-        let google_date = google_task
-            .due
-            .as_deref()
-            .and_then(parse_google_due_date_naive)
-            .map(|d| d.to_string())
-            .unwrap_or_else(|| "<n/a>".to_string());
-        let todo_date = todo
-            .due_date
-            .map(|d| d.date_naive().to_string())
-            .unwrap_or_else(|| "<none>".to_string());
-        let diff_secs = match (
-            google_task.due.as_deref().and_then(parse_google_due),
-            &todo.due_date,
-        ) {
-            (Some(g), Some(t)) => t.signed_duration_since(g).num_seconds().abs(),
-            _ => 0,
-        };
-        info!(
-            " - due: changed (google='{}' date={} vs todo_date={}, |Δ|={}s)",
-            google_due_str, google_date, todo_date, diff_secs
-        );
-        info!(" - due: changed to: {}", display_opt(desired_due));
+    for field in TaskField::ALL {
+        if !diffs.contains(&field) {
+            info!(" - {}: not changed", field.name());
+            continue;
+        }
+        match field {
+            TaskField::Title => info!(" - title: changed to: '{}'", updated_task.title),
+            TaskField::Notes => info!(" - notes: changed to: {}", display_opt(&updated_task.notes)),
+            TaskField::Status => info!(" - status: changed to: '{}'", updated_task.status),
+            TaskField::Due => info!(
+                " - due: changed from {} to {}",
+                display_opt(&google_task.due),
+                display_opt(&updated_task.due)
+            ),
+        }
     }
 }
 
@@ -419,6 +490,7 @@ async fn delete_orphan_tasks(
     dry_run: bool,
     base_url: &str,
     client: &reqwest::Client,
+    report: &mut SyncReport,
 ) -> Result<()> {
     for (task_id, google_task) in google_task_map {
         if !notes_have_ownership_marker(google_task.notes.as_deref()) {
@@ -426,8 +498,10 @@ async fn delete_orphan_tasks(
                 "Skipping orphaned task without ownership marker: '{}' (ID: {})",
                 google_task.title, task_id
             );
+            report.skipped += 1;
             continue;
         }
+        report.deleted += 1;
 
         info!(
             "Deleting orphaned Google Task: '{}' (ID: {})",
@@ -461,7 +535,9 @@ async fn sync_to_tasks_with_base_url(
     dry_run: bool,
     base_url: &str,
     client: &reqwest::Client,
-) -> Result<()> {
+    tz: Tz,
+    target: &SyncTarget,
+) -> Result<SyncReport> {
     if dry_run {
         info!("Starting sync in DRY RUN mode - no changes will be made");
     } else {
@@ -470,7 +546,7 @@ async fn sync_to_tasks_with_base_url(
 
     // First, find the task list for synchronization (across all pages)
     let all_tasklists = fetch_all_tasklists(client, access_token, base_url).await?;
-    let juggler_list = pick_juggler_list(all_tasklists)?;
+    let juggler_list = pick_juggler_list(all_tasklists, &target.list_name)?;
     info!("Parent task list ID: {}", juggler_list.id);
     // Get all existing tasks from the sync list (across all pages)
     let existing_tasks = fetch_all_tasks(client, &juggler_list.id, access_token, base_url).await?;
@@ -481,30 +557,22 @@ async fn sync_to_tasks_with_base_url(
         .filter_map(|task| task.id.clone().map(|id| (id, task)))
         .collect();
 
-    // Process each todo
+    let mut report = SyncReport::default();
     for todo in todos.iter_mut() {
         match &todo.google_task_id {
             Some(task_id) => {
                 // Todo has a Google Task ID, check if it needs updating
                 if let Some(google_task) = google_task_map.remove(task_id) {
-                    let desired = desired_task_values(todo);
-                    let needs_update = google_task.title != desired.title
-                        || google_task.notes.as_deref() != desired.notes.as_deref()
-                        || (google_task.status == "completed") != todo.done
-                        || !due_dates_equivalent(&google_task.due, &todo.due_date);
+                    let desired = desired_task_values(todo, tz, target);
+                    let diffs = task_diffs(&google_task, &desired);
 
-                    if needs_update {
+                    if diffs.is_empty() {
+                        report.unchanged += 1;
+                    } else {
+                        report.updated += 1;
                         let updated_task = GoogleTask::from_desired(Some(task_id.clone()), desired);
 
-                        log_task_diffs(
-                            &google_task,
-                            todo,
-                            task_id,
-                            &updated_task.title,
-                            &updated_task.notes,
-                            &updated_task.status,
-                            &updated_task.due,
-                        );
+                        log_task_diffs(&google_task, &updated_task, task_id, &diffs);
 
                         info!(
                             "Updating Google Task: '{}' (ID: {})",
@@ -534,6 +602,7 @@ async fn sync_to_tasks_with_base_url(
                     }
                 } else {
                     // Task was deleted in Google Tasks, recreate it (one-way sync)
+                    report.created += 1;
                     create_google_task(
                         client,
                         todo,
@@ -541,12 +610,14 @@ async fn sync_to_tasks_with_base_url(
                         access_token,
                         dry_run,
                         base_url,
+                        desired_task_values(todo, tz, target),
                     )
                     .await?;
                 }
             }
             None => {
                 // Todo doesn't have a Google Task ID, create a new task
+                report.created += 1;
                 create_google_task(
                     client,
                     todo,
@@ -554,6 +625,7 @@ async fn sync_to_tasks_with_base_url(
                     access_token,
                     dry_run,
                     base_url,
+                    desired_task_values(todo, tz, target),
                 )
                 .await?;
             }
@@ -567,6 +639,7 @@ async fn sync_to_tasks_with_base_url(
         dry_run,
         base_url,
         client,
+        &mut report,
     )
     .await?;
 
@@ -576,7 +649,166 @@ async fn sync_to_tasks_with_base_url(
         info!("Sync complete");
     }
 
-    Ok(())
+    Ok(report)
+}
+
+/// How a todo, or a remote task no todo links to, compares with Google Tasks.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum Drift {
+    InSync,
+    NeedsUpdate {
+        fields: Vec<TaskField>,
+    },
+    /// The linked remote task is gone; sync would re-create it.
+    MissingRemotely,
+    NeverSynced,
+    /// Sync deletes orphans only when they carry the ownership marker.
+    RemoteOrphan {
+        owned: bool,
+    },
+}
+
+impl Drift {
+    pub fn label(&self) -> String {
+        match self {
+            Drift::InSync => "in sync".to_string(),
+            Drift::NeedsUpdate { fields } => format!(
+                "needs update ({})",
+                fields
+                    .iter()
+                    .map(|field| field.name())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Drift::MissingRemotely => "missing remotely".to_string(),
+            Drift::NeverSynced => "never synced".to_string(),
+            Drift::RemoteOrphan { owned: true } => "remote orphan (owned)".to_string(),
+            Drift::RemoteOrphan { owned: false } => "remote orphan (unowned)".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct DriftEntry {
+    /// `None` for remote orphans.
+    pub todo_id: Option<String>,
+    pub google_task_id: Option<String>,
+    /// The todo title, or the remote title (prefix included) for orphans.
+    pub title: String,
+    #[serde(flatten)]
+    pub drift: Drift,
+}
+
+/// Compare local todos with the remote list without writing to either:
+/// one entry per todo in store order, then one per remote orphan.
+pub async fn drift_report_with_oauth(
+    todos: &[Todo],
+    mut oauth_client: GoogleOAuthClient,
+    tz: Tz,
+    target: &SyncTarget,
+) -> Result<Vec<DriftEntry>> {
+    let access_token = oauth_client.get_access_token().await?;
+    drift_report_with_base_url(
+        todos,
+        &access_token,
+        GOOGLE_TASKS_BASE_URL,
+        &oauth_client.client,
+        tz,
+        target,
+    )
+    .await
+}
+
+async fn drift_report_with_base_url(
+    todos: &[Todo],
+    access_token: &str,
+    base_url: &str,
+    client: &reqwest::Client,
+    tz: Tz,
+    target: &SyncTarget,
+) -> Result<Vec<DriftEntry>> {
+    let all_tasklists = fetch_all_tasklists(client, access_token, base_url).await?;
+    let juggler_list = pick_juggler_list(all_tasklists, &target.list_name)?;
+    let existing_tasks = fetch_all_tasks(client, &juggler_list.id, access_token, base_url).await?;
+    let mut google_task_map: HashMap<&str, &GoogleTask> = existing_tasks
+        .iter()
+        .filter_map(|task| task.id.as_deref().map(|id| (id, task)))
+        .collect();
+
+    // Mirrors the decisions in `sync_to_tasks_with_base_url`.
+    let mut entries: Vec<DriftEntry> = todos
+        .iter()
+        .map(|todo| {
+            let drift = match todo.google_task_id.as_deref() {
+                None => Drift::NeverSynced,
+                Some(task_id) => match google_task_map.remove(task_id) {
+                    None => Drift::MissingRemotely,
+                    Some(google_task) => {
+                        let fields =
+                            task_diffs(google_task, &desired_task_values(todo, tz, target));
+                        if fields.is_empty() {
+                            Drift::InSync
+                        } else {
+                            Drift::NeedsUpdate { fields }
+                        }
+                    }
+                },
+            };
+            DriftEntry {
+                todo_id: todo.todo_id.clone(),
+                google_task_id: todo.google_task_id.clone(),
+                title: todo.title.clone(),
+                drift,
+            }
+        })
+        .collect();
+    entries.extend(existing_tasks.iter().filter_map(|task| {
+        let id = task.id.as_deref()?;
+        google_task_map.contains_key(id).then(|| DriftEntry {
+            todo_id: None,
+            google_task_id: Some(id.to_string()),
+            title: task.title.clone(),
+            drift: Drift::RemoteOrphan {
+                owned: notes_have_ownership_marker(task.notes.as_deref()),
+            },
+        })
+    }));
+    Ok(entries)
+}
+
+/// The drift entries as an `ID  STATE  TITLE` table; orphans show `-` as id.
+pub fn render_drift(entries: &[DriftEntry]) -> String {
+    let rows: Vec<(&str, String, &str)> = entries
+        .iter()
+        .map(|entry| {
+            (
+                entry.todo_id.as_deref().unwrap_or("-"),
+                entry.drift.label(),
+                entry.title.as_str(),
+            )
+        })
+        .collect();
+    let id_width = rows
+        .iter()
+        .map(|(id, _, _)| id.len())
+        .chain(std::iter::once("ID".len()))
+        .max()
+        .unwrap_or_default();
+    let state_width = rows
+        .iter()
+        .map(|(_, state, _)| state.len())
+        .chain(std::iter::once("STATE".len()))
+        .max()
+        .unwrap_or_default();
+    let mut lines = vec![format!(
+        "{:<id_width$}  {:<state_width$}  TITLE",
+        "ID", "STATE"
+    )];
+    for (id, state, title) in rows {
+        lines.push(format!("{id:<id_width$}  {state:<state_width$}  {title}"));
+    }
+    lines.join("\n")
 }
 
 #[cfg(test)]
@@ -588,9 +820,11 @@ mod tests {
     const GOOGLE_OAUTH_CLIENT_SECRET: &str = "test-client-secret";
     use super::*;
     use crate::oauth::{GoogleOAuthClient, GoogleOAuthCredentials};
+    use crate::store::LogEntry;
     use crate::time::test_clock;
     use chrono::{TimeZone, Utc};
-    use wiremock::matchers::{bearer_token, method, path, query_param};
+    use indexmap::IndexMap;
+    use wiremock::matchers::{bearer_token, body_partial_json, method, path, query_param};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
     fn request_count(requests: &[wiremock::Request], method: &str, request_path: &str) -> usize {
@@ -603,37 +837,39 @@ mod tests {
     }
 
     #[test]
-    fn test_due_dates_equivalent_date_only_and_tolerance() {
-        // This unit test verifies the behavior described in the docstring for
-        // `due_dates_equivalent`: Google Tasks API exposes `due` as date-only, so
-        // we consider dues equivalent when they fall on the same UTC day, and we
-        // allow a very small (< 60s) tolerance across boundaries.
-
-        // Same calendar day should be treated as equivalent (date-only semantics)
-        let google_due_same_day = Some("2025-08-20T00:00:00Z".to_string());
-        let todo_due_same_day = Some(
-            chrono::Utc
-                .with_ymd_and_hms(2025, 8, 20, 23, 25, 14)
-                .unwrap(),
+    fn test_google_dues_compare_calendar_days_exactly() {
+        let stockholm: Tz = "Europe/Stockholm".parse().unwrap();
+
+        // All-day dues are published as that exact day, independent of timezone.
+        let all_day = Some(Due::Date(NaiveDate::from_ymd_opt(2025, 8, 20).unwrap()));
+        assert_eq!(
+            google_due_for(all_day, stockholm).as_deref(),
+            Some("2025-08-20T00:00:00.000Z")
         );
-        assert!(due_dates_equivalent(
-            &google_due_same_day,
-            &todo_due_same_day
-        ));
 
-        // Across a calendar boundary but within 60 seconds should still be equivalent
-        let google_due_just_before = Some("2025-08-20T23:59:40Z".to_string());
-        let todo_due_just_after =
-            Some(chrono::Utc.with_ymd_and_hms(2025, 8, 21, 0, 0, 10).unwrap());
-        assert!(due_dates_equivalent(
-            &google_due_just_before,
-            &todo_due_just_after
+        // Timed dues land on their calendar day in the configured timezone.
+        let late_evening = Some(Due::At(
+            Utc.with_ymd_and_hms(2025, 8, 20, 22, 30, 0).unwrap(),
         ));
+        assert_eq!(
+            google_due_for(late_evening, stockholm).as_deref(),
+            Some("2025-08-21T00:00:00.000Z")
+        );
+        assert_eq!(
+            google_due_for(late_evening, Tz::UTC).as_deref(),
+            Some("2025-08-20T00:00:00.000Z")
+        );
 
-        // Different days and beyond the 1-minute tolerance should NOT be equivalent
-        let google_due_far = Some("2025-08-20T00:00:00Z".to_string());
-        let todo_due_far = Some(chrono::Utc.with_ymd_and_hms(2025, 8, 21, 0, 1, 1).unwrap());
-        assert!(!due_dates_equivalent(&google_due_far, &todo_due_far));
+        assert!(google_dues_match(
+            Some("2025-08-20T00:00:00Z"),
+            Some("2025-08-20T00:00:00.000Z")
+        ));
+        assert!(!google_dues_match(
+            Some("2025-08-20T00:00:00Z"),
+            Some("2025-08-21T00:00:00.000Z")
+        ));
+        assert!(!google_dues_match(None, Some("2025-08-20T00:00:00.000Z")));
+        assert!(google_dues_match(None, None));
     }
 
     #[test]
@@ -662,8 +898,16 @@ mod tests {
             due_date: None,
             todo_id: None,
             google_task_id: None,
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra: toml::Table::new(),
         };
-        let desired_with_comment = desired_task_values(&with_comment);
+        let desired_with_comment =
+            desired_task_values(&with_comment, Tz::UTC, &SyncTarget::default());
         assert_eq!(
             desired_with_comment.notes,
             Some(format!("comment\n\n{}", GOOGLE_TASK_OWNERSHIP_MARKER))
@@ -678,14 +922,58 @@ mod tests {
             due_date: None,
             todo_id: None,
             google_task_id: None,
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra: toml::Table::new(),
         };
-        let desired_without_comment = desired_task_values(&without_comment);
+        let desired_without_comment =
+            desired_task_values(&without_comment, Tz::UTC, &SyncTarget::default());
         assert_eq!(
             desired_without_comment.notes,
             Some(GOOGLE_TASK_OWNERSHIP_MARKER.to_string())
         );
     }
 
+    #[test]
+    fn desired_task_notes_include_most_recent_log_entries() {
+        let mut todo = Todo {
+            title: "Title".to_string(),
+            comment: Some("comment".to_string()),
+            expanded: false,
+            done: false,
+            selected: false,
+            due_date: None,
+            todo_id: None,
+            google_task_id: None,
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra: toml::Table::new(),
+        };
+        for hour in 1..=4 {
+            todo.log.push(LogEntry {
+                at: Utc.with_ymd_and_hms(2025, 1, 2, hour, 0, 0).unwrap(),
+                text: format!("note {hour}"),
+            });
+        }
+
+        let desired = desired_task_values(&todo, Tz::UTC, &SyncTarget::default());
+        assert_eq!(
+            desired.notes,
+            Some(format!(
+                "comment\n\n2025-01-02 02:00 note 2\n2025-01-02 03:00 note 3\n2025-01-02 04:00 note 4\n\n{}",
+                GOOGLE_TASK_OWNERSHIP_MARKER
+            ))
+        );
+    }
+
     #[tokio::test]
     async fn test_sync_successful_create_new_task() {
         let mock_server = MockServer::start().await;
@@ -737,6 +1025,13 @@ mod tests {
             due_date: None,
             todo_id: None,
             google_task_id: None,
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra: toml::Table::new(),
         }];
 
         let result = sync_to_tasks_with_base_url(
@@ -745,6 +1040,8 @@ mod tests {
             false,
             &mock_server.uri(),
             &reqwest::Client::new(),
+            Tz::UTC,
+            &SyncTarget::default(),
         )
         .await;
 
@@ -752,6 +1049,78 @@ mod tests {
         assert_eq!(todos[0].google_task_id, Some("new_task_id".to_string()));
     }
 
+    #[tokio::test]
+    async fn test_sync_uses_configured_list_name_and_title_prefix() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/tasks/v1/users/@me/lists"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    { "id": "default_list", "title": "juggler" },
+                    { "id": "work_list", "title": "Work" }
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/tasks/v1/lists/work_list/tasks"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": []
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/tasks/v1/lists/work_list/tasks"))
+            .and(body_partial_json(
+                serde_json::json!({ "title": "todo: Ship it" }),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "work_task",
+                "title": "todo: Ship it",
+                "status": "needsAction"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut todos = vec![Todo {
+            title: "Ship it".to_string(),
+            comment: None,
+            expanded: false,
+            done: false,
+            selected: false,
+            due_date: None,
+            todo_id: None,
+            google_task_id: None,
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra: toml::Table::new(),
+        }];
+        let target = SyncTarget {
+            list_name: "Work".to_string(),
+            title_prefix: "todo: ".to_string(),
+        };
+
+        sync_to_tasks_with_base_url(
+            &mut todos,
+            "test_token",
+            false,
+            &mock_server.uri(),
+            &reqwest::Client::new(),
+            Tz::UTC,
+            &target,
+        )
+        .await
+        .expect("sync to configured list");
+
+        assert_eq!(todos[0].google_task_id, Some("work_task".to_string()));
+    }
+
     #[tokio::test]
     async fn test_sync_authentication_error() {
         let mock_server = MockServer::start().await;
@@ -778,6 +1147,13 @@ mod tests {
             due_date: None,
             todo_id: None,
             google_task_id: None,
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra: toml::Table::new(),
         }];
 
         let result = sync_to_tasks_with_base_url(
@@ -786,12 +1162,45 @@ mod tests {
             false,
             &mock_server.uri(),
             &reqwest::Client::new(),
+            Tz::UTC,
+            &SyncTarget::default(),
         )
         .await;
 
         assert!(result.is_err());
-        let error_msg = result.unwrap_err().to_string();
-        assert!(error_msg.contains("Google Tasks API request failed with status 401"));
+        let error = result.unwrap_err();
+        assert!(!error.is_transient());
+        assert!(
+            error
+                .to_string()
+                .contains("Google Tasks API request failed with status 401")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sync_server_errors_and_rate_limits_are_transient() {
+        for status in [429, 503] {
+            let mock_server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/tasks/v1/users/@me/lists"))
+                .respond_with(ResponseTemplate::new(status))
+                .mount(&mock_server)
+                .await;
+
+            let error = sync_to_tasks_with_base_url(
+                &mut [],
+                "test_token",
+                false,
+                &mock_server.uri(),
+                &reqwest::Client::new(),
+                Tz::UTC,
+                &SyncTarget::default(),
+            )
+            .await
+            .unwrap_err();
+            assert!(matches!(error, JugglerError::Unavailable(_)), "{error:?}");
+            assert!(error.is_transient());
+        }
     }
 
     #[tokio::test]
@@ -822,6 +1231,13 @@ mod tests {
             due_date: None,
             todo_id: None,
             google_task_id: None,
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra: toml::Table::new(),
         }];
 
         let result = sync_to_tasks_with_base_url(
@@ -830,12 +1246,19 @@ mod tests {
             false,
             &mock_server.uri(),
             &reqwest::Client::new(),
+            Tz::UTC,
+            &SyncTarget::default(),
         )
         .await;
 
         assert!(result.is_err());
-        let error_msg = result.unwrap_err().to_string();
-        assert!(error_msg.contains("No 'juggler' task list found in Google Tasks"));
+        let error = result.unwrap_err();
+        assert!(!error.is_transient());
+        assert!(
+            error
+                .to_string()
+                .contains("No 'juggler' task list found in Google Tasks")
+        );
     }
 
     #[tokio::test]
@@ -896,6 +1319,13 @@ mod tests {
             due_date: None,
             todo_id: None,
             google_task_id: Some("existing_task_id".to_string()),
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra: toml::Table::new(),
         }];
 
         let result = sync_to_tasks_with_base_url(
@@ -904,6 +1334,8 @@ mod tests {
             false,
             &mock_server.uri(),
             &reqwest::Client::new(),
+            Tz::UTC,
+            &SyncTarget::default(),
         )
         .await;
 
@@ -965,10 +1397,18 @@ mod tests {
             false,
             &mock_server.uri(),
             &reqwest::Client::new(),
+            Tz::UTC,
+            &SyncTarget::default(),
         )
         .await;
 
-        assert!(result.is_ok());
+        assert_eq!(
+            result.expect("sync succeeds"),
+            SyncReport {
+                deleted: 1,
+                ..SyncReport::default()
+            }
+        );
         let requests = mock_server.received_requests().await.expect("requests");
         assert_eq!(
             request_count(
@@ -1028,10 +1468,18 @@ mod tests {
             false,
             &mock_server.uri(),
             &reqwest::Client::new(),
+            Tz::UTC,
+            &SyncTarget::default(),
         )
         .await;
 
-        assert!(result.is_ok());
+        assert_eq!(
+            result.expect("sync succeeds"),
+            SyncReport {
+                skipped: 1,
+                ..SyncReport::default()
+            }
+        );
         let requests = mock_server.received_requests().await.expect("requests");
         assert_eq!(
             request_count(
@@ -1098,6 +1546,8 @@ mod tests {
             false,
             &mock_server.uri(),
             &reqwest::Client::new(),
+            Tz::UTC,
+            &SyncTarget::default(),
         )
         .await;
 
@@ -1161,6 +1611,13 @@ mod tests {
             due_date: None,
             todo_id: None,
             google_task_id: None,
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra: toml::Table::new(),
         }];
 
         let result = sync_to_tasks_with_base_url(
@@ -1169,6 +1626,8 @@ mod tests {
             true, // dry_run = true
             &mock_server.uri(),
             &reqwest::Client::new(),
+            Tz::UTC,
+            &SyncTarget::default(),
         )
         .await;
 
@@ -1227,9 +1686,16 @@ mod tests {
             expanded: false,
             done: false,
             selected: false,
-            due_date: Some(test_due_date),
+            due_date: Some(Due::At(test_due_date)),
             todo_id: None,
             google_task_id: None,
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra: toml::Table::new(),
         }];
 
         let result = sync_to_tasks_with_base_url(
@@ -1238,6 +1704,8 @@ mod tests {
             false,
             &mock_server.uri(),
             &reqwest::Client::new(),
+            Tz::UTC,
+            &SyncTarget::default(),
         )
         .await;
 
@@ -1295,6 +1763,13 @@ mod tests {
             due_date: None,
             todo_id: None,
             google_task_id: None,
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra: toml::Table::new(),
         }];
 
         let result = sync_to_tasks_with_base_url(
@@ -1303,6 +1778,8 @@ mod tests {
             false,
             &mock_server.uri(),
             &reqwest::Client::new(),
+            Tz::UTC,
+            &SyncTarget::default(),
         )
         .await;
 
@@ -1375,6 +1852,13 @@ mod tests {
             due_date: None,
             todo_id: None,
             google_task_id: None,
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra: toml::Table::new(),
         }];
 
         let credentials = GoogleOAuthCredentials {
@@ -1384,7 +1868,7 @@ mod tests {
         };
 
         let oauth_token_url = format!("{}/token", oauth_mock_server.uri());
-        let oauth_client = GoogleOAuthClient::new_with_custom_oauth_url(
+        let mut oauth_client = GoogleOAuthClient::new_with_custom_oauth_url(
             credentials,
             reqwest::Client::new(),
             oauth_token_url,
@@ -1393,9 +1877,11 @@ mod tests {
 
         let result = sync_to_tasks_with_oauth_and_base_url(
             &mut todos,
-            oauth_client,
+            &mut oauth_client,
             false,
             &mock_server.uri(),
+            Tz::UTC,
+            &SyncTarget::default(),
         )
         .await;
 
@@ -1455,6 +1941,13 @@ mod tests {
             due_date: None,
             todo_id: None,
             google_task_id: None,
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra: toml::Table::new(),
         }];
 
         let credentials = GoogleOAuthCredentials {
@@ -1464,7 +1957,7 @@ mod tests {
         };
 
         let oauth_token_url = format!("{}/token", oauth_mock_server.uri());
-        let oauth_client = GoogleOAuthClient::new_with_custom_oauth_url(
+        let mut oauth_client = GoogleOAuthClient::new_with_custom_oauth_url(
             credentials,
             reqwest::Client::new(),
             oauth_token_url,
@@ -1473,9 +1966,11 @@ mod tests {
 
         let result = sync_to_tasks_with_oauth_and_base_url(
             &mut todos,
-            oauth_client,
+            &mut oauth_client,
             true, // dry_run = true
             &mock_server.uri(),
+            Tz::UTC,
+            &SyncTarget::default(),
         )
         .await;
 
@@ -1522,6 +2017,13 @@ mod tests {
             due_date: None,
             todo_id: None,
             google_task_id: Some("t1".to_string()),
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra: toml::Table::new(),
         }];
 
         // Dry-run should NOT issue a PUT; no PUT mock is defined
@@ -1531,6 +2033,8 @@ mod tests {
             true,
             &mock_server.uri(),
             &reqwest::Client::new(),
+            Tz::UTC,
+            &SyncTarget::default(),
         )
         .await;
 
@@ -1574,6 +2078,8 @@ mod tests {
             true,
             &mock_server.uri(),
             &reqwest::Client::new(),
+            Tz::UTC,
+            &SyncTarget::default(),
         )
         .await;
 
@@ -1652,6 +2158,13 @@ mod tests {
             due_date: None,
             todo_id: None,
             google_task_id: Some("existing_oauth_task_id".to_string()),
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra: toml::Table::new(),
         }];
 
         let credentials = GoogleOAuthCredentials {
@@ -1661,7 +2174,7 @@ mod tests {
         };
 
         let oauth_token_url = format!("{}/token", oauth_mock_server.uri());
-        let oauth_client = GoogleOAuthClient::new_with_custom_oauth_url(
+        let mut oauth_client = GoogleOAuthClient::new_with_custom_oauth_url(
             credentials,
             reqwest::Client::new(),
             oauth_token_url,
@@ -1670,9 +2183,11 @@ mod tests {
 
         let result = sync_to_tasks_with_oauth_and_base_url(
             &mut todos,
-            oauth_client,
+            &mut oauth_client,
             false,
             &mock_server.uri(),
+            Tz::UTC,
+            &SyncTarget::default(),
         )
         .await;
 
@@ -1706,6 +2221,13 @@ mod tests {
             due_date: None,
             todo_id: None,
             google_task_id: None,
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra: toml::Table::new(),
         }];
 
         let credentials = GoogleOAuthCredentials {
@@ -1715,7 +2237,7 @@ mod tests {
         };
 
         let oauth_token_url = format!("{}/token", oauth_mock_server.uri());
-        let oauth_client = GoogleOAuthClient::new_with_custom_oauth_url(
+        let mut oauth_client = GoogleOAuthClient::new_with_custom_oauth_url(
             credentials,
             reqwest::Client::new(),
             oauth_token_url,
@@ -1724,9 +2246,11 @@ mod tests {
 
         let result = sync_to_tasks_with_oauth_and_base_url(
             &mut todos,
-            oauth_client,
+            &mut oauth_client,
             false,
             GOOGLE_TASKS_BASE_URL, // Won't be reached due to OAuth failure
+            Tz::UTC,
+            &SyncTarget::default(),
         )
         .await;
 
@@ -1796,6 +2320,13 @@ mod tests {
             due_date: None,
             todo_id: None,
             google_task_id: None,
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra: toml::Table::new(),
         }];
 
         let result = sync_to_tasks_with_base_url(
@@ -1804,6 +2335,8 @@ mod tests {
             false,
             &mock_server.uri(),
             &reqwest::Client::new(),
+            Tz::UTC,
+            &SyncTarget::default(),
         )
         .await;
         assert!(result.is_ok());
@@ -1885,6 +2418,8 @@ mod tests {
             false,
             &mock_server.uri(),
             &reqwest::Client::new(),
+            Tz::UTC,
+            &SyncTarget::default(),
         )
         .await;
         assert!(result.is_ok());
@@ -1898,4 +2433,116 @@ mod tests {
             1
         );
     }
+
+    #[tokio::test]
+    async fn test_drift_report_classifies_todos_and_orphans_without_writing() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/tasks/v1/users/@me/lists"))
+            .and(bearer_token("test_token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [{ "id": "test_list_id", "title": "juggler" }]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let marker = GOOGLE_TASK_OWNERSHIP_MARKER;
+        Mock::given(method("GET"))
+            .and(path("/tasks/v1/lists/test_list_id/tasks"))
+            .and(bearer_token("test_token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    { "id": "same", "title": "j:Same", "notes": marker, "status": "needsAction" },
+                    {
+                        "id": "stale",
+                        "title": "j:Old title",
+                        "notes": marker,
+                        "status": "needsAction",
+                        "due": "2025-01-07T00:00:00.000Z"
+                    },
+                    { "id": "owned", "title": "j:Gone", "notes": marker, "status": "needsAction" },
+                    { "id": "manual", "title": "Manual", "status": "needsAction" }
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let todo = |id: &str, title: &str, google_task_id: Option<&str>, done: bool| Todo {
+            title: title.to_string(),
+            comment: None,
+            expanded: false,
+            done,
+            selected: false,
+            due_date: None,
+            todo_id: Some(id.to_string()),
+            google_task_id: google_task_id.map(str::to_string),
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra: toml::Table::new(),
+        };
+        let todos = vec![
+            todo("T1", "Same", Some("same"), false),
+            todo("T2", "New title", Some("stale"), true),
+            todo("T3", "Deleted remotely", Some("missing"), false),
+            todo("T4", "Local only", None, false),
+        ];
+
+        let entries = drift_report_with_base_url(
+            &todos,
+            "test_token",
+            &mock_server.uri(),
+            &reqwest::Client::new(),
+            Tz::UTC,
+            &SyncTarget::default(),
+        )
+        .await
+        .expect("drift report succeeds");
+
+        let drifts: Vec<(Option<&str>, &Drift)> = entries
+            .iter()
+            .map(|entry| (entry.todo_id.as_deref(), &entry.drift))
+            .collect();
+        assert_eq!(
+            drifts,
+            vec![
+                (Some("T1"), &Drift::InSync),
+                (
+                    Some("T2"),
+                    &Drift::NeedsUpdate {
+                        fields: vec![TaskField::Title, TaskField::Status, TaskField::Due],
+                    }
+                ),
+                (Some("T3"), &Drift::MissingRemotely),
+                (Some("T4"), &Drift::NeverSynced),
+                (None, &Drift::RemoteOrphan { owned: true }),
+                (None, &Drift::RemoteOrphan { owned: false }),
+            ]
+        );
+        assert_eq!(
+            serde_json::to_value(&entries[1]).unwrap(),
+            serde_json::json!({
+                "todo_id": "T2",
+                "google_task_id": "stale",
+                "title": "New title",
+                "state": "needs_update",
+                "fields": ["title", "status", "due"],
+            })
+        );
+        assert_eq!(
+            render_drift(&entries).lines().nth(2),
+            Some("T2  needs update (title, status, due)  New title")
+        );
+
+        let requests = mock_server.received_requests().await.expect("requests");
+        assert!(
+            requests
+                .iter()
+                .all(|request| request.method.as_str() == "GET")
+        );
+    }
 }
@@ -0,0 +1,382 @@
+//! User hook scripts run on todo lifecycle events.
+//!
+//! An executable in `<juggler dir>/hooks/` named after an event runs with a
+//! JSON document on stdin, `{"event": "on-done", "todos": [...]}`, where the
+//! todos have the `juggler export --format json` shape. `on-*` and `post-*`
+//! hooks are notifications: their output is ignored, and a failure is
+//! reported without undoing anything. `pre-save` and `pre-sync` run before
+//! the operation and may veto it by exiting non-zero (stderr becomes the
+//! error), or print `{"todos": [...]}` to replace the todos with those ids.
+//! `pre-sync` replacements apply only to what is sent to Google Tasks and are
+//! never written back to the store.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::commands::export::JsonTodo;
+use crate::error::{JugglerError, Result};
+use crate::fields::FieldSchema;
+use crate::store::validate_blocked_by;
+use crate::ui::Todo;
+
+pub const HOOKS_DIR_NAME: &str = "hooks";
+
+/// Environment variable carrying the event name, for scripts that handle
+/// several events through symlinks.
+const HOOK_EVENT_ENV_VAR: &str = "JUGGLER_HOOK_EVENT";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    OnAdd,
+    OnDone,
+    OnModify,
+    PreSave,
+    PostSave,
+    PreSync,
+    PostSync,
+}
+
+impl HookEvent {
+    /// File name of the hook in the hooks directory.
+    pub fn name(self) -> &'static str {
+        match self {
+            HookEvent::OnAdd => "on-add",
+            HookEvent::OnDone => "on-done",
+            HookEvent::OnModify => "on-modify",
+            HookEvent::PreSave => "pre-save",
+            HookEvent::PostSave => "post-save",
+            HookEvent::PreSync => "pre-sync",
+            HookEvent::PostSync => "post-sync",
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+struct HookInput {
+    event: &'static str,
+    todos: Vec<JsonTodo>,
+}
+
+#[derive(serde::Deserialize)]
+struct HookOutput {
+    todos: Vec<JsonTodo>,
+}
+
+/// Where to look for hook executables. The default has no directory and
+/// runs nothing.
+#[derive(Debug, Clone, Default)]
+pub struct Hooks {
+    dir: Option<PathBuf>,
+}
+
+impl Hooks {
+    #[cfg(test)]
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir: Some(dir) }
+    }
+
+    /// Hooks in the juggler directory that holds `todos_file`.
+    pub fn for_todos_file(todos_file: &Path) -> Self {
+        Self {
+            dir: todos_file.parent().map(|dir| dir.join(HOOKS_DIR_NAME)),
+        }
+    }
+
+    /// Whether a hooks directory exists, so callers can skip diffing todos
+    /// when nothing would run.
+    pub fn is_enabled(&self) -> bool {
+        self.dir.as_deref().is_some_and(Path::is_dir)
+    }
+
+    fn executable(&self, event: HookEvent) -> Option<PathBuf> {
+        let path = self.dir.as_ref()?.join(event.name());
+        is_executable(&path).then_some(path)
+    }
+
+    /// Run the `event` hook, if installed, with `todos` as its input.
+    pub fn notify(&self, event: HookEvent, todos: &[Todo]) -> Result<()> {
+        match self.executable(event) {
+            Some(path) => run_hook(&path, event, todos).map(|_| ()),
+            None => Ok(()),
+        }
+    }
+
+    /// Run the `event` pre-hook, if installed. A failing hook vetoes the
+    /// operation; todos it prints replace the ones with the same id.
+    pub fn filter(
+        &self,
+        event: HookEvent,
+        todos: &mut [Todo],
+        field_schema: &FieldSchema,
+    ) -> Result<()> {
+        let Some(path) = self.executable(event) else {
+            return Ok(());
+        };
+        let stdout = run_hook(&path, event, todos)?;
+        if stdout.trim().is_empty() {
+            return Ok(());
+        }
+
+        let name = event.name();
+        let output: HookOutput = serde_json::from_str(&stdout)
+            .map_err(|e| JugglerError::hook(format!("{name} hook printed invalid JSON: {e}")))?;
+        let positions: HashMap<String, usize> = todos
+            .iter()
+            .enumerate()
+            .filter_map(|(index, todo)| todo.todo_id.clone().map(|todo_id| (todo_id, index)))
+            .collect();
+        for replacement in output.todos {
+            let todo_id = replacement.id.clone().unwrap_or_default();
+            let index = *positions.get(&todo_id).ok_or_else(|| {
+                JugglerError::hook(format!(
+                    "{name} hook returned unknown todo id '{todo_id}'; hooks may change todos but not add or remove them"
+                ))
+            })?;
+            let item = replacement.into_item(field_schema).map_err(|e| {
+                JugglerError::hook(format!("{name} hook returned invalid todo {todo_id}: {e}"))
+            })?;
+            let mut todo = Todo::from(item);
+            todo.expanded = todos[index].expanded;
+            todo.selected = todos[index].selected;
+            todos[index] = todo;
+        }
+
+        validate_blocked_by(todos.iter().filter_map(|todo| {
+            todo.todo_id
+                .as_deref()
+                .map(|todo_id| (todo_id, todo.blocked_by.as_slice()))
+        }))
+        .map_err(|e| JugglerError::hook(format!("{name} hook returned invalid todos: {e}")))
+    }
+
+    /// Run `on-add`, `on-done`, and `on-modify` for what changed between
+    /// `before` and `after`.
+    pub fn notify_changes(&self, before: &[Todo], after: &[Todo]) -> Result<()> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+        for (event, todos) in lifecycle_events(before, after) {
+            self.notify(event, &todos)?;
+        }
+        Ok(())
+    }
+}
+
+/// Group the todos in `after` by the lifecycle event they represent. Todos
+/// are matched by id; those without one have not been stored yet and are
+/// skipped.
+pub fn lifecycle_events(before: &[Todo], after: &[Todo]) -> Vec<(HookEvent, Vec<Todo>)> {
+    let previous: HashMap<&str, &Todo> = before
+        .iter()
+        .filter_map(|todo| todo.todo_id.as_deref().map(|todo_id| (todo_id, todo)))
+        .collect();
+    let mut added = Vec::new();
+    let mut done = Vec::new();
+    let mut modified = Vec::new();
+    for todo in after {
+        let Some(todo_id) = todo.todo_id.as_deref() else {
+            continue;
+        };
+        match previous.get(todo_id) {
+            None => added.push(todo.clone()),
+            Some(old) if !old.done && todo.done => done.push(todo.clone()),
+            Some(old) if JsonTodo::from(*old) != JsonTodo::from(todo) => {
+                modified.push(todo.clone())
+            }
+            Some(_) => {}
+        }
+    }
+
+    [
+        (HookEvent::OnAdd, added),
+        (HookEvent::OnDone, done),
+        (HookEvent::OnModify, modified),
+    ]
+    .into_iter()
+    .filter(|(_, todos)| !todos.is_empty())
+    .collect()
+}
+
+/// Run one hook with the todos on stdin and return what it printed.
+fn run_hook(path: &Path, event: HookEvent, todos: &[Todo]) -> Result<String> {
+    let name = event.name();
+    let input = serde_json::to_vec(&HookInput {
+        event: name,
+        todos: todos.iter().map(JsonTodo::from).collect(),
+    })?;
+    let mut child = Command::new(path)
+        .env(HOOK_EVENT_ENV_VAR, name)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| JugglerError::hook(format!("Failed to run {}: {e}", path.display())))?;
+
+    // Feed stdin from a thread so a hook that prints before reading cannot
+    // deadlock against us. A hook that exits without reading is fine.
+    let mut stdin = child.stdin.take().expect("hook stdin is piped");
+    let writer = std::thread::spawn(move || stdin.write_all(&input));
+    let output = child.wait_with_output()?;
+    let _ = writer.join();
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = match stderr.trim() {
+            "" => output.status.to_string(),
+            message => message.to_string(),
+        };
+        return Err(JugglerError::hook(format!("{name} hook failed: {reason}")));
+    }
+    String::from_utf8(output.stdout)
+        .map_err(|_| JugglerError::hook(format!("{name} hook printed non-UTF-8 output")))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path)
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::TodoItem;
+    use indexmap::IndexMap;
+
+    fn todo(id: &str, title: &str, done: bool) -> Todo {
+        Todo::from(TodoItem {
+            todo_id: Some(id.to_string()),
+            title: title.to_string(),
+            comment: None,
+            done,
+            due_date: None,
+            google_task_id: None,
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra: toml::Table::new(),
+        })
+    }
+
+    #[cfg(unix)]
+    fn install_hook(dir: &Path, event: HookEvent, script: &str) {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::create_dir_all(dir).unwrap();
+        let path = dir.join(event.name());
+        std::fs::write(&path, format!("#!/bin/sh\n{script}\n")).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[test]
+    fn lifecycle_events_classify_added_done_and_modified_todos() {
+        let before = vec![
+            todo("T1", "a", false),
+            todo("T2", "b", false),
+            todo("T3", "c", true),
+        ];
+        let mut after = before.clone();
+        after[0].done = true;
+        after[1].title = "b edited".to_string();
+        after[2].expanded = true;
+        after.push(todo("T4", "d", false));
+
+        let events = lifecycle_events(&before, &after);
+        let events: Vec<(HookEvent, Vec<&str>)> = events
+            .iter()
+            .map(|(event, todos)| {
+                let ids = todos.iter().filter_map(|t| t.todo_id.as_deref()).collect();
+                (*event, ids)
+            })
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                (HookEvent::OnAdd, vec!["T4"]),
+                (HookEvent::OnDone, vec!["T1"]),
+                (HookEvent::OnModify, vec!["T2"]),
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn notify_passes_event_and_todos_as_json() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let hooks_dir = temp_dir.path().join(HOOKS_DIR_NAME);
+        let received = temp_dir.path().join("received.json");
+        install_hook(
+            &hooks_dir,
+            HookEvent::OnDone,
+            &format!("cat > '{}'", received.display()),
+        );
+        let hooks = Hooks::new(hooks_dir);
+
+        hooks
+            .notify(HookEvent::OnDone, &[todo("T1", "a", true)])
+            .unwrap();
+        hooks
+            .notify(HookEvent::OnAdd, &[todo("T2", "not installed", false)])
+            .unwrap();
+
+        let payload: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&received).unwrap()).unwrap();
+        assert_eq!(payload["event"], "on-done");
+        assert_eq!(payload["todos"][0]["id"], "T1");
+        assert_eq!(payload["todos"][0]["done"], true);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn pre_hooks_can_veto_or_rewrite_todos() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let hooks_dir = temp_dir.path().join(HOOKS_DIR_NAME);
+        let hooks = Hooks::new(hooks_dir.clone());
+        let mut todos = vec![todo("T1", "a", false), todo("T2", "b", false)];
+
+        install_hook(
+            &hooks_dir,
+            HookEvent::PreSave,
+            r#"sed 's/"title":"a"/"title":"A"/'"#,
+        );
+        hooks
+            .filter(HookEvent::PreSave, &mut todos, &FieldSchema::default())
+            .unwrap();
+        assert_eq!(todos[0].title, "A");
+        assert_eq!(todos[1].title, "b");
+
+        install_hook(
+            &hooks_dir,
+            HookEvent::PreSave,
+            "echo 'not on Fridays' >&2; exit 1",
+        );
+        let err = hooks
+            .filter(HookEvent::PreSave, &mut todos, &FieldSchema::default())
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Hook error: pre-save hook failed: not on Fridays"
+        );
+
+        install_hook(
+            &hooks_dir,
+            HookEvent::PreSave,
+            r#"echo '{"todos":[{"id":"T9","title":"new"}]}'"#,
+        );
+        assert!(
+            hooks
+                .filter(HookEvent::PreSave, &mut todos, &FieldSchema::default())
+                .is_err()
+        );
+    }
+}
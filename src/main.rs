@@ -2,26 +2,73 @@ use env_logger::Env;
 
 use log::{error, info};
 
+mod commands;
+mod completions;
 mod config;
 mod credential_storage;
+mod doctor;
+mod due;
 mod error;
+mod fields;
+mod fsck;
 mod google_tasks;
+mod hooks;
 mod oauth;
+mod output;
+mod query;
+mod serve;
+mod settings;
+mod stats;
 mod store;
 mod time;
 mod ui;
 
+use std::io::IsTerminal;
+use std::process::ExitCode;
+
 use error::{JugglerError, Result};
 
-use clap::{Parser, Subcommand};
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand};
+use clap_complete::ArgValueCandidates;
+use commands::add::{NewTodo, add_todo};
+use commands::agenda::{agenda_json, render_agenda};
+use commands::config::{config_get, config_json, config_set, render_config};
+use commands::export::{ExportFormat, render_export};
+use commands::import::{ImportFormat, ImportReport, import_todos};
+use commands::list::{
+    ListFormat, ListOptions, SortKey, list_entries, parse_window, render_list, select_todos,
+};
+use commands::mutate::{
+    Mutation, Targets, edit_todo, mutate_todos, parse_due_op, parse_snooze_days, remove_todos,
+};
+use commands::session::Session;
+use commands::status::{OVERDUE_EXIT_CODE, count_status, render_status};
+use commands::sync::{WatchOptions, save_google_task_ids, sync_google_tasks, watch_google_tasks};
+use completions::{
+    all_id_candidates, done_id_candidates, pending_id_candidates, write_manpage, write_registration,
+};
 use config::{
-    CREDENTIAL_KEYRING_ACCOUNT_GOOGLE_TASKS, CREDENTIAL_KEYRING_SERVICE, get_todos_file_path,
+    CREDENTIAL_KEYRING_SERVICE, get_config_file_path, get_profile_dir, get_todos_file_path,
+    is_valid_profile_name, keyring_account,
 };
 use credential_storage::{CredentialError, CredentialStore, KeyringCredentialStore};
-use google_tasks::{GoogleOAuthClient, GoogleOAuthCredentials, sync_to_tasks_with_oauth};
+use doctor::{ensure_no_failures, render_checks, run_doctor};
+use due::resolve_timezone;
+use fsck::{FsckReport, check_todos_file, ensure_no_errors, repair_todos_file_with_clock};
+use google_tasks::{
+    GoogleOAuthClient, GoogleOAuthCredentials, drift_report_with_oauth, render_drift,
+};
+use hooks::Hooks;
 use oauth::run_oauth_flow;
-use store::{load_todos, store_todos};
-use ui::{App, ExternalEditor, Todo};
+use output::{OutputFormat, Report};
+use query::{blocked_ids, parse_query};
+use serve::{bind, generate_token, run_server};
+use settings::{ConfigFile, Override, SettingKey, SettingSource, Settings, editor_env_override};
+use stats::{StatsFormat, StatsPeriod, compute_stats, render_stats};
+use store::{load_pending_dues, load_todos, load_todos_and_schema, write_file_atomically};
+use time::system_clock;
+use ui::{App, BatchOp, ExternalEditor, Todo};
 
 fn create_oauth_client_from_keychain(
     cred_store: &dyn CredentialStore,
@@ -44,6 +91,20 @@ fn create_oauth_client_from_keychain(
     Ok(GoogleOAuthClient::new(credentials, http_client))
 }
 
+/// Whether a global flag's value came from the command line or its
+/// environment variable.
+fn cli_setting_source(
+    matches: &ArgMatches,
+    id: &str,
+    flag: &'static str,
+    env_var: &'static str,
+) -> SettingSource {
+    match matches.value_source(id) {
+        Some(ValueSource::EnvVariable) => SettingSource::Env(env_var),
+        _ => SettingSource::Flag(flag),
+    }
+}
+
 fn required_google_oauth_value(
     cli_value: Option<&str>,
     value_label: &str,
@@ -56,7 +117,8 @@ fn required_google_oauth_value(
         .map(ToString::to_string)
         .ok_or_else(|| {
             JugglerError::config(format!(
-                "Missing Google OAuth {value_label}. Provide `{flag_name}` or set {env_var_name} for login and sync commands."
+                "Missing Google OAuth {value_label}. Provide `{flag_name}`, set {env_var_name}, or run `juggler config set google_tasks.oauth_{}` for login and sync commands.",
+                value_label.replace(' ', "_")
             ))
         })
 }
@@ -77,8 +139,29 @@ fn required_google_oauth_value_or_skip_sync(
     }
 }
 
+fn parse_profile_name(name: &str) -> std::result::Result<String, String> {
+    if is_valid_profile_name(name) {
+        Ok(name.to_string())
+    } else {
+        Err("use only letters, digits, '-', and '_'".to_string())
+    }
+}
+
+/// Parse `--interval` for `sync --watch`; it must be positive.
+fn parse_watch_interval(input: &str) -> Result<std::time::Duration> {
+    parse_window(input)?
+        .to_std()
+        .ok()
+        .filter(|interval| !interval.is_zero())
+        .ok_or_else(|| {
+            JugglerError::config(format!("Sync interval must be positive, got '{input}'"))
+        })
+}
+
+/// Save the `google_task_id`s the sync of `before` assigned in `synced`.
 fn maybe_persist_todos_after_sync(
-    todos: &mut [Todo],
+    before: &[Todo],
+    synced: &[Todo],
     todos_file: &std::path::Path,
     dry_run: bool,
 ) -> Result<()> {
@@ -86,21 +169,21 @@ fn maybe_persist_todos_after_sync(
         info!("Dry-run mode: skipping local TODO save after sync.");
         return Ok(());
     }
-    store_todos(todos, todos_file)
+    save_google_task_ids(todos_file, before, synced)
 }
 
-fn save_todos_before_sync(todos: &mut [Todo], todos_file: &std::path::Path) -> Result<()> {
-    store_todos(todos, todos_file)
+fn save_todos_before_sync(session: &Session, todos: &mut Vec<Todo>) -> Result<()> {
+    session.save(todos)
 }
 
 fn prepare_tui_sync_on_exit(
-    todos: &mut [Todo],
-    todos_file: &std::path::Path,
+    session: &Session,
+    todos: &mut Vec<Todo>,
     oauth_client_id: Option<&str>,
     oauth_client_secret: Option<&str>,
 ) -> Result<Option<(String, String)>> {
     // Always persist local edits first so sync precondition failures do not lose data.
-    if let Err(e) = save_todos_before_sync(todos, todos_file) {
+    if let Err(e) = save_todos_before_sync(session, todos) {
         error!("Warning: Failed to save todos before sync: {e}");
         return Err(e);
     }
@@ -128,6 +211,95 @@ fn prepare_tui_sync_on_exit(
     Ok(Some((oauth_client_id, oauth_client_secret)))
 }
 
+/// Report an id-addressed change: the ids and the reducer's status message.
+fn report_mutation(report: &mut Report, mutation: Mutation) -> Result<()> {
+    let Mutation { ids, message } = mutation;
+    report.set("ids", ids)?;
+    report.set("message", &message)?;
+    if let Some(message) = message {
+        report.text(message);
+    }
+    Ok(())
+}
+
+/// Split `snooze`/`due` positionals into the targets and the trailing value.
+/// clap cannot make the ids optional ahead of a required value, so the ids and
+/// the value share one positional and `--where` replaces the ids.
+fn split_targets(mut args: Vec<String>, query: Option<String>) -> Result<(Targets, String)> {
+    let value = args.pop().unwrap_or_default();
+    match (args.is_empty(), &query) {
+        (true, None) => Err(JugglerError::config(
+            "Give the todo ids before the value, or select todos with --where",
+        )),
+        (false, Some(_)) => Err(JugglerError::config(
+            "Give either todo ids or --where, not both",
+        )),
+        _ => Ok((Targets::new(args, query), value)),
+    }
+}
+
+fn import_summary(report: &ImportReport, dry_run: bool) -> String {
+    let verb = if dry_run { "Would add" } else { "Added" };
+    let mut lines: Vec<String> = report
+        .added
+        .iter()
+        .map(|todo| {
+            format!(
+                "{verb} {} {}",
+                todo.todo_id.as_deref().unwrap_or_default(),
+                todo.title
+            )
+        })
+        .collect();
+    lines.extend(
+        report
+            .duplicates
+            .iter()
+            .map(|title| format!("Skipped duplicate: {title}")),
+    );
+    lines.push(format!(
+        "{} todo(s) {}, {} duplicate(s) skipped",
+        report.added.len(),
+        if dry_run { "to add" } else { "added" },
+        report.duplicates.len()
+    ));
+    lines.join("\n")
+}
+
+fn fsck_issues_json(report: &FsckReport) -> serde_json::Value {
+    report
+        .issues
+        .iter()
+        .map(|issue| {
+            serde_json::json!({
+                "location": issue.location,
+                "line": issue.line,
+                "severity": issue.severity().to_string(),
+                "message": issue.message,
+                "repairable": issue.kind.is_repairable(),
+            })
+        })
+        .collect()
+}
+
+fn fsck_summary(report: &FsckReport) -> String {
+    if report.issues.is_empty() {
+        return "No problems found.".to_string();
+    }
+
+    let mut summary = format!(
+        "{} problem(s) found ({} error(s), {} warning(s))",
+        report.issues.len(),
+        report.error_count(),
+        report.warning_count()
+    );
+    let repairable = report.repairable_count();
+    if repairable > 0 {
+        summary.push_str(&format!("; {repairable} can be fixed with --repair"));
+    }
+    summary
+}
+
 #[derive(Parser)]
 #[command(name = "juggler")]
 #[command(about = "A TODO juggler TUI application")]
@@ -140,6 +312,17 @@ struct Cli {
     )]
     juggler_dir: Option<std::path::PathBuf>,
 
+    #[arg(
+        long,
+        env = "JUGGLER_PROFILE",
+        global = true,
+        value_name = "NAME",
+        value_parser = parse_profile_name,
+        conflicts_with = "juggler_dir",
+        help = "Use the named profile: its own data directory, config.toml, and Google login (or JUGGLER_PROFILE); the login is shared by same-named profiles under any JUGGLER_DIR"
+    )]
+    profile: Option<String>,
+
     #[arg(
         long,
         env = "GOOGLE_OAUTH_CLIENT_ID",
@@ -159,6 +342,24 @@ struct Cli {
     )]
     google_oauth_client_secret: Option<String>,
 
+    #[arg(
+        long,
+        env = "JUGGLER_TIMEZONE",
+        global = true,
+        value_name = "TZ",
+        help = "IANA timezone for all-day due dates (default: system timezone, or JUGGLER_TIMEZONE)"
+    )]
+    timezone: Option<String>,
+
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        default_value_t = OutputFormat::Text,
+        help = "Print prose, or one JSON result object per command for scripts"
+    )]
+    output: OutputFormat,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -174,36 +375,426 @@ enum Commands {
         port: u16,
     },
     Logout,
+    #[command(about = "Add a todo and print its id")]
+    Add {
+        #[arg(help = "Todo title")]
+        title: String,
+        #[arg(
+            long,
+            allow_hyphen_values = true,
+            help = "Due as a relative offset (3d, -2h, 30m) or YYYY-MM-DD / RFC 3339"
+        )]
+        due: Option<String>,
+        #[arg(long, help = "Free-form comment")]
+        comment: Option<String>,
+    },
+    #[command(about = "Print pending todos grouped by day: overdue, today, tomorrow, and onward")]
+    Agenda {
+        #[arg(
+            long,
+            default_value_t = 7,
+            value_parser = clap::value_parser!(u32).range(1..),
+            help = "Number of days to show, starting today"
+        )]
+        days: u32,
+    },
+    #[command(
+        about = "Show created vs completed counts, pending age, overdue and snooze statistics"
+    )]
+    Stats {
+        #[arg(long, value_enum, default_value_t = StatsPeriod::Day, help = "Group counts by day or week")]
+        by: StatsPeriod,
+        #[arg(
+            long,
+            default_value_t = 14,
+            value_parser = clap::value_parser!(u32).range(1..),
+            help = "Number of days or weeks to show, ending with the current one"
+        )]
+        periods: u32,
+        #[arg(long, value_enum, default_value_t = StatsFormat::Text, help = "Output format")]
+        format: StatsFormat,
+    },
+    #[command(about = format!(
+        "Print overdue, due-soon, and pending counts for a shell prompt; exits {OVERDUE_EXIT_CODE} when something is overdue"
+    ))]
+    Status {
+        #[arg(
+            long,
+            value_name = "FORMAT",
+            help = "Template with {overdue}, {soon}, and {pending} (default: status_format setting, \"{overdue}! {soon}~ {pending}\")"
+        )]
+        format: Option<String>,
+    },
+    #[command(about = "List todos, optionally filtered, in a table, JSON, or TSV")]
+    List {
+        #[arg(long, help = "Include completed todos")]
+        done: bool,
+        #[arg(long, help = "Only pending todos past their due")]
+        overdue: bool,
+        #[arg(
+            long,
+            value_name = "DURATION",
+            help = "Only todos due within this window from now (e.g. 2d, 12h), including overdue ones"
+        )]
+        due_within: Option<String>,
+        #[arg(
+            long,
+            value_name = "TEXT",
+            help = "Only todos whose title or comment contains TEXT (case-insensitive)"
+        )]
+        grep: Option<String>,
+        #[arg(
+            long = "where",
+            value_name = "QUERY",
+            help = "Only todos matching QUERY, e.g. 'due<2d and not done'; done todos are included unless the query excludes them"
+        )]
+        query: Option<String>,
+        #[arg(long, value_enum, default_value_t = SortKey::Due, help = "Sort order")]
+        sort: SortKey,
+        #[arg(long, value_enum, default_value_t = ListFormat::Table, help = "Output format")]
+        format: ListFormat,
+    },
+    #[command(about = "Mark todos done")]
+    Done {
+        #[arg(
+            required_unless_present = "query",
+            conflicts_with = "query",
+            value_name = "ID",
+            add = ArgValueCandidates::new(pending_id_candidates)
+        )]
+        ids: Vec<String>,
+        #[arg(
+            long = "where",
+            value_name = "QUERY",
+            help = "Apply to every todo matching QUERY instead of listed ids"
+        )]
+        query: Option<String>,
+    },
+    #[command(about = "Mark todos not done")]
+    Undone {
+        #[arg(
+            required_unless_present = "query",
+            conflicts_with = "query",
+            value_name = "ID",
+            add = ArgValueCandidates::new(done_id_candidates)
+        )]
+        ids: Vec<String>,
+        #[arg(
+            long = "where",
+            value_name = "QUERY",
+            help = "Apply to every todo matching QUERY instead of listed ids"
+        )]
+        query: Option<String>,
+    },
+    #[command(
+        about = "Move todo dues by whole days; overdue todos move relative to now",
+        override_usage = "juggler snooze <ID>... <DAYS>\n       juggler snooze --where <QUERY> <DAYS>"
+    )]
+    Snooze {
+        #[arg(
+            required = true,
+            value_name = "ID",
+            add = ArgValueCandidates::new(pending_id_candidates),
+            help = "Todo ids, then whole days, e.g. T1 T2 1d (pass negative values after `--`: T1 -- -2d)"
+        )]
+        ids_and_days: Vec<String>,
+        #[arg(
+            long = "where",
+            value_name = "QUERY",
+            help = "Apply to every todo matching QUERY instead of listed ids"
+        )]
+        query: Option<String>,
+    },
+    #[command(
+        about = "Set or clear todo dues",
+        override_usage = "juggler due <ID>... <DUE>\n       juggler due --where <QUERY> <DUE>"
+    )]
+    Due {
+        #[arg(
+            required = true,
+            value_name = "ID",
+            add = ArgValueCandidates::new(all_id_candidates),
+            help = "Todo ids, then a relative offset (3d; negative after `--`), YYYY-MM-DD, RFC 3339, or `none` to clear"
+        )]
+        ids_and_due: Vec<String>,
+        #[arg(
+            long = "where",
+            value_name = "QUERY",
+            help = "Apply to every todo matching QUERY instead of listed ids"
+        )]
+        query: Option<String>,
+    },
+    #[command(about = "Edit a todo in the external editor")]
+    Edit {
+        #[arg(value_name = "ID", add = ArgValueCandidates::new(all_id_candidates))]
+        id: String,
+    },
+    #[command(about = "Delete todos and drop them from other todos' blocked_by")]
+    Rm {
+        #[arg(
+            required_unless_present = "query",
+            conflicts_with = "query",
+            value_name = "ID",
+            add = ArgValueCandidates::new(all_id_candidates)
+        )]
+        ids: Vec<String>,
+        #[arg(
+            long = "where",
+            value_name = "QUERY",
+            help = "Apply to every todo matching QUERY instead of listed ids"
+        )]
+        query: Option<String>,
+    },
+    #[command(about = "Export todos as JSON (lossless), CSV, Markdown, or todo.txt")]
+    Export {
+        #[arg(long, value_enum, default_value_t = ExportFormat::Json, help = "Output format")]
+        format: ExportFormat,
+        #[arg(value_name = "FILE", help = "Write to FILE instead of standard output")]
+        file: Option<std::path::PathBuf>,
+        // `--output` itself is the global result format, so the flag form of
+        // FILE needs another name.
+        #[arg(
+            long = "out",
+            short = 'o',
+            alias = "output-file",
+            value_name = "FILE",
+            conflicts_with = "file",
+            help = "Write to FILE instead of standard output (same as the FILE argument)"
+        )]
+        out: Option<std::path::PathBuf>,
+        #[arg(
+            long = "where",
+            value_name = "QUERY",
+            help = "Only export todos matching QUERY"
+        )]
+        query: Option<String>,
+    },
+    #[command(
+        about = "Import todos from todo.txt, Taskwarrior JSON, CSV, or a juggler JSON export"
+    )]
+    Import {
+        #[arg(long, value_enum, help = "Input format")]
+        format: ImportFormat,
+        #[arg(value_name = "FILE", help = "File to import, or - for standard input")]
+        file: std::path::PathBuf,
+        #[arg(long, help = "Show what would be imported without writing")]
+        dry_run: bool,
+    },
+    #[command(about = "Read or change persistent settings in config.toml")]
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    #[command(about = "Print a shell completion script; completes todo ids from the current store")]
+    Completions {
+        #[arg(value_enum, help = "Shell to generate the script for")]
+        shell: clap_complete::Shell,
+    },
+    #[command(about = "Print the juggler man page (roff)")]
+    Manpage,
+    #[command(about = "Serve a JSON API for the todos on localhost")]
+    Serve {
+        #[arg(long, default_value = "8765", help = "Local port to listen on")]
+        port: u16,
+        #[arg(
+            long,
+            env = "JUGGLER_SERVE_TOKEN",
+            hide_env_values = true,
+            help = "Bearer token clients must send (default: a random token printed at startup)"
+        )]
+        token: Option<String>,
+    },
+    #[command(
+        about = "Check the data directory, files, editor, keychain, and Google setup, with a fix for each problem"
+    )]
+    Doctor,
+    #[command(about = "Check TODOs.toml for problems and optionally repair safe ones")]
+    Fsck {
+        #[arg(
+            long,
+            help = "Renumber invalid ids and drop duplicate remote links after archiving the file"
+        )]
+        repair: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    #[command(about = "Print the effective value of a setting")]
+    Get {
+        #[arg(value_enum)]
+        key: SettingKey,
+    },
+    #[command(about = "Store a setting in config.toml")]
+    Set {
+        #[arg(value_enum)]
+        key: SettingKey,
+        value: String,
+    },
+    #[command(about = "List every setting with its effective value and source")]
+    Show,
 }
 
 #[derive(Subcommand)]
 enum SyncService {
-    #[command(name = "google-tasks")]
+    #[command(name = "google-tasks", args_conflicts_with_subcommands = true)]
     GoogleTasks {
         #[arg(long, help = "Log actions without executing them")]
         dry_run: bool,
         #[arg(long, help = "Print keychain diagnostics for authentication")]
         debug_auth: bool,
+        #[arg(
+            long,
+            conflicts_with = "dry_run",
+            help = "Keep running and sync whenever TODOs.toml changes"
+        )]
+        watch: bool,
+        #[arg(
+            long,
+            value_name = "DURATION",
+            default_value = "5m",
+            requires = "watch",
+            help = "With --watch, longest time between syncs (e.g. 5m, 1h)"
+        )]
+        interval: String,
+        #[command(subcommand)]
+        action: Option<GoogleTasksAction>,
     },
 }
 
+#[derive(Subcommand)]
+enum GoogleTasksAction {
+    #[command(about = "Show how each todo differs from Google Tasks without changing either")]
+    Status,
+}
+
 #[tokio::main]
-async fn main() -> Result<()> {
-    let env = Env::default().filter_or("RUST_LOG", "info");
+async fn main() -> ExitCode {
+    // Answers the shell's TAB requests and exits; must run before anything
+    // else writes to stdout.
+    clap_complete::CompleteEnv::with_factory(Cli::command).complete();
+
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+
+    // Scripts asking for JSON read the result object, not progress logs.
+    let default_log_level = match cli.output {
+        OutputFormat::Text => "info",
+        OutputFormat::Json => "warn",
+    };
+    let env = Env::default().filter_or("RUST_LOG", default_log_level);
     env_logger::Builder::from_env(env).init();
 
+    let mut report = Report::new(cli.output, matches.subcommand_name().unwrap_or("tui"));
+    let result = run(cli, &matches, &mut report).await;
+    report.finish(&result)
+}
+
+/// `juggler config`, which reads the file as written so that it can replace a
+/// value that makes every other command fail.
+fn run_config(
+    action: ConfigAction,
+    config_file: &std::path::Path,
+    overrides: &[Override],
+    report: &mut Report,
+) -> Result<()> {
+    let file = ConfigFile::read(config_file)?;
+    let settings = Settings::resolve(&file, overrides);
+    match action {
+        ConfigAction::Get { key } => {
+            let value = config_get(&settings, key)?;
+            report.set("key", key.name())?;
+            report.set("value", &value)?;
+            report.text(value);
+        }
+        ConfigAction::Set { key, value } => {
+            config_set(config_file, file, key, &value)?;
+            report.set("key", key.name())?;
+            report.set("path", config_file)?;
+            report.text(format!("Set {key} in {}", config_file.display()));
+        }
+        ConfigAction::Show => {
+            report.set("settings", config_json(&settings))?;
+            report.text(render_config(&settings));
+        }
+    }
+    Ok(())
+}
+
+async fn run(cli: Cli, matches: &ArgMatches, report: &mut Report) -> Result<()> {
     let Cli {
         juggler_dir,
+        profile,
         google_oauth_client_id,
         google_oauth_client_secret,
+        timezone,
+        output: _,
         command,
-    } = Cli::parse();
-    let todos_file = get_todos_file_path(juggler_dir.as_deref())?;
-    let oauth_client_id = google_oauth_client_id.as_deref();
-    let oauth_client_secret = google_oauth_client_secret.as_deref();
+    } = cli;
+    let overrides: Vec<Override> = [
+        (
+            SettingKey::Timezone,
+            timezone,
+            "timezone",
+            "--timezone",
+            "JUGGLER_TIMEZONE",
+        ),
+        (
+            SettingKey::OAuthClientId,
+            google_oauth_client_id,
+            "google_oauth_client_id",
+            "--google-oauth-client-id",
+            "GOOGLE_OAUTH_CLIENT_ID",
+        ),
+        (
+            SettingKey::OAuthClientSecret,
+            google_oauth_client_secret,
+            "google_oauth_client_secret",
+            "--google-oauth-client-secret",
+            "GOOGLE_OAUTH_CLIENT_SECRET",
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(key, value, id, flag, env_var)| {
+        value.map(|value| Override {
+            key,
+            value,
+            source: cli_setting_source(matches, id, flag, env_var),
+        })
+    })
+    .chain(editor_env_override())
+    .collect();
+
+    let juggler_dir = match &profile {
+        Some(profile) => Some(get_profile_dir(profile)?),
+        None => juggler_dir,
+    };
+    let cred_store = KeyringCredentialStore::new(keyring_account(profile.as_deref()));
+
+    // Doctor reports a broken data directory or config file instead of
+    // failing on it like the other commands.
+    if let Some(Commands::Doctor) = command {
+        let checks = run_doctor(juggler_dir.as_deref(), &overrides, &cred_store).await;
+        report.set("checks", &checks)?;
+        report.text(render_checks(&checks));
+        return ensure_no_failures(&checks);
+    }
 
-    let cred_store = KeyringCredentialStore::new();
-    let http_client = reqwest::Client::new();
+    let todos_file = get_todos_file_path(juggler_dir.as_deref())?;
+    let config_file = get_config_file_path(juggler_dir.as_deref())?;
+    let command = match command {
+        Some(Commands::Config { action }) => {
+            return run_config(action, &config_file, &overrides, report);
+        }
+        command => command,
+    };
+    let config = ConfigFile::load(&config_file)?;
+    let settings = Settings::resolve(&config, &overrides);
+    let timezone = resolve_timezone(settings.get(SettingKey::Timezone))?;
+    let oauth_client_id = settings.get(SettingKey::OAuthClientId);
+    let oauth_client_secret = settings.get(SettingKey::OAuthClientSecret);
+    let sync_target = settings.sync_target();
+    let hooks = Hooks::for_todos_file(&todos_file);
 
     match command {
         Some(Commands::Login { port }) => {
@@ -224,21 +815,20 @@ async fn main() -> Result<()> {
 
             match run_oauth_flow(oauth_client_id, oauth_client_secret, port).await {
                 Ok(result) => {
-                    println!("\n🎉 Authentication successful!");
+                    report.text("\n🎉 Authentication successful!");
                     match cred_store.store_refresh_token(&result.refresh_token) {
                         Ok(()) => {
-                            println!(
-                                "\nYour refresh token has been saved securely in your system keychain."
+                            report.set("status", "logged_in")?;
+                            report.text(
+                                "\nYour refresh token has been saved securely in your system keychain.",
                             );
-                            println!("You can now sync your TODOs with:");
-                            println!();
-                            println!(
-                                "juggler --google-oauth-client-id <CLIENT_ID> --google-oauth-client-secret <CLIENT_SECRET> sync google-tasks"
+                            report.text("You can now sync your TODOs with:\n");
+                            report.text(
+                                "juggler --google-oauth-client-id <CLIENT_ID> --google-oauth-client-secret <CLIENT_SECRET> sync google-tasks\n",
                             );
-                            println!();
-                            println!("Use --dry-run to preview changes:");
-                            println!(
-                                "juggler --google-oauth-client-id <CLIENT_ID> --google-oauth-client-secret <CLIENT_SECRET> sync google-tasks --dry-run"
+                            report.text("Use --dry-run to preview changes:");
+                            report.text(
+                                "juggler --google-oauth-client-id <CLIENT_ID> --google-oauth-client-secret <CLIENT_SECRET> sync google-tasks --dry-run",
                             );
                         }
                         Err(e) => {
@@ -255,24 +845,312 @@ async fn main() -> Result<()> {
         }
         Some(Commands::Logout) => match cred_store.delete_refresh_token() {
             Ok(()) => {
-                println!("Logged out: refresh token removed from keychain.");
+                report.set("status", "logged_out")?;
+                report.set("token_removed", true)?;
+                report.text("Logged out: refresh token removed from keychain.");
             }
             Err(CredentialError::NotFound) => {
-                println!("Logged out: no refresh token was stored.");
+                report.set("status", "logged_out")?;
+                report.set("token_removed", false)?;
+                report.text("Logged out: no refresh token was stored.");
             }
             Err(e) => {
                 error!("Failed to delete refresh token from keychain: {e}");
                 return Err(JugglerError::Credential(e));
             }
         },
+        Some(Commands::Add {
+            title,
+            due,
+            comment,
+        }) => {
+            let todo_id = add_todo(
+                &todos_file,
+                NewTodo {
+                    title,
+                    due,
+                    comment,
+                },
+                system_clock(),
+            )?;
+            report.set("id", &todo_id)?;
+            report.text(todo_id);
+        }
+        Some(Commands::Status { format }) => {
+            let dues = load_pending_dues(&todos_file)?;
+            let counts = count_status(&dues, system_clock().now(), timezone, settings.due_soon());
+            let line = render_status(
+                format.as_deref().unwrap_or(settings.status_format()),
+                &counts,
+            )?;
+            report.set("counts", counts)?;
+            report.text(line);
+            if counts.overdue > 0 {
+                report.set_success_code(OVERDUE_EXIT_CODE);
+            }
+        }
+        Some(Commands::List {
+            done,
+            overdue,
+            due_within,
+            grep,
+            query,
+            sort,
+            format,
+        }) => {
+            let now = system_clock().now();
+            let (todos, field_schema) = load_todos_and_schema(&todos_file)?;
+            let options = ListOptions {
+                include_done: done,
+                overdue,
+                due_within: due_within.as_deref().map(parse_window).transpose()?,
+                grep,
+                query: query
+                    .map(|query| parse_query(&query, &field_schema, now, timezone))
+                    .transpose()?,
+                sort,
+            };
+            let todos = select_todos(todos, &options, now, timezone);
+            report.set("todos", list_entries(&todos, now, timezone))?;
+            let output = render_list(&todos, format, now, timezone)?;
+            if !output.is_empty() {
+                report.text(output);
+            }
+        }
+        Some(Commands::Agenda { days }) => {
+            let todos = load_todos(&todos_file)?;
+            let now = system_clock().now();
+            let color = std::io::stdout().is_terminal();
+            report.set("groups", agenda_json(&todos, days, now, timezone))?;
+            report.text(render_agenda(
+                &todos,
+                days,
+                now,
+                timezone,
+                settings.due_soon(),
+                color,
+            ));
+        }
+        Some(Commands::Stats {
+            by,
+            periods,
+            format,
+        }) => {
+            let stats = compute_stats(
+                &load_todos(&todos_file)?,
+                by,
+                periods as usize,
+                system_clock().now(),
+                timezone,
+            );
+            report.set("stats", &stats)?;
+            report.text(render_stats(&stats, format)?);
+        }
+        Some(Commands::Done { ids, query }) => {
+            let targets = Targets::new(ids, query);
+            let mutation = mutate_todos(
+                &todos_file,
+                &targets,
+                BatchOp::Done,
+                system_clock(),
+                timezone,
+            )?;
+            report_mutation(report, mutation)?;
+        }
+        Some(Commands::Undone { ids, query }) => {
+            let targets = Targets::new(ids, query);
+            let mutation = mutate_todos(
+                &todos_file,
+                &targets,
+                BatchOp::Undone,
+                system_clock(),
+                timezone,
+            )?;
+            report_mutation(report, mutation)?;
+        }
+        Some(Commands::Snooze {
+            ids_and_days,
+            query,
+        }) => {
+            let (targets, days) = split_targets(ids_and_days, query)?;
+            let op = BatchOp::Snooze {
+                days: parse_snooze_days(&days)?,
+            };
+            let mutation = mutate_todos(&todos_file, &targets, op, system_clock(), timezone)?;
+            report_mutation(report, mutation)?;
+        }
+        Some(Commands::Due { ids_and_due, query }) => {
+            let (targets, due) = split_targets(ids_and_due, query)?;
+            let clock = system_clock();
+            let op = parse_due_op(&due, &clock)?;
+            let mutation = mutate_todos(&todos_file, &targets, op, clock, timezone)?;
+            report_mutation(report, mutation)?;
+        }
+        Some(Commands::Edit { id }) => {
+            let (_, field_schema) = load_todos_and_schema(&todos_file)?;
+            let editor = ExternalEditor::new(field_schema).with_default_command(settings.editor());
+            let message = edit_todo(&todos_file, &id, &editor, system_clock(), timezone)?;
+            report_mutation(
+                report,
+                Mutation {
+                    ids: vec![id],
+                    message,
+                },
+            )?;
+        }
+        Some(Commands::Rm { ids, query }) => {
+            let removed = remove_todos(
+                &todos_file,
+                &Targets::new(ids, query),
+                system_clock(),
+                timezone,
+            )?;
+            report.set("ids", &removed)?;
+            if removed.is_empty() {
+                report.text("No todos match the query");
+            }
+        }
+        Some(Commands::Export {
+            format,
+            file,
+            out,
+            query,
+        }) => {
+            let (mut todos, field_schema) = load_todos_and_schema(&todos_file)?;
+            if let Some(query) = query {
+                let query = parse_query(&query, &field_schema, system_clock().now(), timezone)?;
+                let blocked = blocked_ids(&todos);
+                todos.retain(|todo| query.matches(todo, &blocked));
+            }
+            let rendered = render_export(&todos, &field_schema, format, timezone)?;
+            match file.or(out) {
+                Some(path) => {
+                    write_file_atomically(&path, &rendered)?;
+                    report.set("path", path)?;
+                }
+                None if report.is_json() && format == ExportFormat::Json => {
+                    report.set(
+                        "export",
+                        serde_json::from_str::<serde_json::Value>(&rendered)?,
+                    )?;
+                }
+                None if report.is_json() => report.set("content", rendered)?,
+                None => print!("{rendered}"),
+            }
+        }
+        Some(Commands::Import {
+            format,
+            file,
+            dry_run,
+        }) => {
+            let input = if file.as_os_str() == "-" {
+                std::io::read_to_string(std::io::stdin())?
+            } else {
+                std::fs::read_to_string(&file)?
+            };
+            let imported = import_todos(&todos_file, format, &input, dry_run, system_clock())?;
+            report.set("dry_run", dry_run)?;
+            report.set(
+                "added",
+                imported
+                    .added
+                    .iter()
+                    .map(|todo| serde_json::json!({"id": todo.todo_id, "title": todo.title}))
+                    .collect::<Vec<_>>(),
+            )?;
+            report.set("duplicates", &imported.duplicates)?;
+            report.text(import_summary(&imported, dry_run));
+        }
+        Some(Commands::Config { .. }) => unreachable!("config runs before settings are validated"),
+        Some(Commands::Completions { shell }) => {
+            write_registration(shell, &mut std::io::stdout().lock())?;
+        }
+        Some(Commands::Manpage) => {
+            write_manpage(Cli::command(), &mut std::io::stdout().lock())?;
+        }
+        Some(Commands::Serve { port, token }) => {
+            let listener = bind(port).await?;
+            let url = format!("http://{}", listener.local_addr()?);
+            report.set("url", &url)?;
+            let token = match token {
+                Some(token) => token,
+                None => {
+                    let token = generate_token();
+                    report.set("token", &token)?;
+                    report.text(format!("API token: {token}"));
+                    token
+                }
+            };
+            report.text(format!("Listening on {url} (Ctrl-C to stop)"));
+            report.emit_now();
+            run_server(listener, todos_file, token, system_clock(), timezone).await?;
+        }
+        Some(Commands::Doctor) => unreachable!("doctor runs before settings are resolved"),
+        Some(Commands::Fsck { repair }) => {
+            if repair {
+                let outcome = repair_todos_file_with_clock(&todos_file, system_clock())?;
+                if let Some(archive_path) = &outcome.archive_path {
+                    report.set("archive", archive_path)?;
+                    report.text(format!(
+                        "Archived previous file to {}",
+                        archive_path.display()
+                    ));
+                }
+                for repair in &outcome.repairs {
+                    report.text(format!("Repaired: {repair}"));
+                }
+                report.set("repairs", &outcome.repairs)?;
+            }
+
+            let fsck_report = check_todos_file(&todos_file)?;
+            for issue in &fsck_report.issues {
+                report.text(issue);
+            }
+            report.set("issues", fsck_issues_json(&fsck_report))?;
+            report.text(fsck_summary(&fsck_report));
+            ensure_no_errors(&fsck_report, &todos_file)?;
+        }
         Some(Commands::Sync { service }) => {
             // CLI mode: handle sync commands
             match service {
+                SyncService::GoogleTasks {
+                    action: Some(GoogleTasksAction::Status),
+                    ..
+                } => {
+                    let todos = load_todos(&todos_file)?;
+                    let oauth_client_id = required_google_oauth_value(
+                        oauth_client_id,
+                        "client id",
+                        "--google-oauth-client-id",
+                        "GOOGLE_OAUTH_CLIENT_ID",
+                    )?;
+                    let oauth_client_secret = required_google_oauth_value(
+                        oauth_client_secret,
+                        "client secret",
+                        "--google-oauth-client-secret",
+                        "GOOGLE_OAUTH_CLIENT_SECRET",
+                    )?;
+                    let oauth_client = create_oauth_client_from_keychain(
+                        &cred_store,
+                        reqwest::Client::new(),
+                        &oauth_client_id,
+                        &oauth_client_secret,
+                    )?;
+
+                    let drift =
+                        drift_report_with_oauth(&todos, oauth_client, timezone, &sync_target)
+                            .await?;
+                    report.set("drift", &drift)?;
+                    report.text(render_drift(&drift));
+                }
                 SyncService::GoogleTasks {
                     dry_run,
                     debug_auth,
+                    watch,
+                    interval,
+                    action: None,
                 } => {
-                    let mut todos = load_todos(&todos_file)?;
+                    let interval = parse_watch_interval(&interval)?;
                     let oauth_client_id = required_google_oauth_value(
                         oauth_client_id,
                         "client id",
@@ -291,10 +1169,7 @@ async fn main() -> Result<()> {
                         info!("Auth diagnostics:");
                         info!("  platform: {}", std::env::consts::OS);
                         info!("  keychain service: {}", CREDENTIAL_KEYRING_SERVICE);
-                        info!(
-                            "  keychain account: {}",
-                            CREDENTIAL_KEYRING_ACCOUNT_GOOGLE_TASKS
-                        );
+                        info!("  keychain account: {}", cred_store.account());
                         match cred_store.get_refresh_token() {
                             Ok(t) => {
                                 let len = t.len();
@@ -306,17 +1181,41 @@ async fn main() -> Result<()> {
                         }
                     }
 
-                    let oauth_client = create_oauth_client_from_keychain(
+                    let mut oauth_client = create_oauth_client_from_keychain(
                         &cred_store,
-                        http_client.clone(),
+                        reqwest::Client::new(),
                         &oauth_client_id,
                         &oauth_client_secret,
                     )?;
 
-                    sync_to_tasks_with_oauth(&mut todos, oauth_client, dry_run).await?;
+                    if watch {
+                        let options = WatchOptions {
+                            interval,
+                            timezone,
+                            target: sync_target,
+                            hooks,
+                        };
+                        return watch_google_tasks(&todos_file, &mut oauth_client, &options).await;
+                    }
 
-                    // Save the updated todos with new google_task_ids
-                    if let Err(e) = maybe_persist_todos_after_sync(&mut todos, &todos_file, dry_run)
+                    let (mut todos, field_schema) = load_todos_and_schema(&todos_file)?;
+                    let before = todos.clone();
+                    report.set("dry_run", dry_run)?;
+                    let sync_report = sync_google_tasks(
+                        &mut todos,
+                        &mut oauth_client,
+                        dry_run,
+                        timezone,
+                        &sync_target,
+                        &hooks,
+                        &field_schema,
+                    )
+                    .await?;
+                    report.set("sync", sync_report)?;
+
+                    // Save the new google_task_ids into the store as it is now
+                    if let Err(e) =
+                        maybe_persist_todos_after_sync(&before, &todos, &todos_file, dry_run)
                     {
                         error!("Warning: Failed to save todos after sync: {e}");
                         return Err(e);
@@ -328,17 +1227,26 @@ async fn main() -> Result<()> {
         }
         None => {
             // TUI mode: original behavior
+            let keymap = config.keymap()?;
+            let session = Session::load(&todos_file)?;
+            let field_schema = session.field_schema().clone();
             let mut terminal = ratatui::init();
-            let items = load_todos(&todos_file)?;
-            let mut app = App::new(items, Box::new(ExternalEditor));
+            let editor =
+                ExternalEditor::new(field_schema.clone()).with_default_command(settings.editor());
+            let mut app = App::new(session.todos().to_vec(), Box::new(editor))
+                .with_field_schema(field_schema.clone())
+                .with_hooks(hooks.clone())
+                .with_timezone(timezone)
+                .with_due_soon(settings.due_soon())
+                .with_keymap(keymap);
             let app_result = app.run(&mut terminal);
             ratatui::restore();
 
+            let mut todos = app.items();
             if app.should_sync_on_exit() {
-                let mut todos = app.items();
                 let maybe_oauth_credentials = prepare_tui_sync_on_exit(
+                    &session,
                     &mut todos,
-                    &todos_file,
                     oauth_client_id,
                     oauth_client_secret,
                 )?;
@@ -350,18 +1258,27 @@ async fn main() -> Result<()> {
 
                 match create_oauth_client_from_keychain(
                     &cred_store,
-                    http_client,
+                    reqwest::Client::new(),
                     &oauth_client_id,
                     &oauth_client_secret,
                 ) {
-                    Ok(oauth_client) => {
-                        let sync_result =
-                            sync_to_tasks_with_oauth(&mut todos, oauth_client, false).await;
+                    Ok(mut oauth_client) => {
+                        let before = todos.clone();
+                        let sync_result = sync_google_tasks(
+                            &mut todos,
+                            &mut oauth_client,
+                            false,
+                            timezone,
+                            &sync_target,
+                            &hooks,
+                            &field_schema,
+                        )
+                        .await;
                         match sync_result {
-                            Ok(()) => {
+                            Ok(_) => {
                                 info!("Sync completed successfully!");
-                                // Save again to persist any updated google_task_id values
-                                if let Err(e) = store_todos(&mut todos, &todos_file) {
+                                // Save the new google_task_ids into the store as it is now
+                                if let Err(e) = save_google_task_ids(&todos_file, &before, &todos) {
                                     error!("Warning: Failed to save todos after sync: {e}");
                                 }
                             }
@@ -376,11 +1293,8 @@ async fn main() -> Result<()> {
                         error!("Skipping sync. Todos were saved prior to sync attempt.");
                     }
                 }
-            } else {
-                let mut todos = app.items();
-                if let Err(e) = store_todos(&mut todos, &todos_file) {
-                    error!("Warning: Failed to save todos: {e}");
-                }
+            } else if let Err(e) = session.save(&mut todos) {
+                error!("Warning: Failed to save todos: {e}");
             }
 
             return app_result;
@@ -393,6 +1307,8 @@ async fn main() -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::store::store_todos;
+    use indexmap::IndexMap;
     use std::fs;
     use std::path::PathBuf;
     use tempfile::TempDir;
@@ -407,6 +1323,13 @@ mod tests {
             due_date: None,
             todo_id: None,
             google_task_id: None,
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra: toml::Table::new(),
         }
     }
 
@@ -431,8 +1354,9 @@ mod tests {
         store_todos(&mut original, &todos_file).expect("store initial todos");
         let before = fs::read_to_string(&todos_file).expect("read initial todos file");
 
-        let mut updated = vec![make_todo("updated")];
-        maybe_persist_todos_after_sync(&mut updated, &todos_file, true)
+        let mut synced = original.clone();
+        synced[0].google_task_id = Some("task-1".to_string());
+        maybe_persist_todos_after_sync(&original, &synced, &todos_file, true)
             .expect("dry-run persist should succeed");
 
         let after = fs::read_to_string(&todos_file).expect("read todos file after dry-run");
@@ -441,20 +1365,26 @@ mod tests {
     }
 
     #[test]
-    fn non_dry_run_sync_persists_local_todos_and_archives_previous_file() {
+    fn sync_saves_assigned_ids_over_edits_made_while_it_ran() {
         let temp_dir = TempDir::new().expect("create temp dir");
         let todos_file = temp_dir.path().join("TODOs.toml");
 
         let mut original = vec![make_todo("original")];
         store_todos(&mut original, &todos_file).expect("store initial todos");
-
-        let mut updated = vec![make_todo("updated")];
-        maybe_persist_todos_after_sync(&mut updated, &todos_file, false)
+        let mut synced = original.clone();
+        synced[0].google_task_id = Some("task-1".to_string());
+        // Another writer renames the todo while the sync is on the network.
+        let mut edited = original.clone();
+        edited[0].title = "renamed meanwhile".to_string();
+        store_todos(&mut edited, &todos_file).expect("store concurrent edit");
+
+        maybe_persist_todos_after_sync(&original, &synced, &todos_file, false)
             .expect("persist should succeed");
 
-        let after = fs::read_to_string(&todos_file).expect("read updated todos file");
-        assert!(after.contains("title = \"updated\""));
-        assert_eq!(archive_file_count(temp_dir.path()), 1);
+        let todos = load_todos(&todos_file).expect("load merged todos");
+        assert_eq!(todos[0].title, "renamed meanwhile");
+        assert_eq!(todos[0].google_task_id.as_deref(), Some("task-1"));
+        assert_eq!(archive_file_count(temp_dir.path()), 2);
     }
 
     #[test]
@@ -462,8 +1392,9 @@ mod tests {
         let temp_dir = TempDir::new().expect("create temp dir");
         let todos_file = temp_dir.path().join("TODOs.toml");
 
+        let session = Session::load(&todos_file).expect("load empty store");
         let mut todos = vec![make_todo("saved-before-sync")];
-        save_todos_before_sync(&mut todos, &todos_file).expect("save should succeed");
+        save_todos_before_sync(&session, &mut todos).expect("save should succeed");
 
         let content = fs::read_to_string(&todos_file).expect("read saved todos");
         assert!(content.contains("title = \"saved-before-sync\""));
@@ -472,9 +1403,12 @@ mod tests {
     #[test]
     fn save_todos_before_sync_returns_error_for_directory_path() {
         let temp_dir = TempDir::new().expect("create temp dir");
+        let todos_file = temp_dir.path().join("TODOs.toml");
+        let session = Session::load(&todos_file).expect("load empty store");
+        fs::create_dir(&todos_file).expect("replace store with a directory");
 
         let mut todos = vec![make_todo("cannot-save")];
-        let result = save_todos_before_sync(&mut todos, temp_dir.path());
+        let result = save_todos_before_sync(&session, &mut todos);
         assert!(result.is_err());
     }
 
@@ -483,8 +1417,9 @@ mod tests {
         let temp_dir = TempDir::new().expect("create temp dir");
         let todos_file = temp_dir.path().join("TODOs.toml");
 
+        let session = Session::load(&todos_file).expect("load empty store");
         let mut todos = vec![make_todo("saved-even-when-sync-skips")];
-        let result = prepare_tui_sync_on_exit(&mut todos, &todos_file, None, None)
+        let result = prepare_tui_sync_on_exit(&session, &mut todos, None, None)
             .expect("missing oauth flags should skip sync, not hard-fail");
 
         assert!(result.is_none());
@@ -497,10 +1432,11 @@ mod tests {
         let temp_dir = TempDir::new().expect("create temp dir");
         let todos_file = temp_dir.path().join("TODOs.toml");
 
+        let session = Session::load(&todos_file).expect("load empty store");
         let mut todos = vec![make_todo("saved-before-sync-credentials")];
         let result = prepare_tui_sync_on_exit(
+            &session,
             &mut todos,
-            &todos_file,
             Some("client-id"),
             Some("client-secret"),
         )
@@ -540,9 +1476,87 @@ mod tests {
                 service: SyncService::GoogleTasks {
                     dry_run: true,
                     debug_auth: false,
+                    watch: false,
+                    action: None,
+                    ..
+                }
+            })
+        ));
+    }
+
+    #[test]
+    fn cli_parses_sync_watch_interval_only_with_watch() {
+        let cli = Cli::parse_from([
+            "juggler",
+            "sync",
+            "google-tasks",
+            "--watch",
+            "--interval",
+            "10m",
+        ]);
+        let Some(Commands::Sync {
+            service:
+                SyncService::GoogleTasks {
+                    watch: true,
+                    interval,
+                    ..
+                },
+        }) = cli.command
+        else {
+            panic!("expected sync --watch");
+        };
+        assert_eq!(
+            parse_watch_interval(&interval).unwrap(),
+            std::time::Duration::from_secs(600)
+        );
+        assert!(parse_watch_interval("0m").is_err());
+        assert!(parse_watch_interval("-5m").is_err());
+
+        for args in [
+            &["juggler", "sync", "google-tasks", "--interval", "10m"][..],
+            &["juggler", "sync", "google-tasks", "--watch", "--dry-run"][..],
+        ] {
+            assert!(Cli::try_parse_from(args).is_err(), "{args:?}");
+        }
+    }
+
+    #[test]
+    fn cli_takes_a_valid_profile_name_instead_of_a_juggler_dir() {
+        let cli = Cli::parse_from(["juggler", "list", "--profile", "work"]);
+        assert_eq!(cli.profile.as_deref(), Some("work"));
+
+        for args in [
+            &["juggler", "--profile", "../work", "list"][..],
+            &[
+                "juggler",
+                "--profile",
+                "work",
+                "--juggler-dir",
+                "dir",
+                "list",
+            ][..],
+        ] {
+            assert!(Cli::try_parse_from(args).is_err(), "{args:?}");
+        }
+    }
+
+    #[test]
+    fn cli_parses_sync_status_and_rejects_sync_flags_with_it() {
+        let cli = Cli::parse_from(["juggler", "sync", "google-tasks", "status"]);
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Sync {
+                service: SyncService::GoogleTasks {
+                    action: Some(GoogleTasksAction::Status),
+                    ..
                 }
             })
         ));
+
+        let err = Cli::try_parse_from(["juggler", "sync", "google-tasks", "--dry-run", "status"])
+            .err()
+            .expect("status takes no sync flags");
+        assert_eq!(err.kind(), clap::error::ErrorKind::ArgumentConflict);
     }
 
     #[test]
@@ -583,6 +1597,81 @@ mod tests {
         assert!(matches!(cli.command, Some(Commands::Logout)));
     }
 
+    #[test]
+    fn cli_parses_global_output_format_after_subcommand() {
+        let cli = Cli::parse_from(["juggler", "export", "-o", "out.json", "--output", "json"]);
+
+        assert_eq!(cli.output, OutputFormat::Json);
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Export { out: Some(_), .. })
+        ));
+    }
+
+    #[test]
+    fn status_help_names_the_overdue_exit_code() {
+        let command = Cli::command();
+        let about = command
+            .find_subcommand("status")
+            .and_then(|status| status.get_about())
+            .expect("status has an about text")
+            .to_string();
+        assert!(
+            about.contains(&format!("exits {OVERDUE_EXIT_CODE} ")),
+            "{about}"
+        );
+    }
+
+    #[test]
+    fn cli_takes_the_export_file_as_argument_or_flag() {
+        for args in [
+            &["juggler", "export", "out.json"][..],
+            &["juggler", "export", "--out", "out.json"],
+            &["juggler", "export", "--output-file", "out.json"],
+        ] {
+            let Some(Commands::Export { file, out, .. }) = Cli::parse_from(args).command else {
+                panic!("expected export for {args:?}");
+            };
+            assert_eq!(
+                file.or(out).as_deref(),
+                Some(std::path::Path::new("out.json"))
+            );
+        }
+        assert!(Cli::try_parse_from(["juggler", "export", "a.json", "-o", "b.json"]).is_err());
+    }
+
+    #[test]
+    fn cli_takes_where_query_in_place_of_ids() {
+        let cli = Cli::parse_from(["juggler", "snooze", "--where", "overdue", "1d"]);
+        let Some(Commands::Snooze {
+            ids_and_days,
+            query,
+        }) = cli.command
+        else {
+            panic!("expected snooze");
+        };
+        let (targets, days) = split_targets(ids_and_days, query).unwrap();
+        assert_eq!(targets, Targets::Matching("overdue".to_string()));
+        assert_eq!(days, "1d");
+
+        let (targets, due) =
+            split_targets(vec!["T1".into(), "T2".into(), "none".into()], None).unwrap();
+        assert_eq!(targets, Targets::Ids(vec!["T1".into(), "T2".into()]));
+        assert_eq!(due, "none");
+        assert!(split_targets(vec!["1d".into()], None).is_err());
+        assert!(split_targets(vec!["T1".into(), "1d".into()], Some("done".into())).is_err());
+
+        assert!(Cli::try_parse_from(["juggler", "done"]).is_err());
+        assert!(Cli::try_parse_from(["juggler", "done", "T1", "--where", "done"]).is_err());
+    }
+
+    #[test]
+    fn cli_parses_fsck_repair_flag() {
+        let cli = Cli::parse_from(["juggler", "fsck", "--repair"]);
+
+        assert!(matches!(cli.command, Some(Commands::Fsck { repair: true })));
+    }
+
     #[test]
     fn required_google_oauth_value_accepts_trimmed_values() {
         let client_id = required_google_oauth_value(
@@ -22,11 +22,11 @@ use hyper::service::service_fn;
 use hyper::{Method, Request, Response, StatusCode};
 use hyper_util::rt::TokioIo;
 use log::{debug, error, info};
-use oauth2::basic::BasicClient;
+use oauth2::basic::{BasicClient, BasicErrorResponseType};
 use oauth2::reqwest::async_http_client;
 use oauth2::{
     AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken, PkceCodeChallenge, RedirectUrl,
-    RefreshToken, Scope, TokenResponse, TokenUrl,
+    RefreshToken, RequestTokenError, Scope, TokenResponse, TokenUrl,
 };
 use tokio::net::TcpListener;
 use tokio::sync::{Mutex, oneshot};
@@ -114,11 +114,12 @@ pub async fn run_oauth_flow(
         .set_pkce_challenge(pkce_challenge)
         .url();
 
-    // Open browser
+    // Open browser. The instructions go to stderr so `--output json` keeps
+    // stdout for the result object.
     info!("Opening browser for authentication...");
-    println!("\nOpening your browser to authenticate with Google Tasks.");
-    println!("If your browser doesn't open automatically, please visit:");
-    println!("{auth_url}\n");
+    eprintln!("\nOpening your browser to authenticate with Google Tasks.");
+    eprintln!("If your browser doesn't open automatically, please visit:");
+    eprintln!("{auth_url}\n");
 
     if let Err(e) = open_browser(auth_url.as_str()) {
         error!("Failed to open browser: {e}. Please manually visit the URL above.");
@@ -408,7 +409,7 @@ impl GoogleOAuthClient {
             .exchange_refresh_token(&RefreshToken::new(self.credentials.refresh_token.clone()))
             .request_async(async_http_client)
             .await
-            .map_err(oauth_err("OAuth token refresh failed"))?;
+            .map_err(refresh_error)?;
 
         let access_token = token_result.access_token().secret().to_string();
         let expires_in = token_result
@@ -424,6 +425,29 @@ impl GoogleOAuthClient {
     }
 }
 
+/// Classify a failed refresh: a rejected refresh token needs a new login,
+/// while network failures and server-side outages may pass on retry.
+fn refresh_error<RE: std::error::Error>(
+    error: RequestTokenError<RE, oauth2::basic::BasicErrorResponse>,
+) -> JugglerError {
+    let message = format!("OAuth token refresh failed: {error}");
+    match &error {
+        RequestTokenError::ServerResponse(response) => match response.error() {
+            BasicErrorResponseType::InvalidGrant => JugglerError::login_required(format!(
+                "{message}: the refresh token was rejected; run `juggler login` again"
+            )),
+            BasicErrorResponseType::Extension(code)
+                if code == "server_error" || code == "temporarily_unavailable" =>
+            {
+                JugglerError::unavailable(message)
+            }
+            _ => JugglerError::oauth(message),
+        },
+        RequestTokenError::Request(e) => JugglerError::unavailable(format!("{message}: {e}")),
+        RequestTokenError::Parse(..) | RequestTokenError::Other(_) => JugglerError::oauth(message),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -526,8 +550,29 @@ mod tests {
 
         let result = oauth_client.get_access_token().await;
         assert!(result.is_err());
-        let error_msg = result.unwrap_err().to_string();
-        assert!(error_msg.contains("OAuth token refresh failed"));
+        let error = result.unwrap_err();
+        assert!(matches!(error, JugglerError::LoginRequired(_)), "{error:?}");
+        assert!(error.to_string().contains("OAuth token refresh failed"));
+    }
+
+    #[tokio::test]
+    async fn test_oauth_token_refresh_network_failure_is_transient() {
+        // Nothing listens on the discard port, so the request never reaches a server.
+        let credentials = GoogleOAuthCredentials {
+            client_id: TEST_CLIENT_ID.to_string(),
+            client_secret: TEST_CLIENT_SECRET.to_string(),
+            refresh_token: "test_refresh_token".to_string(),
+        };
+        let mut oauth_client = GoogleOAuthClient::new_with_custom_oauth_url(
+            credentials,
+            reqwest::Client::new(),
+            "http://127.0.0.1:9/token".to_string(),
+            test_clock(),
+        );
+
+        let error = oauth_client.get_access_token().await.unwrap_err();
+        assert!(matches!(error, JugglerError::Unavailable(_)), "{error:?}");
+        assert!(error.is_transient());
     }
 
     #[tokio::test]
@@ -0,0 +1,140 @@
+//! The global `--output` contract.
+//!
+//! In `text` mode commands print prose for people. In `json` mode every
+//! command prints exactly one JSON object on standard output when it finishes:
+//! `{"ok": true, "command": "<name>", ...}` with command-specific fields, or
+//! `{"ok": false, "command": "<name>", "error": {"code", "exit_code",
+//! "message"}, ...}` with whatever fields were recorded before the failure.
+//! Logs stay on standard error in both modes.
+
+use std::fmt::Display;
+use std::process::ExitCode;
+
+use serde_json::{Map, Value};
+
+use crate::error::{JugglerError, Result};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/// Collects one command's result and prints it in the selected format.
+#[derive(Debug)]
+pub struct Report {
+    format: OutputFormat,
+    command: String,
+    fields: Map<String, Value>,
+    emitted: bool,
+    /// Exit code for a successful run that still wants to signal something.
+    success_code: u8,
+}
+
+impl Report {
+    pub fn new(format: OutputFormat, command: impl Into<String>) -> Self {
+        Report {
+            format,
+            command: command.into(),
+            fields: Map::new(),
+            emitted: false,
+            success_code: 0,
+        }
+    }
+
+    pub fn is_json(&self) -> bool {
+        self.format == OutputFormat::Json
+    }
+
+    /// Print a line for people; ignored in JSON mode.
+    pub fn text(&self, text: impl Display) {
+        if !self.is_json() {
+            println!("{text}");
+        }
+    }
+
+    /// Record a field of the JSON result.
+    pub fn set(&mut self, key: &str, value: impl serde::Serialize) -> Result<()> {
+        self.fields
+            .insert(key.to_string(), serde_json::to_value(value)?);
+        Ok(())
+    }
+
+    /// Print the JSON result now rather than on exit, for commands that keep
+    /// running after they have something to report (`serve`). A later
+    /// failure is still reported.
+    pub fn emit_now(&mut self) {
+        if self.is_json() {
+            println!("{}", self.to_json(None));
+            self.emitted = true;
+        }
+    }
+
+    /// Exit with `code` even though the command succeeded (`status` with
+    /// overdue todos).
+    pub fn set_success_code(&mut self, code: u8) {
+        self.success_code = code;
+    }
+
+    /// Report `result` and turn it into the process exit code.
+    pub fn finish(self, result: &Result<()>) -> ExitCode {
+        match (self.format, result) {
+            (OutputFormat::Json, Ok(())) if self.emitted => {}
+            (OutputFormat::Json, result) => println!("{}", self.to_json(result.as_ref().err())),
+            (OutputFormat::Text, Ok(())) => {}
+            (OutputFormat::Text, Err(e)) => eprintln!("Error: {e}"),
+        }
+        match result {
+            Ok(()) => ExitCode::from(self.success_code),
+            Err(e) => ExitCode::from(e.exit_code()),
+        }
+    }
+
+    fn to_json(&self, error: Option<&JugglerError>) -> Value {
+        let mut object = Map::new();
+        object.insert("ok".to_string(), Value::Bool(error.is_none()));
+        object.insert("command".to_string(), Value::String(self.command.clone()));
+        object.extend(self.fields.clone());
+        if let Some(error) = error {
+            object.insert(
+                "error".to_string(),
+                serde_json::json!({
+                    "code": error.code(),
+                    "exit_code": error.exit_code(),
+                    "message": error.to_string(),
+                }),
+            );
+        }
+        Value::Object(object)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_result_keeps_recorded_fields_alongside_the_error() {
+        let mut report = Report::new(OutputFormat::Json, "sync");
+        report.set("dry_run", true).unwrap();
+
+        assert_eq!(
+            report.to_json(None),
+            serde_json::json!({"ok": true, "command": "sync", "dry_run": true})
+        );
+        assert_eq!(
+            report.to_json(Some(&JugglerError::oauth("token revoked"))),
+            serde_json::json!({
+                "ok": false,
+                "command": "sync",
+                "dry_run": true,
+                "error": {
+                    "code": "oauth",
+                    "exit_code": 5,
+                    "message": "OAuth error: token revoked",
+                },
+            })
+        );
+    }
+}
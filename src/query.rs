@@ -0,0 +1,1005 @@
+//! The `--where` query language shared by `list`, `export`, the bulk
+//! commands, and the TUI filter prompt.
+//!
+//! ```text
+//! query   := or
+//! or      := and ("or" and)*
+//! and     := not ("and" not)*
+//! not     := "not" not | "(" or ")" | STRING "in" TEXT | FIELD (OP VALUE)?
+//! OP      := < <= > >= = !=
+//! ```
+//!
+//! Keywords and built-in field names are case-insensitive. Values are bare
+//! words or double-quoted strings (`\"` escapes a quote). Built-in fields:
+//!
+//! - `done`, `blocked`, `overdue` on their own, and `due` / `comment` on their
+//!   own for "has one".
+//! - `due`, `created`, `completed` compared against a relative offset from
+//!   now (`2d`, `-12h`), `now`, `today`, a date, or an RFC 3339 instant. Dates
+//!   compare by calendar day in the configured timezone, offsets and instants
+//!   against the start of the due, as `list --due-within` does.
+//! - `snoozed` (snooze count) and `id` compared numerically.
+//! - `title`, `comment` with `=`/`!=` (case-insensitive) or `"text" in title`;
+//!   `"text" in text` searches both.
+//!
+//! Any other name must be a declared custom field: bool fields work on their
+//! own, other fields on their own mean "has a value", and comparisons parse
+//! the value with the field's type.
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fmt;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
+
+use crate::due::{Due, parse_due_argument, today};
+use crate::error::{JugglerError, Result};
+use crate::fields::{FieldSchema, FieldType, FieldValue};
+use crate::store::parse_todo_id;
+use crate::ui::Todo;
+
+/// A parsed query, ready to test todos against.
+#[derive(Debug, Clone)]
+pub struct Query {
+    source: String,
+    expr: Expr,
+    now: DateTime<Utc>,
+    tz: Tz,
+}
+
+/// A parse failure at a 1-based character column of the query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryError {
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at column {}", self.message, self.column)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+/// Parse `input` for the command line, pointing at the offending column
+/// beneath the query on failure.
+pub fn parse_query(input: &str, schema: &FieldSchema, now: DateTime<Utc>, tz: Tz) -> Result<Query> {
+    Query::parse(input, schema, now, tz).map_err(|e| {
+        JugglerError::config(format!(
+            "Invalid query: {e}\n  {input}\n  {}^",
+            " ".repeat(e.column - 1)
+        ))
+    })
+}
+
+/// Ids of open todos still waiting on another open todo.
+pub fn blocked_ids(todos: &[Todo]) -> HashSet<String> {
+    let open_ids: HashSet<&str> = todos
+        .iter()
+        .filter(|todo| !todo.done)
+        .filter_map(|todo| todo.todo_id.as_deref())
+        .collect();
+    todos
+        .iter()
+        .filter(|todo| !todo.done)
+        .filter(|todo| {
+            todo.blocked_by
+                .iter()
+                .any(|blocker| open_ids.contains(blocker.as_str()))
+        })
+        .filter_map(|todo| todo.todo_id.clone())
+        .collect()
+}
+
+impl Query {
+    /// Parse `input`. Relative values are resolved against `now` once, so a
+    /// long-lived query (the TUI filter) keeps meaning what it meant when typed.
+    pub fn parse(
+        input: &str,
+        schema: &FieldSchema,
+        now: DateTime<Utc>,
+        tz: Tz,
+    ) -> std::result::Result<Query, QueryError> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+            schema,
+            now,
+            tz,
+        };
+        let expr = parser.parse_or()?;
+        let trailing = parser.peek();
+        if trailing.kind != TokenKind::End {
+            return Err(trailing.error(format!("unexpected {}", trailing.kind)));
+        }
+        Ok(Query {
+            source: input.trim().to_string(),
+            expr,
+            now,
+            tz,
+        })
+    }
+
+    /// The query as typed.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Whether `todo` matches; `blocked` holds the ids of blocked todos, as
+    /// computed by `blocked_ids` over the whole store.
+    pub fn matches(&self, todo: &Todo, blocked: &HashSet<String>) -> bool {
+        self.expr.eval(todo, blocked, self.now, self.tz)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CmpOp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+impl CmpOp {
+    fn holds(self, ordering: Ordering) -> bool {
+        match self {
+            CmpOp::Lt => ordering.is_lt(),
+            CmpOp::Le => ordering.is_le(),
+            CmpOp::Gt => ordering.is_gt(),
+            CmpOp::Ge => ordering.is_ge(),
+            CmpOp::Eq => ordering.is_eq(),
+            CmpOp::Ne => ordering.is_ne(),
+        }
+    }
+
+    fn is_equality(self) -> bool {
+        matches!(self, CmpOp::Eq | CmpOp::Ne)
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            CmpOp::Lt => "<",
+            CmpOp::Le => "<=",
+            CmpOp::Gt => ">",
+            CmpOp::Ge => ">=",
+            CmpOp::Eq => "=",
+            CmpOp::Ne => "!=",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Word(String),
+    Str(String),
+    Op(CmpOp),
+    Open,
+    Close,
+    End,
+}
+
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenKind::Word(word) => write!(f, "'{word}'"),
+            TokenKind::Str(text) => write!(f, "\"{text}\""),
+            TokenKind::Op(op) => write!(f, "'{}'", op.symbol()),
+            TokenKind::Open => f.write_str("'('"),
+            TokenKind::Close => f.write_str("')'"),
+            TokenKind::End => f.write_str("end of query"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    column: usize,
+}
+
+impl Token {
+    fn error(&self, message: impl Into<String>) -> QueryError {
+        QueryError {
+            column: self.column,
+            message: message.into(),
+        }
+    }
+
+    fn keyword(&self, keyword: &str) -> bool {
+        matches!(&self.kind, TokenKind::Word(word) if word.eq_ignore_ascii_case(keyword))
+    }
+}
+
+fn is_word_char(ch: char) -> bool {
+    !ch.is_whitespace() && !matches!(ch, '(' | ')' | '<' | '>' | '=' | '!' | '"')
+}
+
+fn tokenize(input: &str) -> std::result::Result<Vec<Token>, QueryError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut index = 0;
+    while index < chars.len() {
+        let ch = chars[index];
+        let column = index + 1;
+        let error = |message: &str| QueryError {
+            column,
+            message: message.to_string(),
+        };
+        let (kind, width) = match (ch, chars.get(index + 1)) {
+            (ch, _) if ch.is_whitespace() => {
+                index += 1;
+                continue;
+            }
+            ('(', _) => (TokenKind::Open, 1),
+            (')', _) => (TokenKind::Close, 1),
+            ('<', Some('=')) => (TokenKind::Op(CmpOp::Le), 2),
+            ('<', _) => (TokenKind::Op(CmpOp::Lt), 1),
+            ('>', Some('=')) => (TokenKind::Op(CmpOp::Ge), 2),
+            ('>', _) => (TokenKind::Op(CmpOp::Gt), 1),
+            ('!', Some('=')) => (TokenKind::Op(CmpOp::Ne), 2),
+            ('!', _) => return Err(error("'!' must be followed by '=' (use 'not' to negate)")),
+            ('=', Some('=')) => (TokenKind::Op(CmpOp::Eq), 2),
+            ('=', _) => (TokenKind::Op(CmpOp::Eq), 1),
+            ('"', _) => {
+                let mut text = String::new();
+                let mut end = index + 1;
+                loop {
+                    match chars.get(end) {
+                        None => return Err(error("unterminated string")),
+                        Some('"') => break,
+                        Some('\\') if chars.get(end + 1) == Some(&'"') => {
+                            text.push('"');
+                            end += 2;
+                        }
+                        Some(ch) => {
+                            text.push(*ch);
+                            end += 1;
+                        }
+                    }
+                }
+                (TokenKind::Str(text), end + 1 - index)
+            }
+            _ => {
+                let width = chars[index..]
+                    .iter()
+                    .take_while(|ch| is_word_char(**ch))
+                    .count();
+                let word = chars[index..index + width].iter().collect();
+                (TokenKind::Word(word), width)
+            }
+        };
+        tokens.push(Token { kind, column });
+        index += width;
+    }
+    tokens.push(Token {
+        kind: TokenKind::End,
+        column: chars.len() + 1,
+    });
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Flag {
+    Done,
+    Blocked,
+    Overdue,
+    HasDue,
+    HasComment,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TimeField {
+    Due,
+    Created,
+    Completed,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TextField {
+    Title,
+    Comment,
+    /// Title or comment.
+    Text,
+    Custom(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TimeBound {
+    Day(NaiveDate),
+    Instant(DateTime<Utc>),
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Flag(Flag),
+    Contains(String, TextField),
+    Time(TimeField, CmpOp, TimeBound),
+    Snoozed(CmpOp, u32),
+    Id(CmpOp, u64),
+    Text(TextField, CmpOp, String),
+    Custom(String, CmpOp, FieldValue),
+    CustomSet(String),
+}
+
+impl Expr {
+    fn eval(&self, todo: &Todo, blocked: &HashSet<String>, now: DateTime<Utc>, tz: Tz) -> bool {
+        match self {
+            Expr::Or(left, right) => {
+                left.eval(todo, blocked, now, tz) || right.eval(todo, blocked, now, tz)
+            }
+            Expr::And(left, right) => {
+                left.eval(todo, blocked, now, tz) && right.eval(todo, blocked, now, tz)
+            }
+            Expr::Not(inner) => !inner.eval(todo, blocked, now, tz),
+            Expr::Flag(Flag::Done) => todo.done,
+            Expr::Flag(Flag::Blocked) => todo
+                .todo_id
+                .as_ref()
+                .is_some_and(|todo_id| blocked.contains(todo_id)),
+            Expr::Flag(Flag::Overdue) => todo.is_overdue(now, tz),
+            Expr::Flag(Flag::HasDue) => todo.due_date.is_some(),
+            Expr::Flag(Flag::HasComment) => todo.has_comment(),
+            Expr::Contains(needle, field) => text_values(todo, field)
+                .iter()
+                .any(|text| text.to_lowercase().contains(needle)),
+            Expr::Time(field, op, bound) => {
+                let (day, instant) = match (field, todo) {
+                    (
+                        TimeField::Due,
+                        Todo {
+                            due_date: Some(due),
+                            ..
+                        },
+                    ) => (due.date_in(tz), due.starts_at(tz)),
+                    (
+                        TimeField::Created,
+                        Todo {
+                            created_at: Some(at),
+                            ..
+                        },
+                    )
+                    | (
+                        TimeField::Completed,
+                        Todo {
+                            completed_at: Some(at),
+                            ..
+                        },
+                    ) => (at.with_timezone(&tz).date_naive(), *at),
+                    _ => return false,
+                };
+                match bound {
+                    TimeBound::Day(date) => op.holds(day.cmp(date)),
+                    TimeBound::Instant(at) => op.holds(instant.cmp(at)),
+                }
+            }
+            Expr::Snoozed(op, count) => op.holds(todo.snooze_count.cmp(count)),
+            Expr::Id(op, number) => todo
+                .todo_id
+                .as_deref()
+                .and_then(parse_todo_id)
+                .is_some_and(|own| op.holds(own.cmp(number))),
+            Expr::Text(field, op, value) => {
+                let equal = text_values(todo, field)
+                    .iter()
+                    .any(|text| text.to_lowercase() == *value);
+                equal == (*op == CmpOp::Eq)
+            }
+            Expr::Custom(name, op, value) => match todo.fields.get(name) {
+                Some(own) => compare_values(own, value).is_some_and(|ordering| op.holds(ordering)),
+                None => *op == CmpOp::Ne,
+            },
+            Expr::CustomSet(name) => match todo.fields.get(name) {
+                Some(FieldValue::Bool(value)) => *value,
+                Some(_) => true,
+                None => false,
+            },
+        }
+    }
+}
+
+fn text_values<'a>(todo: &'a Todo, field: &TextField) -> Vec<&'a str> {
+    let comment = todo.comment.as_deref();
+    match field {
+        TextField::Title => vec![&todo.title],
+        TextField::Comment => comment.into_iter().collect(),
+        TextField::Text => std::iter::once(todo.title.as_str())
+            .chain(comment)
+            .collect(),
+        TextField::Custom(name) => match todo.fields.get(name) {
+            Some(FieldValue::String(value)) => vec![value],
+            _ => Vec::new(),
+        },
+    }
+}
+
+fn compare_values(own: &FieldValue, value: &FieldValue) -> Option<Ordering> {
+    match (own, value) {
+        (FieldValue::String(own), FieldValue::String(value)) => Some(own.cmp(value)),
+        (FieldValue::Number(own), FieldValue::Number(value)) => own.partial_cmp(value),
+        (FieldValue::Date(own), FieldValue::Date(value)) => Some(own.cmp(value)),
+        (FieldValue::Bool(own), FieldValue::Bool(value)) => Some(own.cmp(value)),
+        _ => None,
+    }
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    position: usize,
+    schema: &'a FieldSchema,
+    now: DateTime<Utc>,
+    tz: Tz,
+}
+
+type Parsed<T> = std::result::Result<T, QueryError>;
+
+impl Parser<'_> {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.position]
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.position].clone();
+        if token.kind != TokenKind::End {
+            self.position += 1;
+        }
+        token
+    }
+
+    fn parse_or(&mut self) -> Parsed<Expr> {
+        let mut expr = self.parse_and()?;
+        while self.peek().keyword("or") {
+            self.next();
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Parsed<Expr> {
+        let mut expr = self.parse_not()?;
+        while self.peek().keyword("and") {
+            self.next();
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_not()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_not(&mut self) -> Parsed<Expr> {
+        let token = self.next();
+        match &token.kind {
+            _ if token.keyword("not") => Ok(Expr::Not(Box::new(self.parse_not()?))),
+            TokenKind::Open => {
+                let expr = self.parse_or()?;
+                let close = self.next();
+                if close.kind != TokenKind::Close {
+                    return Err(close.error(format!("expected ')' but found {}", close.kind)));
+                }
+                Ok(expr)
+            }
+            TokenKind::Str(needle) => {
+                let keyword = self.next();
+                if !keyword.keyword("in") {
+                    return Err(keyword.error(format!("expected 'in' but found {}", keyword.kind)));
+                }
+                let field = self.next();
+                let field = self.text_field(&field)?;
+                Ok(Expr::Contains(needle.to_lowercase(), field))
+            }
+            TokenKind::Word(_) if ["and", "or", "in"].iter().any(|kw| token.keyword(kw)) => {
+                Err(token.error(format!("expected a condition but found {}", token.kind)))
+            }
+            TokenKind::Word(name) => {
+                if let TokenKind::Op(op) = self.peek().kind {
+                    let op_token = self.next();
+                    let value = self.next();
+                    self.comparison(&token, name, op, &op_token, &value)
+                } else {
+                    self.bare_field(&token, name)
+                }
+            }
+            _ => Err(token.error(format!("expected a condition but found {}", token.kind))),
+        }
+    }
+
+    fn text_field(&self, token: &Token) -> Parsed<TextField> {
+        let TokenKind::Word(name) = &token.kind else {
+            return Err(token.error(format!("expected a field name but found {}", token.kind)));
+        };
+        match name.to_ascii_lowercase().as_str() {
+            "title" => Ok(TextField::Title),
+            "comment" => Ok(TextField::Comment),
+            "text" => Ok(TextField::Text),
+            _ => match self
+                .schema
+                .get(name)
+                .map(|definition| definition.field_type)
+            {
+                Some(FieldType::String | FieldType::Enum) => Ok(TextField::Custom(name.clone())),
+                Some(_) => {
+                    Err(token.error(format!("'in' needs a text field; '{name}' is not one")))
+                }
+                None => Err(token.error(format!("unknown field '{name}'"))),
+            },
+        }
+    }
+
+    fn bare_field(&self, token: &Token, name: &str) -> Parsed<Expr> {
+        let flag = match name.to_ascii_lowercase().as_str() {
+            "done" => Flag::Done,
+            "blocked" => Flag::Blocked,
+            "overdue" => Flag::Overdue,
+            "due" => Flag::HasDue,
+            "comment" => Flag::HasComment,
+            "title" | "text" | "created" | "completed" | "snoozed" | "id" => {
+                return Err(
+                    token.error(format!("'{name}' needs a comparison such as {name} = ..."))
+                );
+            }
+            _ if self.schema.get(name).is_some() => return Ok(Expr::CustomSet(name.to_string())),
+            _ => return Err(token.error(format!("unknown field '{name}'"))),
+        };
+        Ok(Expr::Flag(flag))
+    }
+
+    fn comparison(
+        &self,
+        field: &Token,
+        name: &str,
+        op: CmpOp,
+        op_token: &Token,
+        value_token: &Token,
+    ) -> Parsed<Expr> {
+        let value = match &value_token.kind {
+            TokenKind::Word(value) | TokenKind::Str(value) => value.as_str(),
+            other => {
+                return Err(value_token.error(format!(
+                    "expected a value after '{}' but found {other}",
+                    op.symbol()
+                )));
+            }
+        };
+        let invalid = |expected: &str| {
+            value_token.error(format!(
+                "invalid value '{value}' for {name} (expected {expected})"
+            ))
+        };
+        let equality_only = || {
+            Err(op_token.error(format!(
+                "'{}' does not apply to {name}; use = or !=",
+                op.symbol()
+            )))
+        };
+
+        let time_field = match name.to_ascii_lowercase().as_str() {
+            "due" => TimeField::Due,
+            "created" => TimeField::Created,
+            "completed" => TimeField::Completed,
+            "snoozed" => {
+                let count = value.parse().map_err(|_| invalid("a whole number"))?;
+                return Ok(Expr::Snoozed(op, count));
+            }
+            "id" => {
+                let number = parse_todo_id(value)
+                    .or_else(|| value.parse().ok())
+                    .ok_or_else(|| invalid("an id such as T3"))?;
+                return Ok(Expr::Id(op, number));
+            }
+            "title" | "comment" => {
+                if !op.is_equality() {
+                    return equality_only();
+                }
+                let field = if name.eq_ignore_ascii_case("title") {
+                    TextField::Title
+                } else {
+                    TextField::Comment
+                };
+                return Ok(Expr::Text(field, op, value.to_lowercase()));
+            }
+            _ => {
+                let Some(definition) = self.schema.get(name) else {
+                    return Err(field.error(format!("unknown field '{name}'")));
+                };
+                let ordered = matches!(definition.field_type, FieldType::Number | FieldType::Date);
+                if !ordered && !op.is_equality() {
+                    return equality_only();
+                }
+                let value = self
+                    .schema
+                    .parse_input(name, value)
+                    .map_err(|_| invalid(&definition.field_type.to_string()))?;
+                return Ok(Expr::Custom(name.to_string(), op, value));
+            }
+        };
+
+        let bound = match value.to_ascii_lowercase().as_str() {
+            "now" => TimeBound::Instant(self.now),
+            "today" => TimeBound::Day(today(self.now, self.tz)),
+            _ => match parse_due_argument(value, self.now) {
+                Ok(Due::Date(date)) => TimeBound::Day(date),
+                Ok(Due::At(at)) => TimeBound::Instant(at),
+                Err(_) => return Err(invalid("an offset like 2d, now, today, or a date")),
+            },
+        };
+        Ok(Expr::Time(time_field, op, bound))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::TodoItem;
+    use chrono::TimeZone;
+    use indexmap::IndexMap;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 1, 10, 12, 0, 0).unwrap()
+    }
+
+    fn todo(id: &str, title: &str, due: Option<Due>, done: bool) -> Todo {
+        Todo::from(TodoItem {
+            todo_id: Some(id.to_string()),
+            title: title.to_string(),
+            comment: None,
+            done,
+            due_date: due,
+            google_task_id: None,
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra: toml::Table::new(),
+        })
+    }
+
+    fn matching(query: &str, todos: &[Todo]) -> Vec<String> {
+        matching_with(query, todos, &FieldSchema::default(), now(), Tz::UTC)
+    }
+
+    fn matching_with(
+        query: &str,
+        todos: &[Todo],
+        schema: &FieldSchema,
+        now: DateTime<Utc>,
+        tz: Tz,
+    ) -> Vec<String> {
+        let query = Query::parse(query, schema, now, tz).unwrap_or_else(|e| panic!("{query}: {e}"));
+        let blocked = blocked_ids(todos);
+        todos
+            .iter()
+            .filter(|todo| query.matches(todo, &blocked))
+            .filter_map(|todo| todo.todo_id.clone())
+            .collect()
+    }
+
+    fn sample() -> Vec<Todo> {
+        let soon = Due::At(now() + chrono::Duration::hours(20));
+        let later = Due::Date(NaiveDate::from_ymd_opt(2025, 2, 1).unwrap());
+        let mut todos = vec![
+            todo("T1", "Deploy backend", Some(soon), false),
+            todo("T2", "Deploy docs", Some(later), false),
+            todo("T3", "Deploy hotfix", Some(soon), true),
+            todo("T4", "Write release notes", None, false),
+        ];
+        todos[3].blocked_by = vec!["T1".to_string()];
+        todos
+    }
+
+    fn at(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    /// One todo per built-in field shape, with `now()` as 2025-01-10 12:00 UTC:
+    /// T1 due tomorrow morning, T2 overdue and blocked by T1, T3 done, and T5
+    /// with no due.
+    fn fixture() -> Vec<Todo> {
+        let day = |d| Due::Date(NaiveDate::from_ymd_opt(2025, 1, d).unwrap());
+        let mut todos = vec![
+            todo("T1", "Alpha", Some(Due::At(at(2025, 1, 11, 8, 0))), false),
+            todo("T2", r#"Say "hi""#, Some(day(9)), false),
+            todo("T3", "Gamma", Some(Due::At(at(2025, 1, 8, 12, 0))), true),
+            todo("T5", "Delta (b) <= c", None, false),
+        ];
+        todos[0].comment = Some(r#"Call "Bob" first"#.to_string());
+        todos[0].created_at = Some(at(2025, 1, 1, 9, 0));
+        todos[1].comment = Some("   ".to_string());
+        todos[1].created_at = Some(at(2025, 1, 9, 23, 30));
+        todos[1].snooze_count = 2;
+        todos[1].blocked_by = vec!["T1".to_string()];
+        todos[2].comment = Some(r"C:\tmp".to_string());
+        todos[2].created_at = Some(at(2024, 12, 20, 9, 0));
+        todos[2].completed_at = Some(at(2025, 1, 10, 11, 0));
+        todos[2].snooze_count = 1;
+        todos[3].blocked_by = vec!["T3".to_string()];
+        todos
+    }
+
+    fn check_table(todos: &[Todo], cases: &[(&str, &[&str])]) {
+        for (query, expected) in cases {
+            assert_eq!(matching(query, todos), *expected, "{query}");
+        }
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or_and_not_tighter_than_both() {
+        check_table(
+            &fixture(),
+            &[
+                ("done or due and blocked", &["T2", "T3"]),
+                ("(done or due) and blocked", &["T2"]),
+                ("blocked and due or done", &["T2", "T3"]),
+                ("blocked and (due or done)", &["T2"]),
+                ("not done and due", &["T1", "T2"]),
+                ("not (done and due)", &["T1", "T2", "T5"]),
+                ("not done or blocked", &["T1", "T2", "T5"]),
+                ("not not done", &["T3"]),
+                ("due and not done or id = T5", &["T1", "T2", "T5"]),
+                ("((done))", &["T3"]),
+                ("DONE Or Overdue", &["T2", "T3"]),
+            ],
+        );
+    }
+
+    #[test]
+    fn built_in_fields_match_their_todos() {
+        check_table(
+            &fixture(),
+            &[
+                ("done", &["T3"]),
+                ("overdue", &["T2"]),
+                ("blocked", &["T2"]),
+                ("due", &["T1", "T2", "T3"]),
+                ("not due", &["T5"]),
+                ("comment", &["T1", "T3"]),
+                (r#""bob" in comment"#, &["T1"]),
+                (r#""tmp" in text"#, &["T3"]),
+                (r#""hi" in text"#, &["T2"]),
+                ("snoozed = 0", &["T1", "T5"]),
+                ("snoozed >= 1", &["T2", "T3"]),
+                ("snoozed < 2", &["T1", "T3", "T5"]),
+                ("id = T5", &["T5"]),
+                ("id <= 2", &["T1", "T2"]),
+                ("id != T1", &["T2", "T3", "T5"]),
+                ("created < 2025-01-05", &["T1", "T3"]),
+                ("created = 2025-01-09", &["T2"]),
+                ("created >= -1d", &["T2"]),
+                ("created != 2025-01-01", &["T2", "T3"]),
+                ("completed = today", &["T3"]),
+                ("completed > -2h", &["T3"]),
+                ("completed < -2h", &[]),
+                ("due = 2025-01-11", &["T1"]),
+                ("due < today", &["T2", "T3"]),
+                ("due > now", &["T1"]),
+                ("due >= 2025-01-09", &["T1", "T2"]),
+            ],
+        );
+
+        for bare in ["created", "completed", "snoozed", "id", "title"] {
+            let err = Query::parse(bare, &FieldSchema::default(), now(), Tz::UTC).unwrap_err();
+            assert!(err.message.contains("needs a comparison"), "{bare}: {err}");
+        }
+    }
+
+    #[test]
+    fn quoted_strings_unescape_quotes_and_keep_other_characters() {
+        check_table(
+            &fixture(),
+            &[
+                (r#"title = "say \"hi\"""#, &["T2"]),
+                (r#""\"hi\"" in title"#, &["T2"]),
+                (r#"comment = "call \"bob\" first""#, &["T1"]),
+                (r#"comment != "call \"bob\" first""#, &["T2", "T3", "T5"]),
+                (r#"comment = "c:\tmp""#, &["T3"]),
+                (r#"title = "delta (b) <= c""#, &["T5"]),
+                (r#""" in title and done"#, &["T3"]),
+            ],
+        );
+
+        let err =
+            Query::parse(r#"title = "abc\""#, &FieldSchema::default(), now(), Tz::UTC).unwrap_err();
+        assert_eq!(
+            (err.column, err.message.as_str()),
+            (9, "unterminated string")
+        );
+    }
+
+    #[test]
+    fn relative_offsets_compare_instants_and_dates_compare_days() {
+        check_table(
+            &fixture(),
+            &[
+                ("due <= 20h", &["T1", "T2", "T3"]),
+                ("due < 20h", &["T2", "T3"]),
+                ("due < 2025-01-11T08:00:00Z", &["T2", "T3"]),
+                ("due <= 2025-01-11", &["T1", "T2", "T3"]),
+                ("due < 2025-01-11", &["T2", "T3"]),
+                ("due > -1d", &["T1"]),
+            ],
+        );
+
+        // 22:30 on the 10th in New York is already the 11th in UTC.
+        let now = at(2025, 1, 11, 3, 30);
+        let new_york: Tz = "America/New_York".parse().unwrap();
+        let todos = vec![
+            // 21:00 on the 10th in New York.
+            todo("T1", "evening", Some(Due::At(at(2025, 1, 11, 2, 0))), false),
+            // 01:00 on the 11th in New York.
+            todo("T2", "night", Some(Due::At(at(2025, 1, 11, 6, 0))), false),
+            todo(
+                "T3",
+                "all day",
+                Some(Due::Date(NaiveDate::from_ymd_opt(2025, 1, 10).unwrap())),
+                false,
+            ),
+        ];
+        let cases: &[(&str, &[&str], &[&str])] = &[
+            ("due = today", &["T1", "T3"], &["T1", "T2"]),
+            ("due = 2025-01-11", &["T2"], &["T1", "T2"]),
+            ("due < 1h", &["T1", "T3"], &["T1", "T3"]),
+            ("overdue", &["T1"], &["T1", "T3"]),
+        ];
+        let schema = FieldSchema::default();
+        for (query, in_new_york, in_utc) in cases {
+            assert_eq!(
+                matching_with(query, &todos, &schema, now, new_york),
+                *in_new_york,
+                "{query} in New York"
+            );
+            assert_eq!(
+                matching_with(query, &todos, &schema, now, Tz::UTC),
+                *in_utc,
+                "{query} in UTC"
+            );
+        }
+    }
+
+    #[test]
+    fn custom_field_values_must_parse_as_the_declared_type() {
+        let table: toml::Table = toml::from_str(
+            r#"
+estimate = { type = "number" }
+start = { type = "date" }
+urgent = { type = "bool" }
+size = { type = "enum", values = ["small", "large"] }
+owner = { type = "string" }
+"#,
+        )
+        .expect("parse schema fixture");
+        let schema =
+            FieldSchema::from_toml(Some(&toml::Value::Table(table))).expect("valid schema");
+
+        let rejected = [
+            ("estimate = lots", 12, "expected number"),
+            ("start < 2025-13-01", 9, "expected date"),
+            ("urgent = maybe", 10, "expected bool"),
+            ("size = medium", 8, "expected enum"),
+            ("urgent < true", 8, "does not apply to urgent"),
+            ("size >= small", 6, "does not apply to size"),
+            ("owner > bob", 7, "does not apply to owner"),
+            (r#""x" in estimate"#, 8, "'in' needs a text field"),
+            ("nosuch = 1", 1, "unknown field 'nosuch'"),
+        ];
+        for (query, column, message) in rejected {
+            let err = Query::parse(query, &schema, now(), Tz::UTC).unwrap_err();
+            assert_eq!(err.column, column, "{query}: {err}");
+            assert!(err.message.contains(message), "{query}: {err}");
+        }
+
+        let mut todos = fixture();
+        let fields = [
+            ("estimate", FieldValue::Number(3.0)),
+            (
+                "start",
+                FieldValue::Date(NaiveDate::from_ymd_opt(2025, 1, 20).unwrap()),
+            ),
+            ("urgent", FieldValue::Bool(false)),
+            ("size", FieldValue::String("small".to_string())),
+            ("owner", FieldValue::String("Ana".to_string())),
+        ];
+        for (name, value) in fields {
+            todos[0].fields.insert(name.to_string(), value);
+        }
+        todos[1]
+            .fields
+            .insert("urgent".to_string(), FieldValue::Bool(true));
+
+        let cases: &[(&str, &[&str])] = &[
+            ("estimate = 3.0", &["T1"]),
+            ("estimate >= 3", &["T1"]),
+            ("estimate != 3", &["T2", "T3", "T5"]),
+            ("start < 2025-02-01", &["T1"]),
+            ("urgent", &["T2"]),
+            ("urgent = no", &["T1"]),
+            ("size = small", &["T1"]),
+            ("owner", &["T1"]),
+            (r#""an" in owner"#, &["T1"]),
+        ];
+        for (query, expected) in cases {
+            assert_eq!(
+                matching_with(query, &todos, &schema, now(), Tz::UTC),
+                *expected,
+                "{query}"
+            );
+        }
+    }
+
+    #[test]
+    fn combines_comparisons_flags_and_text_search() {
+        let todos = sample();
+
+        assert_eq!(
+            matching(r#"due<2d and not done and "deploy" in title"#, &todos),
+            ["T1"]
+        );
+        assert_eq!(
+            matching("blocked or due >= 2025-02-01", &todos),
+            ["T2", "T4"]
+        );
+        assert_eq!(matching("NOT (done OR due) ", &todos), ["T4"]);
+        assert_eq!(matching(r#"title = "deploy DOCS""#, &todos), ["T2"]);
+        assert_eq!(matching("id > T2 and id != 4", &todos), ["T3"]);
+    }
+
+    #[test]
+    fn custom_fields_compare_with_their_declared_type() {
+        let table: toml::Table = toml::from_str(
+            r#"
+estimate = { type = "number" }
+urgent = { type = "bool" }
+"#,
+        )
+        .expect("parse schema fixture");
+        let schema =
+            FieldSchema::from_toml(Some(&toml::Value::Table(table))).expect("valid schema");
+        let mut todos = sample();
+        todos[0]
+            .fields
+            .insert("estimate".into(), FieldValue::Number(3.0));
+        todos[1]
+            .fields
+            .insert("estimate".into(), FieldValue::Number(8.0));
+        todos[1]
+            .fields
+            .insert("urgent".into(), FieldValue::Bool(true));
+
+        let query = Query::parse("estimate < 5 or urgent", &schema, now(), Tz::UTC).unwrap();
+        let selected: Vec<_> = todos
+            .iter()
+            .filter(|todo| query.matches(todo, &HashSet::new()))
+            .map(|todo| todo.title.as_str())
+            .collect();
+        assert_eq!(selected, ["Deploy backend", "Deploy docs"]);
+
+        let err = Query::parse("estimate < lots", &schema, now(), Tz::UTC).unwrap_err();
+        assert_eq!(err.column, 12);
+    }
+
+    #[test]
+    fn parse_errors_point_at_the_offending_column() {
+        let column = |input: &str| {
+            Query::parse(input, &FieldSchema::default(), now(), Tz::UTC)
+                .unwrap_err()
+                .column
+        };
+
+        assert_eq!(column("due<"), 5);
+        assert_eq!(column("not dne"), 5);
+        assert_eq!(column("done and (overdue"), 18);
+        assert_eq!(column("done done"), 6);
+        assert_eq!(column("\"x\" in due"), 8);
+        assert_eq!(column("title < x"), 7);
+        assert_eq!(column("due < soon"), 7);
+        assert_eq!(column("done and \"open"), 10);
+
+        let err = parse_query("due < soon", &FieldSchema::default(), now(), Tz::UTC).unwrap_err();
+        assert!(err.to_string().ends_with("  due < soon\n        ^"));
+    }
+}
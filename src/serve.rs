@@ -0,0 +1,803 @@
+//! `juggler serve`: a JSON API over the TODO store on localhost.
+//!
+//! Every request must carry `Authorization: Bearer <token>`; `GET /events`
+//! also accepts `?token=<token>` for clients such as browser `EventSource`
+//! that cannot set headers. Routes:
+//!
+//! - `GET /todos` and `GET /todos/{id}`: todos in the JSON export shape.
+//! - `POST /todos`: create from `{"title", "due"?, "comment"?}`, where `due`
+//!   uses the CLI syntax (`3d`, `YYYY-MM-DD`, RFC 3339).
+//! - `PATCH /todos/{id}`: any of `title`, `comment`, `due`, `done`,
+//!   `blocked_by`; `null` clears `comment` and `due`.
+//! - `POST /todos/{id}/done` and `POST /todos/{id}/snooze` with `{"days": N}`.
+//! - `DELETE /todos/{id}`.
+//! - `GET /events`: Server-Sent Events named `add`, `done`, `modify`, and
+//!   `delete`, each with `{"todos": [...]}`, for every change to the store,
+//!   including changes made by other juggler processes.
+//!
+//! Writes go through the same command functions as the CLI, so they hold the
+//! store lock, archive the previous file, and run hooks.
+
+use std::collections::HashSet;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use http_body_util::combinators::BoxBody;
+use http_body_util::{BodyExt, Full, Limited};
+use hyper::body::{Bytes, Frame, Incoming};
+use hyper::header::{AUTHORIZATION, CONTENT_TYPE, WWW_AUTHENTICATE};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use log::{error, info, warn};
+use tokio::net::TcpListener;
+use tokio::sync::{Notify, broadcast, mpsc};
+
+use crate::commands::add::{NewTodo, add_todo};
+use crate::commands::export::JsonTodo;
+use crate::commands::mutate::{Targets, edit_todo, mutate_todos, remove_todos};
+use crate::due::parse_due_argument;
+use crate::error::{JugglerError, Result};
+use crate::hooks::{HookEvent, lifecycle_events};
+use crate::store::{load_todos, modified_time};
+use crate::time::SharedClock;
+use crate::ui::{BatchOp, Todo, TodoEditor};
+
+/// Largest request body accepted, far above any single todo.
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+/// How often the store file is checked for changes made by other processes.
+const STORE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Comment frames keep idle event streams from being closed by proxies.
+const EVENT_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+const EVENT_CHANNEL_CAPACITY: usize = 64;
+
+type ApiBody = BoxBody<Bytes, Infallible>;
+
+/// Everything a request handler needs; shared by all connections.
+struct ServerState {
+    todos_file: PathBuf,
+    token: String,
+    clock: SharedClock,
+    tz: Tz,
+    /// Serialized SSE frames for every connected `/events` client.
+    events: broadcast::Sender<Bytes>,
+    /// Wakes the store watcher right after a write through the API.
+    store_changed: Notify,
+}
+
+/// A fresh random API token for a server started without `--token`.
+pub fn generate_token() -> String {
+    URL_SAFE_NO_PAD.encode(rand::random::<[u8; 32]>())
+}
+
+/// Bind the API listener on `127.0.0.1:port`; port 0 picks a free one.
+pub async fn bind(port: u16) -> Result<TcpListener> {
+    Ok(TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], port))).await?)
+}
+
+/// Serve the API on `listener` until Ctrl-C.
+pub async fn run_server(
+    listener: TcpListener,
+    todos_file: PathBuf,
+    token: String,
+    clock: SharedClock,
+    tz: Tz,
+) -> Result<()> {
+    info!(
+        "Serving the juggler API on http://{}",
+        listener.local_addr()?
+    );
+
+    let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+    let state = Arc::new(ServerState {
+        todos_file,
+        token,
+        clock,
+        tz,
+        events,
+        store_changed: Notify::new(),
+    });
+    tokio::spawn(watch_store(Arc::clone(&state)));
+
+    loop {
+        let stream = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    error!("Failed to accept connection: {e}");
+                    continue;
+                }
+            },
+            _ = tokio::signal::ctrl_c() => break,
+        };
+        let state = Arc::clone(&state);
+        tokio::spawn(async move {
+            if let Err(err) = http1::Builder::new()
+                .serve_connection(
+                    TokioIo::new(stream),
+                    service_fn(move |req| handle_request(req, Arc::clone(&state))),
+                )
+                .await
+            {
+                error!("Error serving connection: {err:?}");
+            }
+        });
+    }
+
+    info!("API server stopped");
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Route {
+    List,
+    Create,
+    Get(String),
+    Update(String),
+    Done(String),
+    Snooze(String),
+    Delete(String),
+    Events,
+}
+
+impl Route {
+    fn writes(&self) -> bool {
+        !matches!(self, Route::List | Route::Get(_) | Route::Events)
+    }
+}
+
+/// Match a request line to a route; unknown paths are 404 and known paths
+/// with the wrong method are 405.
+fn route(method: &Method, path: &str) -> std::result::Result<Route, StatusCode> {
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let matched = match (method, segments.as_slice()) {
+        (&Method::GET, ["todos"]) => Some(Route::List),
+        (&Method::POST, ["todos"]) => Some(Route::Create),
+        (&Method::GET, ["todos", id]) => Some(Route::Get(id.to_string())),
+        (&Method::PATCH, ["todos", id]) => Some(Route::Update(id.to_string())),
+        (&Method::DELETE, ["todos", id]) => Some(Route::Delete(id.to_string())),
+        (&Method::POST, ["todos", id, "done"]) => Some(Route::Done(id.to_string())),
+        (&Method::POST, ["todos", id, "snooze"]) => Some(Route::Snooze(id.to_string())),
+        (&Method::GET, ["events"]) => Some(Route::Events),
+        _ => None,
+    };
+    matched.ok_or(match segments.as_slice() {
+        ["todos"] | ["todos", _] | ["todos", _, "done" | "snooze"] | ["events"] => {
+            StatusCode::METHOD_NOT_ALLOWED
+        }
+        _ => StatusCode::NOT_FOUND,
+    })
+}
+
+async fn handle_request(
+    req: Request<Incoming>,
+    state: Arc<ServerState>,
+) -> std::result::Result<Response<ApiBody>, Infallible> {
+    if !is_authorized(&req, &state.token) {
+        let mut response = error_response(StatusCode::UNAUTHORIZED, "Missing or invalid API token");
+        response
+            .headers_mut()
+            .insert(WWW_AUTHENTICATE, "Bearer".parse().expect("valid header"));
+        return Ok(response);
+    }
+    let route = match route(req.method(), req.uri().path()) {
+        Ok(route) => route,
+        Err(status) => {
+            return Ok(error_response(
+                status,
+                status.canonical_reason().unwrap_or_default(),
+            ));
+        }
+    };
+    if route == Route::Events {
+        return Ok(event_stream_response(&state));
+    }
+
+    let body = match Limited::new(req.into_body(), MAX_BODY_BYTES)
+        .collect()
+        .await
+    {
+        Ok(collected) => collected.to_bytes(),
+        Err(e) => {
+            return Ok(error_response(
+                StatusCode::BAD_REQUEST,
+                &format!("Failed to read request body: {e}"),
+            ));
+        }
+    };
+
+    // Store calls block on the file lock and on hooks, so keep them off the
+    // async workers.
+    let writes = route.writes();
+    let handler_state = Arc::clone(&state);
+    let result =
+        tokio::task::spawn_blocking(move || handle_store_route(&handler_state, route, &body)).await;
+    if writes {
+        state.store_changed.notify_one();
+    }
+    Ok(match result {
+        Ok(Ok(reply)) => reply.into_response(),
+        Ok(Err(e)) => error_response(error_status(&e), &e.to_string()),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+    })
+}
+
+/// Status and JSON body of a handled store route.
+#[derive(Debug)]
+struct Reply {
+    status: StatusCode,
+    body: Option<serde_json::Value>,
+}
+
+impl Reply {
+    fn json(status: StatusCode, body: serde_json::Value) -> Self {
+        Reply {
+            status,
+            body: Some(body),
+        }
+    }
+
+    fn todo(status: StatusCode, todo: &Todo) -> Result<Self> {
+        Ok(Reply::json(
+            status,
+            serde_json::to_value(JsonTodo::from(todo))?,
+        ))
+    }
+
+    fn not_found(todo_id: &str) -> Self {
+        Reply::json(
+            StatusCode::NOT_FOUND,
+            serde_json::json!({ "error": format!("Unknown todo id '{todo_id}'") }),
+        )
+    }
+
+    fn into_response(self) -> Response<ApiBody> {
+        match self.body {
+            Some(body) => json_response(self.status, &body),
+            None => Response::builder()
+                .status(self.status)
+                .body(Full::new(Bytes::new()).boxed())
+                .expect("valid response"),
+        }
+    }
+}
+
+/// Run one non-streaming route against the store.
+fn handle_store_route(state: &ServerState, route: Route, body: &[u8]) -> Result<Reply> {
+    let todos_file = state.todos_file.as_path();
+    let clock = state.clock.clone();
+    match route {
+        Route::List => {
+            let todos: Vec<JsonTodo> = load_todos(todos_file)?.iter().map(JsonTodo::from).collect();
+            Ok(Reply::json(StatusCode::OK, serde_json::to_value(todos)?))
+        }
+        Route::Get(todo_id) => respond_with_todo(todos_file, &todo_id, StatusCode::OK),
+        Route::Create => {
+            let new_todo: NewTodo = serde_json::from_slice(body)?;
+            let todo_id = add_todo(todos_file, new_todo, clock)?;
+            respond_with_todo(todos_file, &todo_id, StatusCode::CREATED)
+        }
+        Route::Update(todo_id) => {
+            let editor = PatchEditor {
+                patch: serde_json::from_slice(body)?,
+                now: clock.now(),
+            };
+            edit_todo(todos_file, &todo_id, &editor, clock, state.tz)?;
+            respond_with_todo(todos_file, &todo_id, StatusCode::OK)
+        }
+        Route::Done(todo_id) => mutate_one(state, &todo_id, BatchOp::Done),
+        Route::Snooze(todo_id) => {
+            let SnoozeRequest { days } = serde_json::from_slice(body)?;
+            mutate_one(state, &todo_id, BatchOp::Snooze { days })
+        }
+        Route::Delete(todo_id) => {
+            remove_todos(todos_file, &Targets::Ids(vec![todo_id]), clock, state.tz)?;
+            Ok(Reply {
+                status: StatusCode::NO_CONTENT,
+                body: None,
+            })
+        }
+        Route::Events => unreachable!("event streams are answered before reaching the store"),
+    }
+}
+
+fn mutate_one(state: &ServerState, todo_id: &str, op: BatchOp) -> Result<Reply> {
+    mutate_todos(
+        &state.todos_file,
+        &Targets::Ids(vec![todo_id.to_string()]),
+        op,
+        state.clock.clone(),
+        state.tz,
+    )?;
+    respond_with_todo(&state.todos_file, todo_id, StatusCode::OK)
+}
+
+fn respond_with_todo(todos_file: &Path, todo_id: &str, status: StatusCode) -> Result<Reply> {
+    match find_todo(todos_file, todo_id)? {
+        Some(todo) => Reply::todo(status, &todo),
+        None => Ok(Reply::not_found(todo_id)),
+    }
+}
+
+fn find_todo(todos_file: &Path, todo_id: &str) -> Result<Option<Todo>> {
+    Ok(load_todos(todos_file)?
+        .into_iter()
+        .find(|todo| todo.todo_id.as_deref() == Some(todo_id)))
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SnoozeRequest {
+    days: i64,
+}
+
+/// Body of `PATCH /todos/{id}`; absent keys leave the todo unchanged.
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TodoPatch {
+    title: Option<String>,
+    #[serde(default, deserialize_with = "present")]
+    comment: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    due: Option<Option<String>>,
+    done: Option<bool>,
+    blocked_by: Option<Vec<String>>,
+}
+
+/// Tell an explicit `null` (`Some(None)`) apart from a missing key (`None`).
+fn present<'de, D, T>(deserializer: D) -> std::result::Result<Option<Option<T>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::Deserialize<'de>,
+{
+    <Option<T> as serde::Deserialize>::deserialize(deserializer).map(Some)
+}
+
+/// Applies a `TodoPatch` in place of an interactive editor, so API updates get
+/// the same validation as `juggler edit`.
+struct PatchEditor {
+    patch: TodoPatch,
+    now: DateTime<Utc>,
+}
+
+impl TodoEditor for PatchEditor {
+    fn edit_todo(&self, todo: &Todo) -> Result<Todo> {
+        let mut updated = todo.clone();
+        if let Some(title) = &self.patch.title {
+            updated.title = title.trim().to_string();
+        }
+        if let Some(comment) = &self.patch.comment {
+            updated.comment = comment.clone().filter(|comment| !comment.trim().is_empty());
+        }
+        if let Some(due) = &self.patch.due {
+            updated.due_date = due
+                .as_deref()
+                .map(|due| parse_due_argument(due, self.now))
+                .transpose()?;
+        }
+        if let Some(done) = self.patch.done {
+            updated.done = done;
+        }
+        if let Some(blocked_by) = &self.patch.blocked_by {
+            updated.blocked_by = blocked_by.clone();
+        }
+        Ok(updated)
+    }
+
+    fn needs_terminal_restoration(&self) -> bool {
+        false
+    }
+}
+
+/// Map a store error to the status a client can act on. Unknown ids are
+/// reported by the locked write itself, so there is no separate lookup that
+/// another process could invalidate before the write.
+fn error_status(error: &JugglerError) -> StatusCode {
+    match error {
+        JugglerError::Config(_) | JugglerError::Json(_) => StatusCode::BAD_REQUEST,
+        JugglerError::NotFound(_) => StatusCode::NOT_FOUND,
+        JugglerError::Hook(_) => StatusCode::CONFLICT,
+        JugglerError::Locked(_) => StatusCode::SERVICE_UNAVAILABLE,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+fn json_response(status: StatusCode, body: &serde_json::Value) -> Response<ApiBody> {
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Full::new(Bytes::from(body.to_string())).boxed())
+        .expect("valid response")
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response<ApiBody> {
+    json_response(status, &serde_json::json!({ "error": message }))
+}
+
+fn is_authorized<B>(req: &Request<B>, token: &str) -> bool {
+    let bearer = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    let query = (req.uri().path() == "/events")
+        .then(|| req.uri().query())
+        .flatten()
+        .and_then(|query| {
+            url::form_urlencoded::parse(query.as_bytes())
+                .find(|(key, _)| key == "token")
+                .map(|(_, value)| value.into_owned())
+        });
+    bearer
+        .map(str::to_string)
+        .or(query)
+        .is_some_and(|given| tokens_match(&given, token))
+}
+
+/// Compare without an early exit so response timing does not leak how much
+/// of a guessed token was right.
+fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// A response body fed frame by frame from a channel.
+struct ChannelBody {
+    frames: mpsc::Receiver<Bytes>,
+}
+
+impl hyper::body::Body for ChannelBody {
+    type Data = Bytes;
+    type Error = Infallible;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<std::result::Result<Frame<Bytes>, Infallible>>> {
+        self.frames
+            .poll_recv(cx)
+            .map(|frame| frame.map(|bytes| Ok(Frame::data(bytes))))
+    }
+}
+
+/// Subscribe a client to the change feed; the forwarding task ends when the
+/// client disconnects.
+fn event_stream_response(state: &ServerState) -> Response<ApiBody> {
+    let mut changes = state.events.subscribe();
+    let (frames, receiver) = mpsc::channel(EVENT_CHANNEL_CAPACITY);
+    tokio::spawn(async move {
+        let mut keepalive = tokio::time::interval(EVENT_KEEPALIVE_INTERVAL);
+        loop {
+            let frame = tokio::select! {
+                change = changes.recv() => match change {
+                    Ok(frame) => frame,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Event stream client fell behind; skipped {skipped} event(s)");
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                _ = keepalive.tick() => Bytes::from_static(b": keepalive\n\n"),
+            };
+            if frames.send(frame).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    Response::builder()
+        .header(CONTENT_TYPE, "text/event-stream")
+        .header("cache-control", "no-cache")
+        .body(ChannelBody { frames: receiver }.boxed())
+        .expect("valid response")
+}
+
+/// Broadcast the difference each time the store file changes, whether the
+/// change came through the API or from another process.
+async fn watch_store(state: Arc<ServerState>) {
+    let mut snapshot = load_todos(&state.todos_file).unwrap_or_default();
+    let mut last_modified = modified_time(&state.todos_file);
+    loop {
+        tokio::select! {
+            _ = state.store_changed.notified() => {}
+            _ = tokio::time::sleep(STORE_POLL_INTERVAL) => {}
+        }
+        let modified = modified_time(&state.todos_file);
+        if modified == last_modified {
+            continue;
+        }
+        last_modified = modified;
+        match load_todos(&state.todos_file) {
+            Ok(todos) => {
+                for frame in change_frames(&snapshot, &todos) {
+                    // No subscribers is not an error.
+                    let _ = state.events.send(frame);
+                }
+                snapshot = todos;
+            }
+            Err(e) => warn!("Not streaming changes; failed to reload the store: {e}"),
+        }
+    }
+}
+
+/// SSE frames describing how `after` differs from `before`.
+fn change_frames(before: &[Todo], after: &[Todo]) -> Vec<Bytes> {
+    let remaining: HashSet<&str> = after
+        .iter()
+        .filter_map(|todo| todo.todo_id.as_deref())
+        .collect();
+    let deleted: Vec<Todo> = before
+        .iter()
+        .filter(|todo| {
+            todo.todo_id
+                .as_deref()
+                .is_some_and(|todo_id| !remaining.contains(todo_id))
+        })
+        .cloned()
+        .collect();
+
+    lifecycle_events(before, after)
+        .into_iter()
+        .map(|(event, todos)| {
+            let name = match event {
+                HookEvent::OnAdd => "add",
+                HookEvent::OnDone => "done",
+                _ => "modify",
+            };
+            (name, todos)
+        })
+        .chain((!deleted.is_empty()).then_some(("delete", deleted)))
+        .map(|(name, todos)| {
+            let data = serde_json::json!({
+                "todos": todos.iter().map(JsonTodo::from).collect::<Vec<_>>(),
+            });
+            Bytes::from(format!("event: {name}\ndata: {data}\n\n"))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::test_clock;
+
+    fn state(temp_dir: &tempfile::TempDir) -> ServerState {
+        ServerState {
+            todos_file: temp_dir.path().join("TODOs.toml"),
+            token: "secret".to_string(),
+            clock: test_clock(),
+            tz: chrono_tz::UTC,
+            events: broadcast::channel(1).0,
+            store_changed: Notify::new(),
+        }
+    }
+
+    fn call(state: &ServerState, method: Method, path: &str, body: &str) -> Reply {
+        let route = route(&method, path).expect("known route");
+        handle_store_route(state, route, body.as_bytes()).expect("handled")
+    }
+
+    #[test]
+    fn routes_cover_crud_and_reject_unknown_paths_and_methods() {
+        assert_eq!(route(&Method::GET, "/todos"), Ok(Route::List));
+        assert_eq!(
+            route(&Method::POST, "/todos/T3/snooze"),
+            Ok(Route::Snooze("T3".to_string()))
+        );
+        assert_eq!(
+            route(&Method::DELETE, "/todos/T3/"),
+            Ok(Route::Delete("T3".to_string()))
+        );
+        assert_eq!(
+            route(&Method::PUT, "/todos/T3"),
+            Err(StatusCode::METHOD_NOT_ALLOWED)
+        );
+        assert_eq!(route(&Method::GET, "/nope"), Err(StatusCode::NOT_FOUND));
+    }
+
+    #[test]
+    fn requests_need_the_bearer_token_or_an_events_query_token() {
+        let request = |uri: &str, header: Option<&str>| {
+            let mut builder = Request::builder().uri(uri);
+            if let Some(header) = header {
+                builder = builder.header(AUTHORIZATION, header);
+            }
+            builder.body(()).unwrap()
+        };
+
+        assert!(is_authorized(
+            &request("/todos", Some("Bearer secret")),
+            "secret"
+        ));
+        assert!(!is_authorized(
+            &request("/todos", Some("Bearer secreT")),
+            "secret"
+        ));
+        assert!(!is_authorized(&request("/todos", None), "secret"));
+        assert!(is_authorized(
+            &request("/events?token=secret", None),
+            "secret"
+        ));
+        assert!(!is_authorized(
+            &request("/todos?token=secret", None),
+            "secret"
+        ));
+    }
+
+    #[test]
+    fn store_routes_create_update_complete_and_delete() {
+        let temp_dir = tempfile::TempDir::new().expect("create temp dir");
+        let state = state(&temp_dir);
+
+        let created = call(
+            &state,
+            Method::POST,
+            "/todos",
+            r#"{"title": "Deploy", "due": "1d", "comment": "after standup"}"#,
+        );
+        assert_eq!(created.status, StatusCode::CREATED);
+        assert_eq!(created.body.as_ref().unwrap()["id"], "T1");
+        assert_eq!(
+            created.body.as_ref().unwrap()["due"],
+            "2025-01-02T00:00:00+00:00"
+        );
+
+        let patched = call(
+            &state,
+            Method::PATCH,
+            "/todos/T1",
+            r#"{"title": "Deploy v2", "comment": null}"#,
+        );
+        let body = patched.body.unwrap();
+        assert_eq!(body["title"], "Deploy v2");
+        assert!(body.get("comment").is_none());
+        assert_eq!(body["due"], "2025-01-02T00:00:00+00:00");
+
+        let snoozed = call(&state, Method::POST, "/todos/T1/snooze", r#"{"days": 2}"#);
+        assert_eq!(snoozed.body.unwrap()["due"], "2025-01-04T00:00:00+00:00");
+
+        let done = call(&state, Method::POST, "/todos/T1/done", "");
+        assert_eq!(done.body.unwrap()["done"], true);
+
+        let deleted = call(&state, Method::DELETE, "/todos/T1", "");
+        assert_eq!(deleted.status, StatusCode::NO_CONTENT);
+        let listed = call(&state, Method::GET, "/todos", "");
+        assert_eq!(listed.body.unwrap(), serde_json::json!([]));
+        assert_eq!(
+            call(&state, Method::GET, "/todos/T1", "").status,
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    #[test]
+    fn invalid_updates_are_client_errors_and_leave_the_store_alone() {
+        let temp_dir = tempfile::TempDir::new().expect("create temp dir");
+        let state = state(&temp_dir);
+        call(&state, Method::POST, "/todos", r#"{"title": "Deploy"}"#);
+        let before = std::fs::read_to_string(&state.todos_file).unwrap();
+
+        for body in [
+            r#"{"title": " "}"#,
+            r#"{"blocked_by": ["T9"]}"#,
+            r#"{"colour": 1}"#,
+        ] {
+            let route = route(&Method::PATCH, "/todos/T1").unwrap();
+            let error = handle_store_route(&state, route, body.as_bytes()).unwrap_err();
+            assert_eq!(error_status(&error), StatusCode::BAD_REQUEST, "{body}");
+        }
+        assert_eq!(std::fs::read_to_string(&state.todos_file).unwrap(), before);
+    }
+
+    #[test]
+    fn unknown_ids_lock_contention_and_broken_stores_get_their_own_statuses() {
+        let temp_dir = tempfile::TempDir::new().expect("create temp dir");
+        let state = state(&temp_dir);
+        call(&state, Method::POST, "/todos", r#"{"title": "Deploy"}"#);
+
+        for (method, path, body) in [
+            (Method::PATCH, "/todos/T9", r#"{"title": "Ship"}"#),
+            (Method::POST, "/todos/T9/done", ""),
+            (Method::POST, "/todos/T9/snooze", r#"{"days": 1}"#),
+            (Method::DELETE, "/todos/T9", ""),
+        ] {
+            let route = route(&method, path).unwrap();
+            let error = handle_store_route(&state, route, body.as_bytes()).unwrap_err();
+            assert_eq!(
+                error_status(&error),
+                StatusCode::NOT_FOUND,
+                "{method} {path}"
+            );
+        }
+
+        let locked = JugglerError::locked("TODO store is locked by another juggler process");
+        assert_eq!(error_status(&locked), StatusCode::SERVICE_UNAVAILABLE);
+
+        let content = std::fs::read_to_string(&state.todos_file).unwrap();
+        std::fs::write(
+            &state.todos_file,
+            content.replace("format_version = 1", "format_version = 99"),
+        )
+        .unwrap();
+        let route = route(&Method::POST, "/todos/T1/done").unwrap();
+        let error = handle_store_route(&state, route, b"").unwrap_err();
+        assert_eq!(
+            error_status(&error),
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn change_frames_name_adds_completions_edits_and_deletions() {
+        let todo = |id: &str, title: &str, done: bool| {
+            Todo::from(crate::store::TodoItem {
+                todo_id: Some(id.to_string()),
+                title: title.to_string(),
+                comment: None,
+                done,
+                due_date: None,
+                google_task_id: None,
+                blocked_by: Vec::new(),
+                log: Vec::new(),
+                created_at: None,
+                completed_at: None,
+                snooze_count: 0,
+                fields: indexmap::IndexMap::new(),
+                extra: toml::Table::new(),
+            })
+        };
+        let before = vec![
+            todo("T1", "a", false),
+            todo("T2", "b", false),
+            todo("T3", "c", false),
+        ];
+        let after = vec![
+            todo("T1", "a", true),
+            todo("T2", "b!", false),
+            todo("T4", "d", false),
+        ];
+
+        let frames: Vec<(String, serde_json::Value)> = change_frames(&before, &after)
+            .iter()
+            .map(|frame| {
+                let frame = String::from_utf8(frame.to_vec()).unwrap();
+                let (event, data) = frame
+                    .strip_suffix("\n\n")
+                    .and_then(|frame| frame.split_once('\n'))
+                    .expect("event and data lines");
+                (
+                    event.trim_start_matches("event: ").to_string(),
+                    serde_json::from_str(data.trim_start_matches("data: ")).unwrap(),
+                )
+            })
+            .collect();
+
+        let summary: Vec<(&str, &serde_json::Value)> = frames
+            .iter()
+            .map(|(event, data)| (event.as_str(), &data["todos"][0]["id"]))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("add", &serde_json::json!("T4")),
+                ("done", &serde_json::json!("T1")),
+                ("modify", &serde_json::json!("T2")),
+                ("delete", &serde_json::json!("T3")),
+            ]
+        );
+    }
+}
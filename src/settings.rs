@@ -0,0 +1,483 @@
+//! Persistent settings from `config.toml` in the juggler directory.
+//!
+//! Every setting is layered: a command-line flag wins over its environment
+//! variable, which wins over `config.toml`, which wins over the built-in
+//! default. This is the same precedence `get_juggler_dir` applies to the data
+//! directory itself. `juggler config` reads and writes the file; the rest of
+//! the program consumes the resolved [`Settings`].
+
+use std::fmt;
+use std::path::Path;
+
+use chrono::Duration;
+use indexmap::IndexMap;
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::commands::status::{StatusCounts, render_status};
+use crate::config::{
+    DEFAULT_EDITOR, DEFAULT_STATUS_FORMAT, DUE_SOON_THRESHOLD_SECS, GOOGLE_TASK_TITLE_PREFIX,
+    GOOGLE_TASKS_LIST_NAME,
+};
+use crate::due::resolve_timezone;
+use crate::error::{JugglerError, Result};
+use crate::google_tasks::SyncTarget;
+use crate::store::{ensure_parent_dir, write_file_atomically};
+use crate::ui::{Keymap, format_duration_compact, parse_relative_duration};
+
+/// A setting that can be stored in `config.toml`, named by its dotted key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SettingKey {
+    #[value(name = "editor")]
+    Editor,
+    #[value(name = "timezone")]
+    Timezone,
+    #[value(name = "due_soon")]
+    DueSoon,
+    #[value(name = "status_format")]
+    StatusFormat,
+    #[value(name = "google_tasks.list_name")]
+    ListName,
+    #[value(name = "google_tasks.title_prefix")]
+    TitlePrefix,
+    #[value(name = "google_tasks.oauth_client_id")]
+    OAuthClientId,
+    #[value(name = "google_tasks.oauth_client_secret")]
+    OAuthClientSecret,
+}
+
+impl SettingKey {
+    pub const ALL: [SettingKey; 8] = [
+        SettingKey::Editor,
+        SettingKey::Timezone,
+        SettingKey::DueSoon,
+        SettingKey::StatusFormat,
+        SettingKey::ListName,
+        SettingKey::TitlePrefix,
+        SettingKey::OAuthClientId,
+        SettingKey::OAuthClientSecret,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SettingKey::Editor => "editor",
+            SettingKey::Timezone => "timezone",
+            SettingKey::DueSoon => "due_soon",
+            SettingKey::StatusFormat => "status_format",
+            SettingKey::ListName => "google_tasks.list_name",
+            SettingKey::TitlePrefix => "google_tasks.title_prefix",
+            SettingKey::OAuthClientId => "google_tasks.oauth_client_id",
+            SettingKey::OAuthClientSecret => "google_tasks.oauth_client_secret",
+        }
+    }
+
+    /// Whether `config show` masks the value.
+    pub fn is_secret(self) -> bool {
+        self == SettingKey::OAuthClientSecret
+    }
+
+    fn default_value(self) -> Option<String> {
+        match self {
+            SettingKey::Editor => Some(DEFAULT_EDITOR.to_string()),
+            SettingKey::DueSoon => Some(format_duration_compact(Duration::seconds(
+                DUE_SOON_THRESHOLD_SECS,
+            ))),
+            SettingKey::StatusFormat => Some(DEFAULT_STATUS_FORMAT.to_string()),
+            SettingKey::ListName => Some(GOOGLE_TASKS_LIST_NAME.to_string()),
+            SettingKey::TitlePrefix => Some(GOOGLE_TASK_TITLE_PREFIX.to_string()),
+            SettingKey::Timezone | SettingKey::OAuthClientId | SettingKey::OAuthClientSecret => {
+                None
+            }
+        }
+    }
+
+    /// Reject values that would only fail later, when the setting is used.
+    fn validate(self, value: &str) -> Result<()> {
+        let invalid = |reason: &str| {
+            Err(JugglerError::config(format!(
+                "Invalid {} '{value}': {reason}",
+                self.name()
+            )))
+        };
+        match self {
+            SettingKey::Timezone => resolve_timezone(Some(value)).map(|_| ()),
+            SettingKey::DueSoon => match parse_relative_duration(value) {
+                Some(duration) if duration > Duration::zero() => Ok(()),
+                _ => invalid("expected a positive duration such as 2d or 12h"),
+            },
+            SettingKey::StatusFormat => render_status(value, &StatusCounts::default()).map(|_| ()),
+            SettingKey::TitlePrefix => Ok(()),
+            SettingKey::Editor
+            | SettingKey::ListName
+            | SettingKey::OAuthClientId
+            | SettingKey::OAuthClientSecret => {
+                if value.trim().is_empty() {
+                    invalid("must not be empty")
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for SettingKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The contents of `config.toml`. Absent keys fall through to the defaults;
+/// unknown ones are kept so that `config set` does not drop them.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigFile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    editor: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    timezone: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    due_soon: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    status_format: Option<String>,
+    #[serde(default, skip_serializing_if = "GoogleTasksConfig::is_empty")]
+    google_tasks: GoogleTasksConfig,
+    /// TUI key bindings by action name, applied over the defaults.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    keys: IndexMap<String, String>,
+    #[serde(flatten)]
+    extra: toml::Table,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+struct GoogleTasksConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    list_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    title_prefix: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    oauth_client_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    oauth_client_secret: Option<String>,
+    #[serde(flatten)]
+    extra: toml::Table,
+}
+
+impl GoogleTasksConfig {
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl ConfigFile {
+    /// Read `path` without checking the values, as `juggler config` needs to
+    /// fix a bad one; a missing file is an empty configuration. Unknown keys
+    /// are logged as warnings.
+    pub fn read(path: &Path) -> Result<Self> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.into()),
+        };
+        let file: ConfigFile = toml::from_str(&content)
+            .map_err(|e| JugglerError::config(format!("{}: {e}", path.display())))?;
+        for key in file.unknown_keys() {
+            warn!("{}: ignoring unknown key '{key}'", path.display());
+        }
+        Ok(file)
+    }
+
+    /// Read `path` and validate every setting in it.
+    pub fn load(path: &Path) -> Result<Self> {
+        let file = Self::read(path)?;
+        for key in SettingKey::ALL {
+            if let Some(value) = file.get(key) {
+                key.validate(value).map_err(|e| {
+                    JugglerError::config(format!("{}: {}", path.display(), error_message(e)))
+                })?;
+            }
+        }
+        Ok(file)
+    }
+
+    /// Dotted names of the keys juggler does not recognise, such as typos.
+    pub fn unknown_keys(&self) -> Vec<String> {
+        let google_tasks = self
+            .google_tasks
+            .extra
+            .keys()
+            .map(|key| format!("google_tasks.{key}"));
+        self.extra.keys().cloned().chain(google_tasks).collect()
+    }
+
+    /// The TUI key bindings, with the `[keys]` table applied. Only the TUI
+    /// needs them, so `load` leaves a bad `[keys]` table for this to report.
+    pub fn keymap(&self) -> Result<Keymap> {
+        Keymap::from_config(&self.keys)
+            .map_err(|e| JugglerError::config(format!("[keys]: {}", error_message(e))))
+    }
+
+    /// Write the file atomically, creating the juggler directory if needed
+    /// with the same private permissions the store gets.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        ensure_parent_dir(path)?;
+        write_file_atomically(path, &toml::to_string(self)?)
+    }
+
+    pub fn get(&self, key: SettingKey) -> Option<&str> {
+        match key {
+            SettingKey::Editor => self.editor.as_deref(),
+            SettingKey::Timezone => self.timezone.as_deref(),
+            SettingKey::DueSoon => self.due_soon.as_deref(),
+            SettingKey::StatusFormat => self.status_format.as_deref(),
+            SettingKey::ListName => self.google_tasks.list_name.as_deref(),
+            SettingKey::TitlePrefix => self.google_tasks.title_prefix.as_deref(),
+            SettingKey::OAuthClientId => self.google_tasks.oauth_client_id.as_deref(),
+            SettingKey::OAuthClientSecret => self.google_tasks.oauth_client_secret.as_deref(),
+        }
+    }
+
+    /// Store `value` under `key` after checking it is usable.
+    pub fn set(&mut self, key: SettingKey, value: &str) -> Result<()> {
+        key.validate(value)?;
+        let slot = match key {
+            SettingKey::Editor => &mut self.editor,
+            SettingKey::Timezone => &mut self.timezone,
+            SettingKey::DueSoon => &mut self.due_soon,
+            SettingKey::StatusFormat => &mut self.status_format,
+            SettingKey::ListName => &mut self.google_tasks.list_name,
+            SettingKey::TitlePrefix => &mut self.google_tasks.title_prefix,
+            SettingKey::OAuthClientId => &mut self.google_tasks.oauth_client_id,
+            SettingKey::OAuthClientSecret => &mut self.google_tasks.oauth_client_secret,
+        };
+        *slot = Some(value.to_string());
+        Ok(())
+    }
+}
+
+fn error_message(error: JugglerError) -> String {
+    match error {
+        JugglerError::Config(message) => message,
+        other => other.to_string(),
+    }
+}
+
+/// Where a resolved setting came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingSource {
+    Flag(&'static str),
+    Env(&'static str),
+    ConfigFile,
+    Default,
+}
+
+impl fmt::Display for SettingSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SettingSource::Flag(flag) => write!(f, "flag {flag}"),
+            SettingSource::Env(var) => write!(f, "env {var}"),
+            SettingSource::ConfigFile => f.write_str("config.toml"),
+            SettingSource::Default => f.write_str("default"),
+        }
+    }
+}
+
+/// A value supplied by a flag or environment variable, shadowing the file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Override {
+    pub key: SettingKey,
+    pub value: String,
+    pub source: SettingSource,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedSetting {
+    pub key: SettingKey,
+    pub value: Option<String>,
+    pub source: SettingSource,
+}
+
+/// Effective settings after layering overrides, the file, and defaults.
+#[derive(Debug, Clone)]
+pub struct Settings {
+    entries: Vec<ResolvedSetting>,
+}
+
+impl Settings {
+    pub fn resolve(file: &ConfigFile, overrides: &[Override]) -> Self {
+        let entries = SettingKey::ALL
+            .into_iter()
+            .map(|key| {
+                let overridden = overrides.iter().find(|o| o.key == key);
+                let (value, source) = match (overridden, file.get(key)) {
+                    (Some(o), _) => (Some(o.value.clone()), o.source),
+                    (None, Some(value)) => (Some(value.to_string()), SettingSource::ConfigFile),
+                    (None, None) => (key.default_value(), SettingSource::Default),
+                };
+                ResolvedSetting { key, value, source }
+            })
+            .collect();
+        Self { entries }
+    }
+
+    pub fn entries(&self) -> &[ResolvedSetting] {
+        &self.entries
+    }
+
+    pub fn get(&self, key: SettingKey) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|entry| entry.key == key)
+            .and_then(|entry| entry.value.as_deref())
+    }
+
+    pub fn editor(&self) -> &str {
+        self.get(SettingKey::Editor).unwrap_or(DEFAULT_EDITOR)
+    }
+
+    pub fn due_soon(&self) -> Duration {
+        self.get(SettingKey::DueSoon)
+            .and_then(parse_relative_duration)
+            .unwrap_or_else(|| Duration::seconds(DUE_SOON_THRESHOLD_SECS))
+    }
+
+    pub fn status_format(&self) -> &str {
+        self.get(SettingKey::StatusFormat)
+            .unwrap_or(DEFAULT_STATUS_FORMAT)
+    }
+
+    pub fn sync_target(&self) -> SyncTarget {
+        let defaults = SyncTarget::default();
+        SyncTarget {
+            list_name: self
+                .get(SettingKey::ListName)
+                .map_or(defaults.list_name, ToString::to_string),
+            title_prefix: self
+                .get(SettingKey::TitlePrefix)
+                .map_or(defaults.title_prefix, ToString::to_string),
+        }
+    }
+}
+
+/// `$VISUAL`, then `$EDITOR`, as the editor launcher picks them.
+pub fn editor_env_override() -> Option<Override> {
+    ["VISUAL", "EDITOR"].into_iter().find_map(|var| {
+        std::env::var(var)
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+            .map(|value| Override {
+                key: SettingKey::Editor,
+                value,
+                source: SettingSource::Env(var),
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn resolve_prefers_overrides_then_file_then_defaults() {
+        let mut file = ConfigFile::default();
+        file.set(SettingKey::Timezone, "Europe/Stockholm").unwrap();
+        file.set(SettingKey::ListName, "work").unwrap();
+        let overrides = [Override {
+            key: SettingKey::Timezone,
+            value: "UTC".to_string(),
+            source: SettingSource::Flag("--timezone"),
+        }];
+
+        let settings = Settings::resolve(&file, &overrides);
+
+        assert_eq!(settings.get(SettingKey::Timezone), Some("UTC"));
+        assert_eq!(settings.get(SettingKey::ListName), Some("work"));
+        assert_eq!(settings.get(SettingKey::OAuthClientId), None);
+        let sources: Vec<SettingSource> = settings.entries().iter().map(|e| e.source).collect();
+        assert_eq!(sources[1], SettingSource::Flag("--timezone"));
+        assert_eq!(sources[4], SettingSource::ConfigFile);
+        assert_eq!(sources[5], SettingSource::Default);
+        assert_eq!(
+            settings.sync_target(),
+            SyncTarget {
+                list_name: "work".to_string(),
+                title_prefix: GOOGLE_TASK_TITLE_PREFIX.to_string(),
+            }
+        );
+        assert_eq!(settings.due_soon(), Duration::days(2));
+    }
+
+    #[test]
+    fn config_file_roundtrips_and_validates_values() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("nested").join("config.toml");
+        assert_eq!(ConfigFile::load(&path).unwrap(), ConfigFile::default());
+
+        let mut file = ConfigFile::default();
+        file.set(SettingKey::DueSoon, "12h").unwrap();
+        file.set(SettingKey::OAuthClientId, "client").unwrap();
+        assert!(file.set(SettingKey::DueSoon, "soon").is_err());
+        assert!(file.set(SettingKey::Timezone, "Mars/Olympus").is_err());
+        assert!(file.set(SettingKey::ListName, " ").is_err());
+        assert!(file.set(SettingKey::StatusFormat, "{late}").is_err());
+        file.save(&path).unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "due_soon = \"12h\"\n\n[google_tasks]\noauth_client_id = \"client\"\n"
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let dir_mode = std::fs::metadata(path.parent().unwrap())
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(dir_mode & 0o777, 0o700);
+        }
+        assert_eq!(ConfigFile::load(&path).unwrap(), file);
+
+        std::fs::write(&path, "due_soon = \"-1d\"\n").unwrap();
+        let err = ConfigFile::load(&path).unwrap_err().to_string();
+        assert!(err.contains("Invalid due_soon '-1d'"), "{err}");
+        std::fs::write(&path, "[keys]\ntoggle_done = \"d\"\n").unwrap();
+        let file = ConfigFile::load(&path).unwrap();
+        assert!(file.keymap().is_ok());
+        file.save(&path).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "[keys]\ntoggle_done = \"d\"\n"
+        );
+        std::fs::write(&path, "[keys]\ntoggle_done = \"x\"\n").unwrap();
+        let file = ConfigFile::load(&path).unwrap();
+        let err = file.keymap().unwrap_err().to_string();
+        assert!(err.contains("[keys]: Duplicate key binding 'x'"), "{err}");
+        std::fs::write(&path, "colour = \"red\"\n").unwrap();
+        let file = ConfigFile::load(&path).unwrap();
+        assert_eq!(file.unknown_keys(), vec!["colour".to_string()]);
+    }
+
+    #[test]
+    fn read_skips_validation_so_a_bad_value_can_be_replaced() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(
+            &path,
+            "timezone = \"Mars/Olympus\"\ncolour = \"red\"\n\n[google_tasks]\nlist = \"work\"\n",
+        )
+        .unwrap();
+        assert!(ConfigFile::load(&path).is_err());
+
+        let mut file = ConfigFile::read(&path).unwrap();
+        assert_eq!(
+            file.unknown_keys(),
+            vec!["colour".to_string(), "google_tasks.list".to_string()]
+        );
+        file.set(SettingKey::Timezone, "UTC").unwrap();
+        file.save(&path).unwrap();
+
+        let file = ConfigFile::load(&path).unwrap();
+        assert_eq!(file.get(SettingKey::Timezone), Some("UTC"));
+        assert_eq!(file.unknown_keys().len(), 2);
+    }
+}
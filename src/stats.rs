@@ -0,0 +1,413 @@
+//! Completion statistics over the TODO store.
+//!
+//! Statistics are derived from the bookkeeping each todo carries:
+//! `created_at`, `completed_at`, and `snooze_count`. Todos recorded before
+//! juggler tracked those values are counted where possible (pending, overdue)
+//! and reported separately where a timestamp is needed (age).
+//!
+//! The same `Stats` value backs `juggler stats` (text or JSON) and the TUI
+//! stats screen.
+
+use chrono::{DateTime, Datelike, Days, NaiveDate, Utc};
+use chrono_tz::Tz;
+
+use crate::due::today;
+use crate::store::parse_todo_id;
+use crate::ui::{Todo, format_duration_compact};
+
+/// Number of entries in the "most snoozed" list.
+const MOST_SNOOZED_LIMIT: usize = 3;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatsPeriod {
+    #[default]
+    Day,
+    /// Calendar weeks starting on Monday.
+    Week,
+}
+
+impl StatsPeriod {
+    fn length_days(self) -> u64 {
+        match self {
+            StatsPeriod::Day => 1,
+            StatsPeriod::Week => 7,
+        }
+    }
+
+    /// First day of the period containing `date`.
+    fn start_of(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            StatsPeriod::Day => date,
+            StatsPeriod::Week => date
+                .checked_sub_days(Days::new(date.weekday().num_days_from_monday().into()))
+                .unwrap_or(date),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum StatsFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/// Todos created and completed during one day or week.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct PeriodCount {
+    pub start: NaiveDate,
+    pub created: usize,
+    pub completed: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct OverdueStats {
+    pub count: usize,
+    pub average_secs: Option<i64>,
+    pub max_secs: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct SnoozedTodo {
+    pub id: String,
+    pub title: String,
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct SnoozeStats {
+    /// Snoozes across all todos, pending and done.
+    pub total: u64,
+    /// Todos snoozed at least once.
+    pub snoozed_todos: usize,
+    /// Average snoozes per snoozed todo.
+    pub average_per_snoozed: Option<f64>,
+    pub most_snoozed: Vec<SnoozedTodo>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Stats {
+    pub period: StatsPeriod,
+    /// Oldest period first; the last entry is the current day or week.
+    pub periods: Vec<PeriodCount>,
+    pub pending: usize,
+    pub done: usize,
+    pub average_pending_age_secs: Option<i64>,
+    /// Pending todos without a `created_at`, left out of the average age.
+    pub pending_without_created_at: usize,
+    pub overdue: OverdueStats,
+    pub snoozes: SnoozeStats,
+}
+
+/// Compute statistics for the last `count` periods, ending with the one that
+/// contains `now` in `tz`.
+pub fn compute_stats(
+    todos: &[Todo],
+    period: StatsPeriod,
+    count: usize,
+    now: DateTime<Utc>,
+    tz: Tz,
+) -> Stats {
+    let current = period.start_of(today(now, tz));
+    let periods: Vec<PeriodCount> = (0..count as u64)
+        .rev()
+        .filter_map(|back| current.checked_sub_days(Days::new(back * period.length_days())))
+        .map(|start| {
+            let end = start
+                .checked_add_days(Days::new(period.length_days()))
+                .unwrap_or(NaiveDate::MAX);
+            let in_period = |at: Option<DateTime<Utc>>| {
+                at.is_some_and(|at| {
+                    let date = at.with_timezone(&tz).date_naive();
+                    start <= date && date < end
+                })
+            };
+            PeriodCount {
+                start,
+                created: todos
+                    .iter()
+                    .filter(|todo| in_period(todo.created_at))
+                    .count(),
+                completed: todos
+                    .iter()
+                    .filter(|todo| todo.done && in_period(todo.completed_at))
+                    .count(),
+            }
+        })
+        .collect();
+
+    let pending: Vec<&Todo> = todos.iter().filter(|todo| !todo.done).collect();
+    let ages: Vec<i64> = pending
+        .iter()
+        .filter_map(|todo| todo.created_at)
+        .map(|created_at| (now - created_at).num_seconds())
+        .collect();
+    let overdue_by: Vec<i64> = pending
+        .iter()
+        .filter(|todo| todo.is_overdue(now, tz))
+        .filter_map(|todo| todo.due_date)
+        .map(|due| (now - due.deadline(tz)).num_seconds())
+        .collect();
+
+    let mut snoozed: Vec<&Todo> = todos.iter().filter(|todo| todo.snooze_count > 0).collect();
+    snoozed.sort_by_key(|todo| {
+        (
+            std::cmp::Reverse(todo.snooze_count),
+            todo.todo_id.as_deref().and_then(parse_todo_id),
+        )
+    });
+    let total: u64 = snoozed
+        .iter()
+        .map(|todo| u64::from(todo.snooze_count))
+        .sum();
+
+    Stats {
+        period,
+        periods,
+        pending: pending.len(),
+        done: todos.len() - pending.len(),
+        average_pending_age_secs: average(&ages),
+        pending_without_created_at: pending.len() - ages.len(),
+        overdue: OverdueStats {
+            count: overdue_by.len(),
+            average_secs: average(&overdue_by),
+            max_secs: overdue_by.iter().copied().max(),
+        },
+        snoozes: SnoozeStats {
+            total,
+            snoozed_todos: snoozed.len(),
+            average_per_snoozed: (!snoozed.is_empty()).then(|| total as f64 / snoozed.len() as f64),
+            most_snoozed: snoozed
+                .iter()
+                .take(MOST_SNOOZED_LIMIT)
+                .map(|todo| SnoozedTodo {
+                    id: todo.todo_id.clone().unwrap_or_default(),
+                    title: todo.title.clone(),
+                    count: todo.snooze_count,
+                })
+                .collect(),
+        },
+    }
+}
+
+fn average(values: &[i64]) -> Option<i64> {
+    let count = i64::try_from(values.len())
+        .ok()
+        .filter(|count| *count > 0)?;
+    Some(values.iter().sum::<i64>() / count)
+}
+
+impl Stats {
+    /// Heading for the created/completed table or chart.
+    pub fn periods_heading(&self) -> String {
+        let unit = match self.period {
+            StatsPeriod::Day => "day",
+            StatsPeriod::Week => "week",
+        };
+        format!(
+            "Created vs completed per {unit} (last {})",
+            self.periods.len()
+        )
+    }
+
+    /// Summary lines below the per-period counts.
+    pub fn summary_lines(&self) -> Vec<String> {
+        let mut pending = format!("Pending: {}", self.pending);
+        if let Some(age) = self.average_pending_age_secs {
+            pending.push_str(&format!(
+                ", average age {}",
+                format_duration_compact(chrono::Duration::seconds(age))
+            ));
+        }
+        if self.pending_without_created_at > 0 {
+            pending.push_str(&format!(
+                " ({} without a creation time)",
+                self.pending_without_created_at
+            ));
+        }
+
+        let mut overdue = format!("Overdue: {}", self.overdue.count);
+        if let (Some(average), Some(max)) = (self.overdue.average_secs, self.overdue.max_secs) {
+            overdue.push_str(&format!(
+                ", by {} on average, {} at most",
+                format_duration_compact(chrono::Duration::seconds(average)),
+                format_duration_compact(chrono::Duration::seconds(max))
+            ));
+        }
+
+        let mut snoozes = format!(
+            "Snoozes: {} across {} todo(s)",
+            self.snoozes.total, self.snoozes.snoozed_todos
+        );
+        if let Some(average) = self.snoozes.average_per_snoozed {
+            snoozes.push_str(&format!(", {average:.1} per snoozed todo"));
+        }
+
+        let mut lines = vec![pending, format!("Done: {}", self.done), overdue, snoozes];
+        if !self.snoozes.most_snoozed.is_empty() {
+            let most: Vec<String> = self
+                .snoozes
+                .most_snoozed
+                .iter()
+                .map(|todo| format!("{} {} ({})", todo.id, todo.title, todo.count))
+                .collect();
+            lines.push(format!("Most snoozed: {}", most.join(", ")));
+        }
+        lines
+    }
+}
+
+/// Render `stats` as plain text or pretty-printed JSON.
+pub fn render_stats(stats: &Stats, format: StatsFormat) -> crate::error::Result<String> {
+    match format {
+        StatsFormat::Json => Ok(serde_json::to_string_pretty(stats)?),
+        StatsFormat::Text => {
+            let mut lines = vec![
+                stats.periods_heading(),
+                format!("  {:<10}  {:>7}  {:>9}", "START", "CREATED", "COMPLETED"),
+            ];
+            for period in &stats.periods {
+                lines.push(format!(
+                    "  {:<10}  {:>7}  {:>9}",
+                    period.start.to_string(),
+                    period.created,
+                    period.completed
+                ));
+            }
+            lines.push(String::new());
+            lines.extend(stats.summary_lines());
+            Ok(lines.join("\n"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::due::Due;
+    use crate::store::TodoItem;
+    use chrono::{Duration, TimeZone};
+    use indexmap::IndexMap;
+
+    fn todo(id: &str, created_days_ago: Option<i64>) -> Todo {
+        Todo::from(TodoItem {
+            todo_id: Some(id.to_string()),
+            title: id.to_lowercase(),
+            comment: None,
+            done: false,
+            due_date: None,
+            google_task_id: None,
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: created_days_ago.map(|days| now() - Duration::days(days)),
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra: toml::Table::new(),
+        })
+    }
+
+    fn now() -> DateTime<Utc> {
+        // A Wednesday.
+        Utc.with_ymd_and_hms(2025, 1, 8, 12, 0, 0).unwrap()
+    }
+
+    fn fixture() -> Vec<Todo> {
+        let mut finished = todo("T1", Some(3));
+        finished.done = true;
+        finished.completed_at = Some(now() - Duration::days(1));
+        finished.snooze_count = 1;
+
+        let mut overdue = todo("T2", Some(4));
+        overdue.due_date = Some(Due::At(now() - Duration::hours(6)));
+        overdue.snooze_count = 4;
+
+        let mut very_overdue = todo("T3", None);
+        very_overdue.due_date = Some(Due::At(now() - Duration::days(2)));
+
+        vec![finished, overdue, very_overdue, todo("T4", Some(0))]
+    }
+
+    #[test]
+    fn compute_stats_counts_per_day_ages_overdue_and_snoozes() {
+        let stats = compute_stats(&fixture(), StatsPeriod::Day, 5, now(), Tz::UTC);
+
+        let counts: Vec<(String, usize, usize)> = stats
+            .periods
+            .iter()
+            .map(|period| (period.start.to_string(), period.created, period.completed))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![
+                ("2025-01-04".to_string(), 1, 0),
+                ("2025-01-05".to_string(), 1, 0),
+                ("2025-01-06".to_string(), 0, 0),
+                ("2025-01-07".to_string(), 0, 1),
+                ("2025-01-08".to_string(), 1, 0),
+            ]
+        );
+        assert_eq!(stats.pending, 3);
+        assert_eq!(stats.done, 1);
+        assert_eq!(stats.average_pending_age_secs, Some(2 * 86_400));
+        assert_eq!(stats.pending_without_created_at, 1);
+        assert_eq!(
+            stats.overdue,
+            OverdueStats {
+                count: 2,
+                average_secs: Some(27 * 3_600),
+                max_secs: Some(2 * 86_400),
+            }
+        );
+        assert_eq!(stats.snoozes.total, 5);
+        assert_eq!(stats.snoozes.snoozed_todos, 2);
+        assert_eq!(stats.snoozes.most_snoozed[0].id, "T2");
+    }
+
+    #[test]
+    fn compute_stats_groups_weeks_from_monday() {
+        let stats = compute_stats(&fixture(), StatsPeriod::Week, 2, now(), Tz::UTC);
+
+        assert_eq!(
+            stats.periods,
+            vec![
+                PeriodCount {
+                    start: NaiveDate::from_ymd_opt(2024, 12, 30).unwrap(),
+                    created: 2,
+                    completed: 0,
+                },
+                PeriodCount {
+                    start: NaiveDate::from_ymd_opt(2025, 1, 6).unwrap(),
+                    created: 1,
+                    completed: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn render_stats_prints_table_and_summary() {
+        let stats = compute_stats(&fixture(), StatsPeriod::Day, 2, now(), Tz::UTC);
+
+        assert_eq!(
+            render_stats(&stats, StatsFormat::Text).unwrap(),
+            "Created vs completed per day (last 2)\n\
+             \x20 START       CREATED  COMPLETED\n\
+             \x20 2025-01-07        0          1\n\
+             \x20 2025-01-08        1          0\n\
+             \n\
+             Pending: 3, average age 2d (1 without a creation time)\n\
+             Done: 1\n\
+             Overdue: 2, by 1d on average, 2d at most\n\
+             Snoozes: 5 across 2 todo(s), 2.5 per snoozed todo\n\
+             Most snoozed: T2 t2 (4), T1 t1 (1)"
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&render_stats(&stats, StatsFormat::Json).unwrap()).unwrap();
+        assert_eq!(json["period"], "day");
+        assert_eq!(json["periods"][1]["created"], 1);
+        assert_eq!(json["overdue"]["max_secs"], 2 * 86_400);
+    }
+}
@@ -5,23 +5,32 @@
 //!
 //! Save paths use temporary files, atomic replacement, and timestamped archive
 //! copies of previous files. It also handles directory creation and Unix
-//! permission setup for the local data file.
+//! permission setup for the local data file, and runs the `pre-save` and
+//! `post-save` hooks around each write.
 
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
-use std::{collections::HashSet, fs, io::Write};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::Write,
+};
 
 use tempfile::NamedTempFile;
 
 use chrono::{DateTime, Utc};
 use indexmap::IndexMap;
+use log::warn;
 
+use crate::due::Due;
 use crate::error::{JugglerError, Result};
+use crate::fields::{CUSTOM_FIELDS_TABLE, FieldSchema, FieldValue, fields_to_toml};
+use crate::hooks::{HookEvent, Hooks};
 use crate::time::{Clock, SharedClock, system_clock};
 use crate::ui::Todo;
 
-const FORMAT_VERSION_CURRENT: u32 = 1;
-const JUGGLER_EDITION_CURRENT: u32 = 1;
+pub(crate) const FORMAT_VERSION_CURRENT: u32 = 1;
+pub(crate) const JUGGLER_EDITION_CURRENT: u32 = 1;
 
 /// Storage-facing todo representation used for disk format transforms.
 ///
@@ -33,8 +42,22 @@ pub struct TodoItem {
     pub title: String,
     pub comment: Option<String>,
     pub done: bool,
-    pub due_date: Option<DateTime<Utc>>,
+    pub due_date: Option<Due>,
     pub google_task_id: Option<String>,
+    pub blocked_by: Vec<String>,
+    pub log: Vec<LogEntry>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub snooze_count: u32,
+    pub fields: IndexMap<String, FieldValue>,
+    pub extra: toml::Table,
+}
+
+/// One append-only progress note on a todo, stored under `[[todos.TN.log]]`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct LogEntry {
+    pub at: DateTime<Utc>,
+    pub text: String,
 }
 
 /// Version gate for persisted TODO files.
@@ -59,7 +82,9 @@ impl Metadata {
 /// Serialized todo payload stored under `[todos.TN]` in TOML.
 ///
 /// Optional fields are omitted when absent to keep files concise and avoid
-/// introducing sentinel/null encodings in TOML.
+/// introducing sentinel/null encodings in TOML. Keys this release does not
+/// know about are captured in `extra` and written back unchanged, so files
+/// written by newer releases or annotated by other tools survive a save.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 struct TodoRecord {
     title: String,
@@ -71,14 +96,37 @@ struct TodoRecord {
     due_date: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     google_task_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    blocked_by: Vec<String>,
+    #[serde(default, skip_serializing_if = "toml::Table::is_empty")]
+    fields: toml::Table,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    log: Vec<LogEntry>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    created_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    completed_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    snooze_count: u32,
+    #[serde(flatten)]
+    extra: toml::Table,
+}
+
+fn is_zero(value: &u32) -> bool {
+    *value == 0
 }
 
 /// Full TOML document shape for the TODO store.
+///
+/// Unknown top-level tables are kept in `extra` and re-emitted on save. The
+/// `[custom_fields]` schema also travels there, since the store only reads it.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 struct TodosFile {
     metadata: Metadata,
     #[serde(default)]
     todos: IndexMap<String, TodoRecord>,
+    #[serde(flatten)]
+    extra: toml::Table,
 }
 
 impl From<&Todo> for TodoItem {
@@ -90,6 +138,13 @@ impl From<&Todo> for TodoItem {
             done: todo.done,
             due_date: todo.due_date,
             google_task_id: todo.google_task_id.clone(),
+            blocked_by: todo.blocked_by.clone(),
+            log: todo.log.clone(),
+            created_at: todo.created_at,
+            completed_at: todo.completed_at,
+            snooze_count: todo.snooze_count,
+            fields: todo.fields.clone(),
+            extra: todo.extra.clone(),
         }
     }
 }
@@ -100,23 +155,95 @@ impl From<&TodoItem> for TodoRecord {
             title: todo.title.clone(),
             comment: todo.comment.clone(),
             done: todo.done,
-            due_date: todo.due_date.map(|date| date.to_rfc3339()),
+            due_date: todo.due_date.map(|due| due.to_storage_string()),
             google_task_id: todo.google_task_id.clone(),
+            blocked_by: todo.blocked_by.clone(),
+            log: todo.log.clone(),
+            created_at: todo.created_at,
+            completed_at: todo.completed_at,
+            snooze_count: todo.snooze_count,
+            fields: fields_to_toml(&todo.fields),
+            extra: todo.extra.clone(),
         }
     }
 }
 
 /// Load todos from canonical TOML.
 pub fn load_todos<P: AsRef<std::path::Path>>(file_path: P) -> Result<Vec<Todo>> {
+    load_todos_and_schema(file_path).map(|(todos, _)| todos)
+}
+
+/// Just the keys `load_pending_dues` reads; serde skips the rest of each record.
+#[derive(serde::Deserialize)]
+struct DueRecord {
+    #[serde(default)]
+    done: bool,
+    #[serde(default)]
+    due_date: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct DuesFile {
+    metadata: Metadata,
+    #[serde(default)]
+    todos: HashMap<String, DueRecord>,
+}
+
+/// Last modification time of the store, or `None` while it does not exist.
+/// Watchers poll this to notice saves from any process.
+pub fn modified_time(path: &std::path::Path) -> Option<std::time::SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+/// Fast path for `juggler status`: the due of every pending todo, without
+/// building todos. Skips the flattened unknown-key capture, custom field,
+/// dependency, and log handling that dominate `load_todos` on large files.
+pub fn load_pending_dues<P: AsRef<std::path::Path>>(file_path: P) -> Result<Vec<Option<Due>>> {
+    let content = match fs::read_to_string(file_path.as_ref()) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let file: DuesFile = toml::from_str(&content)?;
+    validate_metadata(&file.metadata).map_err(invalid_store)?;
+    file.todos
+        .into_values()
+        .filter(|record| !record.done)
+        .map(|record| record.due_date.as_deref().map(parse_due_date).transpose())
+        .collect::<Result<_>>()
+        .map_err(invalid_store)
+}
+
+/// Load todos together with the custom field schema they were validated against.
+pub fn load_todos_and_schema<P: AsRef<std::path::Path>>(
+    file_path: P,
+) -> Result<(Vec<Todo>, FieldSchema)> {
     let file_path = file_path.as_ref();
     let content = match fs::read_to_string(file_path) {
         Ok(content) => content,
-        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Ok((Vec::new(), FieldSchema::default()));
+        }
         Err(e) => return Err(e.into()),
     };
 
-    let file: TodosFile = toml::from_str(&content)?;
+    parse_todos_file(&content).map_err(invalid_store)
+}
+
+/// A store that breaks the format's rules is bad data, not bad input, even
+/// though the checks are shared with validating edits.
+fn invalid_store(error: JugglerError) -> JugglerError {
+    match error {
+        JugglerError::Config(message) => JugglerError::Store(message),
+        other => other,
+    }
+}
+
+fn parse_todos_file(content: &str) -> Result<(Vec<Todo>, FieldSchema)> {
+    let file: TodosFile = toml::from_str(content)?;
     validate_metadata(&file.metadata)?;
+    let field_schema = FieldSchema::from_toml(file.extra.get(CUSTOM_FIELDS_TABLE))?;
 
     let mut parsed_items: Vec<(u64, TodoItem)> = Vec::with_capacity(file.todos.len());
     for (todo_id, record) in file.todos {
@@ -131,6 +258,15 @@ pub fn load_todos<P: AsRef<std::path::Path>>(file_path: P) -> Result<Vec<Todo>>
             None => None,
         };
 
+        let fields = field_schema
+            .validate_table(&record.fields)
+            .map_err(|e| match e {
+                JugglerError::Config(message) => {
+                    JugglerError::config(format!("Todo '{todo_id}': {message}"))
+                }
+                other => other,
+            })?;
+
         parsed_items.push((
             number,
             TodoItem {
@@ -140,17 +276,95 @@ pub fn load_todos<P: AsRef<std::path::Path>>(file_path: P) -> Result<Vec<Todo>>
                 done: record.done,
                 due_date,
                 google_task_id: record.google_task_id,
+                blocked_by: record.blocked_by,
+                log: record.log,
+                created_at: record.created_at,
+                completed_at: record.completed_at,
+                snooze_count: record.snooze_count,
+                fields,
+                extra: record.extra,
             },
         ));
     }
 
     parsed_items.sort_by_key(|(number, _)| *number);
+    validate_blocked_by(parsed_items.iter().map(|(_, item)| {
+        (
+            item.todo_id.as_deref().unwrap_or_default(),
+            item.blocked_by.as_slice(),
+        )
+    }))?;
+
     let todos: Vec<Todo> = parsed_items
         .into_iter()
         .map(|(_, item)| item.into())
         .collect();
 
-    Ok(todos)
+    Ok((todos, field_schema))
+}
+
+/// Check `blocked_by` references between todos given as `(todo_id, blocked_by)`.
+///
+/// Every referenced id must name a todo in the same list, and following the
+/// references must never lead back to where it started.
+pub(crate) fn validate_blocked_by<'a, I>(todos: I) -> Result<()>
+where
+    I: IntoIterator<Item = (&'a str, &'a [String])>,
+{
+    let graph: IndexMap<&str, &[String]> = todos.into_iter().collect();
+
+    for (todo_id, blocked_by) in &graph {
+        if let Some(missing) = blocked_by
+            .iter()
+            .find(|blocker| !graph.contains_key(blocker.as_str()))
+        {
+            return Err(JugglerError::config(format!(
+                "Todo '{todo_id}' is blocked by unknown todo '{missing}'"
+            )));
+        }
+    }
+
+    let mut state = HashMap::new();
+    for todo_id in graph.keys() {
+        visit_blockers(todo_id, &graph, &mut state, &mut Vec::new())?;
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Visit {
+    InProgress,
+    Finished,
+}
+
+fn visit_blockers<'a>(
+    todo_id: &'a str,
+    graph: &IndexMap<&'a str, &'a [String]>,
+    state: &mut HashMap<&'a str, Visit>,
+    path: &mut Vec<&'a str>,
+) -> Result<()> {
+    match state.get(todo_id) {
+        Some(Visit::Finished) => return Ok(()),
+        Some(Visit::InProgress) => {
+            let start = path.iter().position(|id| *id == todo_id).unwrap_or(0);
+            let mut cycle = path[start..].to_vec();
+            cycle.push(todo_id);
+            return Err(JugglerError::config(format!(
+                "Dependency cycle between todos: {}",
+                cycle.join(" -> ")
+            )));
+        }
+        None => {}
+    }
+
+    state.insert(todo_id, Visit::InProgress);
+    path.push(todo_id);
+    for blocker in graph.get(todo_id).copied().unwrap_or_default() {
+        visit_blockers(blocker, graph, state, path)?;
+    }
+    path.pop();
+    state.insert(todo_id, Visit::Finished);
+    Ok(())
 }
 
 /// Persist todos atomically, assigning missing stable IDs before serialization.
@@ -163,25 +377,28 @@ pub fn store_todos_with_clock<P: AsRef<std::path::Path>>(
     clock: SharedClock,
 ) -> Result<()> {
     let file_path = file_path.as_ref();
+    ensure_parent_dir(file_path)?;
+
+    // Top-level tables are not part of the todo list, so carry forward any
+    // unknown ones from the file being replaced.
+    let extra = if file_path.exists() {
+        read_extra_top_level_tables(file_path)
+    } else {
+        toml::Table::new()
+    };
 
-    if let Some(parent) = file_path.parent()
-        && !parent.exists()
-    {
-        fs::create_dir_all(parent)?;
-        #[cfg(unix)]
-        {
-            let mut perms = fs::metadata(parent)?.permissions();
-            perms.set_mode(0o700);
-            fs::set_permissions(parent, perms)?;
-        }
-    }
+    assign_missing_todo_ids(todos)?;
+
+    // The pre-save hook may veto the save, so it runs before anything on disk
+    // changes.
+    let hooks = Hooks::for_todos_file(file_path);
+    let field_schema = FieldSchema::from_toml(extra.get(CUSTOM_FIELDS_TABLE)).unwrap_or_default();
+    hooks.filter(HookEvent::PreSave, todos, &field_schema)?;
 
     if file_path.exists() {
         archive_todos_file(file_path, clock.as_ref())?;
     }
 
-    assign_missing_todo_ids(todos)?;
-
     let mut todo_items: Vec<TodoItem> = todos.iter().map(TodoItem::from).collect();
 
     // Store in numeric todo-id order for stable user-visible IDs.
@@ -204,10 +421,21 @@ pub fn store_todos_with_clock<P: AsRef<std::path::Path>>(
     let file = TodosFile {
         metadata: Metadata::current(),
         todos: todo_map,
+        extra,
     };
 
     let toml_content = toml::to_string_pretty(&file)?;
 
+    write_file_atomically(file_path, &toml_content)?;
+    if let Err(e) = hooks.notify(HookEvent::PostSave, todos) {
+        warn!("{e}");
+    }
+    Ok(())
+}
+
+/// Replace `file_path` with `content` via a synced temporary file and rename,
+/// so readers observe either the old or the new file but never a partial one.
+pub(crate) fn write_file_atomically(file_path: &std::path::Path, content: &str) -> Result<()> {
     let target_dir = file_path
         .parent()
         .unwrap_or_else(|| std::path::Path::new("."));
@@ -215,7 +443,7 @@ pub fn store_todos_with_clock<P: AsRef<std::path::Path>>(
 
     {
         let file = temp_file.as_file_mut();
-        file.write_all(toml_content.as_bytes())?;
+        file.write_all(content.as_bytes())?;
 
         file.flush()?;
         file.sync_all()?;
@@ -235,11 +463,96 @@ pub fn store_todos_with_clock<P: AsRef<std::path::Path>>(
     Ok(())
 }
 
+/// Exclusive lock on a TODO store, held for one load-modify-save cycle so
+/// concurrent juggler processes (CLI commands, `serve`) cannot lose each
+/// other's writes. Released when dropped.
+#[derive(Debug)]
+pub struct StoreLock {
+    _file: fs::File,
+}
+
+/// Take the store lock for `todos_file`, waiting up to `STORE_LOCK_TIMEOUT`
+/// for another process to release it.
+///
+/// The lock lives in a sibling `<file>.lock` file because the store itself is
+/// replaced by rename on every save. Locks are per open file, so a process
+/// must not take the lock again while it already holds it.
+pub fn lock_store(todos_file: &std::path::Path) -> Result<StoreLock> {
+    lock_store_with_timeout(todos_file, STORE_LOCK_TIMEOUT)
+}
+
+fn lock_store_with_timeout(
+    todos_file: &std::path::Path,
+    timeout: std::time::Duration,
+) -> Result<StoreLock> {
+    ensure_parent_dir(todos_file)?;
+    let mut lock_path = todos_file.as_os_str().to_owned();
+    lock_path.push(".lock");
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)?;
+
+    let deadline = std::time::Instant::now() + timeout;
+    loop {
+        match file.try_lock() {
+            Ok(()) => return Ok(StoreLock { _file: file }),
+            Err(fs::TryLockError::WouldBlock) if std::time::Instant::now() < deadline => {
+                std::thread::sleep(STORE_LOCK_RETRY_DELAY);
+            }
+            Err(fs::TryLockError::WouldBlock) => {
+                return Err(JugglerError::locked(format!(
+                    "TODO store {} is locked by another juggler process",
+                    todos_file.display()
+                )));
+            }
+            Err(fs::TryLockError::Error(e)) => return Err(e.into()),
+        }
+    }
+}
+
+const STORE_LOCK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+const STORE_LOCK_RETRY_DELAY: std::time::Duration = std::time::Duration::from_millis(25);
+
+/// Create the data directory for `file_path` (private on Unix) if missing.
+pub(crate) fn ensure_parent_dir(file_path: &std::path::Path) -> Result<()> {
+    if let Some(parent) = file_path.parent()
+        && !parent.as_os_str().is_empty()
+        && !parent.exists()
+    {
+        fs::create_dir_all(parent)?;
+        #[cfg(unix)]
+        {
+            let mut perms = fs::metadata(parent)?.permissions();
+            perms.set_mode(0o700);
+            fs::set_permissions(parent, perms)?;
+        }
+    }
+    Ok(())
+}
+
 /// Save todos to disk, mutating input to assign missing stable ids as needed.
 pub fn store_todos<P: AsRef<std::path::Path>>(todos: &mut [Todo], file_path: P) -> Result<()> {
     store_todos_with_clock(todos, file_path, system_clock())
 }
 
+/// Best-effort read of unknown top-level tables from an existing store.
+///
+/// An unreadable or unparseable previous file has nothing worth preserving, and
+/// must not block saving the in-memory list over it.
+fn read_extra_top_level_tables(file_path: &std::path::Path) -> toml::Table {
+    fs::read_to_string(file_path)
+        .ok()
+        .and_then(|content| toml::from_str::<toml::Table>(&content).ok())
+        .map(|mut document| {
+            document.remove("metadata");
+            document.remove("todos");
+            document
+        })
+        .unwrap_or_default()
+}
+
 /// Enforce strict version gating so unsupported files fail fast.
 fn validate_metadata(metadata: &Metadata) -> Result<()> {
     if metadata.format_version != FORMAT_VERSION_CURRENT {
@@ -261,7 +574,7 @@ fn validate_metadata(metadata: &Metadata) -> Result<()> {
 
 /// Ensure every todo has a unique `T<N>` ID, preserving existing IDs and
 /// allocating new IDs monotonically from the current maximum.
-fn assign_missing_todo_ids(todos: &mut [Todo]) -> Result<()> {
+pub(crate) fn assign_missing_todo_ids(todos: &mut [Todo]) -> Result<()> {
     let mut used_ids: HashSet<u64> = HashSet::new();
     let mut max_seen = 0u64;
 
@@ -297,7 +610,7 @@ fn assign_missing_todo_ids(todos: &mut [Todo]) -> Result<()> {
 }
 
 /// Parse storage key format `T<N>` where `N` is a non-zero positive integer.
-fn parse_todo_id(input: &str) -> Option<u64> {
+pub(crate) fn parse_todo_id(input: &str) -> Option<u64> {
     let mut chars = input.chars();
     if chars.next()? != 'T' {
         return None;
@@ -315,18 +628,21 @@ fn parse_todo_id(input: &str) -> Option<u64> {
     numeric.parse::<u64>().ok()
 }
 
-fn format_todo_id(number: u64) -> String {
+pub(crate) fn format_todo_id(number: u64) -> String {
     format!("T{number}")
 }
 
 /// Parse due date strings from persisted/editor TOML as RFC3339 timestamps.
-pub(crate) fn parse_due_date(input: &str) -> Result<DateTime<Utc>> {
-    DateTime::parse_from_rfc3339(input)
-        .map(|dt| dt.with_timezone(&Utc))
-        .map_err(|e| JugglerError::config(format!("Invalid due_date value '{}': {}", input, e)))
+pub(crate) fn parse_due_date(input: &str) -> Result<Due> {
+    Due::parse(input)
 }
 
-fn archive_todos_file(file_path: &std::path::Path, clock: &dyn Clock) -> Result<()> {
+/// Copy the current TODO file to a fresh timestamped archive next to it and
+/// return the archive path.
+pub(crate) fn archive_todos_file(
+    file_path: &std::path::Path,
+    clock: &dyn Clock,
+) -> Result<std::path::PathBuf> {
     let parent = file_path
         .parent()
         .ok_or_else(|| JugglerError::Other("File path has no parent directory".to_string()))?;
@@ -359,8 +675,8 @@ fn archive_todos_file(file_path: &std::path::Path, clock: &dyn Clock) -> Result<
         }
     };
 
-    fs::copy(file_path, archive_path)?;
-    Ok(())
+    fs::copy(file_path, &archive_path)?;
+    Ok(archive_path)
 }
 
 #[cfg(test)]
@@ -378,6 +694,13 @@ mod tests {
             due_date: None,
             todo_id: None,
             google_task_id: None,
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra: toml::Table::new(),
         }
     }
 
@@ -443,6 +766,43 @@ done = true
         }
     }
 
+    #[test]
+    fn load_pending_dues_reads_only_pending_dues() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().expect("create temp dir");
+        let test_file = temp_dir.path().join("TODOs.toml");
+        assert!(load_pending_dues(&test_file).unwrap().is_empty());
+
+        fs::write(
+            &test_file,
+            r#"[metadata]
+format_version = 1
+juggler_edition = 1
+
+[todos.T1]
+title = "dated"
+due_date = "2025-01-07"
+
+[todos.T2]
+title = "undated"
+
+[todos.T3]
+title = "finished"
+done = true
+due_date = "2025-01-01"
+"#,
+        )
+        .expect("write fixture");
+
+        let dues = load_pending_dues(&test_file).unwrap();
+        assert_eq!(dues.len(), 2);
+        assert!(dues.contains(&None));
+        assert!(dues.contains(&Some(Due::Date(
+            chrono::NaiveDate::from_ymd_opt(2025, 1, 7).unwrap()
+        ))));
+    }
+
     #[test]
     fn load_todos_missing_file_returns_empty() {
         use tempfile::TempDir;
@@ -560,6 +920,13 @@ done = false
                 due_date: None,
                 todo_id: Some("T7".to_string()),
                 google_task_id: None,
+                blocked_by: Vec::new(),
+                log: Vec::new(),
+                created_at: None,
+                completed_at: None,
+                snooze_count: 0,
+                fields: IndexMap::new(),
+                extra: toml::Table::new(),
             },
             Todo {
                 title: "Test todo 2".to_string(),
@@ -567,13 +934,16 @@ done = false
                 expanded: false,
                 done: true,
                 selected: false,
-                due_date: Some(
-                    chrono::DateTime::parse_from_rfc3339("2025-01-01T00:00:00Z")
-                        .unwrap()
-                        .with_timezone(&Utc),
-                ),
+                due_date: Some(Due::parse("2025-01-01T00:00:00Z").unwrap()),
                 todo_id: None,
                 google_task_id: Some("google_task_123".to_string()),
+                blocked_by: Vec::new(),
+                log: Vec::new(),
+                created_at: None,
+                completed_at: None,
+                snooze_count: 0,
+                fields: IndexMap::new(),
+                extra: toml::Table::new(),
             },
         ];
 
@@ -614,7 +984,7 @@ done = false
 
         let fixed_now = chrono::DateTime::parse_from_rfc3339("2025-01-01T00:00:00Z")
             .unwrap()
-            .with_timezone(&Utc);
+            .with_timezone(&chrono::Utc);
         let clock = fixed_clock(fixed_now);
 
         store_todos_with_clock(&mut initial_todos, &test_file, clock.clone())
@@ -651,7 +1021,7 @@ done = false
 
         let fixed_now = chrono::DateTime::parse_from_rfc3339("2025-01-01T00:00:00Z")
             .unwrap()
-            .with_timezone(&Utc);
+            .with_timezone(&chrono::Utc);
         let clock = fixed_clock(fixed_now);
 
         store_todos_with_clock(&mut todo, &test_file, clock.clone()).expect("store 1");
@@ -735,6 +1105,13 @@ done = false
                 due_date: None,
                 todo_id: Some("T10".to_string()),
                 google_task_id: Some("id_3".to_string()),
+                blocked_by: Vec::new(),
+                log: Vec::new(),
+                created_at: None,
+                completed_at: None,
+                snooze_count: 0,
+                fields: IndexMap::new(),
+                extra: toml::Table::new(),
             },
             Todo {
                 title: "Apple".to_string(),
@@ -745,6 +1122,13 @@ done = false
                 due_date: None,
                 todo_id: Some("T2".to_string()),
                 google_task_id: None,
+                blocked_by: Vec::new(),
+                log: Vec::new(),
+                created_at: None,
+                completed_at: None,
+                snooze_count: 0,
+                fields: IndexMap::new(),
+                extra: toml::Table::new(),
             },
             Todo {
                 title: "Banana".to_string(),
@@ -755,6 +1139,13 @@ done = false
                 due_date: None,
                 todo_id: Some("T1".to_string()),
                 google_task_id: Some("id_1".to_string()),
+                blocked_by: Vec::new(),
+                log: Vec::new(),
+                created_at: None,
+                completed_at: None,
+                snooze_count: 0,
+                fields: IndexMap::new(),
+                extra: toml::Table::new(),
             },
         ];
 
@@ -783,6 +1174,13 @@ done = false
                 due_date: None,
                 todo_id: Some("T4".to_string()),
                 google_task_id: None,
+                blocked_by: Vec::new(),
+                log: Vec::new(),
+                created_at: None,
+                completed_at: None,
+                snooze_count: 0,
+                fields: IndexMap::new(),
+                extra: toml::Table::new(),
             },
             make_todo("New one"),
             Todo {
@@ -794,6 +1192,13 @@ done = false
                 due_date: None,
                 todo_id: Some("T9".to_string()),
                 google_task_id: None,
+                blocked_by: Vec::new(),
+                log: Vec::new(),
+                created_at: None,
+                completed_at: None,
+                snooze_count: 0,
+                fields: IndexMap::new(),
+                extra: toml::Table::new(),
             },
             make_todo("New two"),
         ];
@@ -823,6 +1228,13 @@ done = false
                 due_date: None,
                 todo_id: Some("T1".to_string()),
                 google_task_id: None,
+                blocked_by: Vec::new(),
+                log: Vec::new(),
+                created_at: None,
+                completed_at: None,
+                snooze_count: 0,
+                fields: IndexMap::new(),
+                extra: toml::Table::new(),
             },
             Todo {
                 title: "B".to_string(),
@@ -833,6 +1245,13 @@ done = false
                 due_date: None,
                 todo_id: Some("T1".to_string()),
                 google_task_id: None,
+                blocked_by: Vec::new(),
+                log: Vec::new(),
+                created_at: None,
+                completed_at: None,
+                snooze_count: 0,
+                fields: IndexMap::new(),
+                extra: toml::Table::new(),
             },
         ];
 
@@ -856,6 +1275,13 @@ done = false
             due_date: None,
             todo_id: Some("T01".to_string()),
             google_task_id: None,
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra: toml::Table::new(),
         }];
 
         let err = store_todos(&mut todos, &test_file).expect_err("invalid IDs should fail");
@@ -876,9 +1302,224 @@ done = false
         assert!(!content.contains("comment ="));
         assert!(!content.contains("due_date ="));
         assert!(!content.contains("google_task_id ="));
+        assert!(!content.contains("snooze_count ="));
         assert!(content.contains("done = false"));
     }
 
+    #[test]
+    fn store_todos_preserves_unknown_record_keys_and_top_level_tables() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().expect("create temp dir");
+        let test_file = temp_dir.path().join("TODOs.toml");
+        fs::write(
+            &test_file,
+            r#"[metadata]
+format_version = 1
+juggler_edition = 1
+
+[other_tool]
+synced_at = 2025-01-01T00:00:00Z
+
+[todos.T1]
+title = "Annotated"
+done = false
+priority = 3
+
+[todos.T1.external]
+url = "https://example.com/1"
+"#,
+        )
+        .expect("write fixture");
+
+        let mut todos = load_todos(&test_file).expect("load todos");
+        assert_eq!(
+            todos[0].extra.get("priority"),
+            Some(&toml::Value::Integer(3))
+        );
+
+        todos[0].title = "Annotated and edited".to_string();
+        store_todos(&mut todos, &test_file).expect("store todos");
+
+        let document: toml::Table =
+            toml::from_str(&fs::read_to_string(&test_file).expect("read stored TOML"))
+                .expect("parse stored TOML");
+        let record = document["todos"]["T1"].as_table().expect("T1 table");
+        assert_eq!(record["title"].as_str(), Some("Annotated and edited"));
+        assert_eq!(record["priority"].as_integer(), Some(3));
+        assert_eq!(
+            record["external"]["url"].as_str(),
+            Some("https://example.com/1")
+        );
+        assert!(document["other_tool"]["synced_at"].as_datetime().is_some());
+    }
+
+    #[test]
+    fn load_todos_rejects_dangling_and_cyclic_blocked_by() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().expect("create temp dir");
+        let test_file = temp_dir.path().join("TODOs.toml");
+        let header = "[metadata]\nformat_version = 1\njuggler_edition = 1\n";
+
+        fs::write(
+            &test_file,
+            format!("{header}\n[todos.T1]\ntitle = \"a\"\n\n[todos.T2]\ntitle = \"b\"\nblocked_by = [\"T1\"]\n"),
+        )
+        .expect("write fixture");
+        let todos = load_todos(&test_file).expect("valid dependencies load");
+        assert_eq!(todos[1].blocked_by, vec!["T1".to_string()]);
+
+        fs::write(
+            &test_file,
+            format!("{header}\n[todos.T1]\ntitle = \"a\"\nblocked_by = [\"T9\"]\n"),
+        )
+        .expect("write fixture");
+        let err = load_todos(&test_file).expect_err("dangling reference should fail");
+        assert!(err.to_string().contains("unknown todo 'T9'"), "{err}");
+
+        fs::write(
+            &test_file,
+            format!("{header}\n[todos.T1]\ntitle = \"a\"\nblocked_by = [\"T1\"]\n"),
+        )
+        .expect("write fixture");
+        let err = load_todos(&test_file).expect_err("self-dependency should fail");
+        assert!(err.to_string().contains("T1 -> T1"), "{err}");
+    }
+
+    #[test]
+    fn load_todos_validates_custom_fields_against_schema() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().expect("create temp dir");
+        let test_file = temp_dir.path().join("TODOs.toml");
+        let header = r#"[metadata]
+format_version = 1
+juggler_edition = 1
+
+[custom_fields.ticket]
+type = "string"
+
+[custom_fields.estimate]
+type = "number"
+"#;
+        fs::write(
+            &test_file,
+            format!("{header}\n[todos.T1]\ntitle = \"a\"\n\n[todos.T1.fields]\nestimate = 2.5\nticket = \"OPS-1\"\n"),
+        )
+        .expect("write fixture");
+
+        let (mut todos, schema) = load_todos_and_schema(&test_file).expect("load todos");
+        assert_eq!(schema.iter().count(), 2);
+        assert_eq!(
+            todos[0].fields.get("estimate"),
+            Some(&FieldValue::Number(2.5))
+        );
+
+        store_todos(&mut todos, &test_file).expect("store todos");
+        let (reloaded, _) = load_todos_and_schema(&test_file).expect("reload todos");
+        assert_eq!(reloaded[0].fields, todos[0].fields);
+
+        fs::write(
+            &test_file,
+            format!(
+                "{header}\n[todos.T1]\ntitle = \"a\"\n\n[todos.T1.fields]\nestimate = \"soon\"\n"
+            ),
+        )
+        .expect("write fixture");
+        let err = load_todos(&test_file).expect_err("invalid value should fail");
+        assert!(err.to_string().contains("Todo 'T1'"), "{err}");
+    }
+
+    #[test]
+    fn store_todos_roundtrips_log_entries() {
+        use chrono::TimeZone;
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().expect("create temp dir");
+        let test_file = temp_dir.path().join("TODOs.toml");
+
+        let mut todo = make_todo("Vendor outage");
+        todo.log = vec![
+            LogEntry {
+                at: Utc.with_ymd_and_hms(2025, 1, 2, 9, 30, 0).unwrap(),
+                text: "Opened ticket".to_string(),
+            },
+            LogEntry {
+                at: Utc.with_ymd_and_hms(2025, 1, 3, 14, 0, 0).unwrap(),
+                text: "Vendor asked for logs".to_string(),
+            },
+        ];
+        let mut todos = vec![todo];
+        store_todos(&mut todos, &test_file).expect("store todos");
+
+        let content = fs::read_to_string(&test_file).expect("read stored TOML");
+        assert!(content.contains("[[todos.T1.log]]"), "{content}");
+
+        let reloaded = load_todos(&test_file).expect("reload todos");
+        assert_eq!(reloaded[0].log, todos[0].log);
+    }
+
+    #[test]
+    fn store_todos_roundtrips_bookkeeping_fields() {
+        use chrono::TimeZone;
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().expect("create temp dir");
+        let test_file = temp_dir.path().join("TODOs.toml");
+
+        let mut todo = make_todo("Renew passport");
+        todo.done = true;
+        todo.created_at = Some(Utc.with_ymd_and_hms(2025, 1, 2, 9, 30, 0).unwrap());
+        todo.completed_at = Some(Utc.with_ymd_and_hms(2025, 1, 9, 17, 0, 0).unwrap());
+        todo.snooze_count = 3;
+        let mut todos = vec![todo];
+        store_todos(&mut todos, &test_file).expect("store todos");
+
+        let content = fs::read_to_string(&test_file).expect("read stored TOML");
+        assert!(content.contains("snooze_count = 3"), "{content}");
+
+        let reloaded = load_todos(&test_file).expect("reload todos");
+        assert_eq!(reloaded[0].created_at, todos[0].created_at);
+        assert_eq!(reloaded[0].completed_at, todos[0].completed_at);
+        assert_eq!(reloaded[0].snooze_count, 3);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn pre_save_hook_veto_leaves_store_and_archives_untouched() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().expect("create temp dir");
+        let test_file = temp_dir.path().join("TODOs.toml");
+        let mut todos = vec![make_todo("Before hook")];
+        store_todos(&mut todos, &test_file).expect("store todos");
+        let before = fs::read_to_string(&test_file).expect("read stored TOML");
+
+        let hooks_dir = temp_dir.path().join(crate::hooks::HOOKS_DIR_NAME);
+        fs::create_dir(&hooks_dir).expect("create hooks dir");
+        let hook = hooks_dir.join("pre-save");
+        fs::write(&hook, "#!/bin/sh\necho 'frozen' >&2\nexit 1\n").expect("write hook");
+        fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)).expect("chmod hook");
+
+        todos[0].title = "After hook".to_string();
+        let err = store_todos(&mut todos, &test_file).expect_err("hook vetoes save");
+        assert!(err.to_string().contains("frozen"), "{err}");
+        assert_eq!(fs::read_to_string(&test_file).unwrap(), before);
+        let archives = fs::read_dir(temp_dir.path())
+            .unwrap()
+            .filter(|entry| {
+                entry
+                    .as_ref()
+                    .unwrap()
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with("TODOs_")
+            })
+            .count();
+        assert_eq!(archives, 0);
+    }
+
     #[test]
     fn store_todos_always_writes_metadata_versions() {
         use tempfile::TempDir;
@@ -894,4 +1535,20 @@ done = false
         assert!(content.contains("format_version = 1"));
         assert!(content.contains("juggler_edition = 1"));
     }
+
+    #[test]
+    fn store_lock_is_exclusive_until_dropped() {
+        let temp_dir = tempfile::TempDir::new().expect("create temp dir");
+        let todos_file = temp_dir.path().join("nested").join("TODOs.toml");
+
+        let held = lock_store(&todos_file).expect("first lock");
+        let contended = lock_store_with_timeout(&todos_file, std::time::Duration::ZERO);
+        assert!(
+            matches!(contended, Err(JugglerError::Locked(ref message)) if message.contains("locked"))
+        );
+
+        drop(held);
+        lock_store_with_timeout(&todos_file, std::time::Duration::ZERO)
+            .expect("lock is free after drop");
+    }
 }
@@ -0,0 +1,215 @@
+//! Headless access to the reducer for scripted, id-addressed changes.
+//!
+//! CLI subcommands such as `juggler done T4` select the named todos in a fresh
+//! `AppModel` and dispatch the same reducer actions the TUI keys do, so the
+//! two entry points cannot drift apart (for example in how overdue todos are
+//! snoozed relative to now).
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+
+use crate::due::Due;
+use crate::error::{JugglerError, Result};
+
+use super::editor::TodoEditor;
+use super::keymap::Action as NormalAction;
+use super::model::{AppModel, Section, TodoItems};
+use super::todo::Todo;
+use super::update::{Action, try_apply_edited_item, update};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchOp {
+    Done,
+    Undone,
+    Snooze { days: i64 },
+    SetDue(Option<Due>),
+}
+
+/// Todos after a batch change, plus the footer message the TUI would have shown.
+#[derive(Debug)]
+pub struct BatchOutcome {
+    pub todos: Vec<Todo>,
+    pub message: Option<String>,
+}
+
+/// Apply `op` to the todos named by `ids`. Completion changes skip todos that
+/// are already in the requested state.
+pub fn apply_by_id(
+    todos: Vec<Todo>,
+    ids: &[String],
+    op: BatchOp,
+    now: DateTime<Utc>,
+    tz: Tz,
+) -> Result<BatchOutcome> {
+    let mut model = headless_model(todos, tz);
+    let mut any_selected = false;
+    for section in [Section::Pending, Section::Done] {
+        let mut index = 0;
+        while let Some(item) = model.items.get_mut(section, index) {
+            let named = item
+                .todo_id
+                .as_ref()
+                .is_some_and(|todo_id| ids.contains(todo_id));
+            item.selected = named
+                && match op {
+                    BatchOp::Done => !item.done,
+                    BatchOp::Undone => item.done,
+                    BatchOp::Snooze { .. } | BatchOp::SetDue(_) => true,
+                };
+            any_selected |= item.selected;
+            index += 1;
+        }
+    }
+
+    if any_selected {
+        let action = match op {
+            BatchOp::Done | BatchOp::Undone => Action::Normal(NormalAction::ToggleDone),
+            BatchOp::Snooze { days } => Action::Snooze { days },
+            BatchOp::SetDue(due) => Action::SetDue(due),
+        };
+        update(&mut model, action, now);
+    }
+
+    Ok(finish(model))
+}
+
+/// Open the todo named `todo_id` in `editor` and apply the result with the
+/// same validation as an edit from the TUI.
+pub fn edit_by_id(
+    todos: Vec<Todo>,
+    todo_id: &str,
+    editor: &dyn TodoEditor,
+    now: DateTime<Utc>,
+    tz: Tz,
+) -> Result<BatchOutcome> {
+    let mut model = headless_model(todos, tz);
+    let (section, index) = [Section::Pending, Section::Done]
+        .into_iter()
+        .find_map(|section| {
+            let mut index = 0;
+            while let Some(item) = model.items.get(section, index) {
+                if item.todo_id.as_deref() == Some(todo_id) {
+                    return Some((section, index));
+                }
+                index += 1;
+            }
+            None
+        })
+        .ok_or_else(|| JugglerError::not_found(format!("Unknown todo id '{todo_id}'")))?;
+
+    let original = model
+        .items
+        .get(section, index)
+        .cloned()
+        .expect("index was just located");
+    let updated_item = editor.edit_todo(&original)?;
+    if updated_item.title.trim().is_empty() {
+        return Err(JugglerError::config("Todo title must not be empty"));
+    }
+    try_apply_edited_item(&mut model, section, index, updated_item, now)?;
+
+    Ok(finish(model))
+}
+
+fn headless_model(todos: Vec<Todo>, tz: Tz) -> AppModel {
+    let mut model = AppModel::new(Vec::new());
+    model.timezone = tz;
+    model.items = TodoItems::new(todos, tz);
+    model
+}
+
+fn finish(model: AppModel) -> BatchOutcome {
+    let mut todos = model.items.to_vec();
+    for todo in &mut todos {
+        todo.selected = false;
+    }
+    BatchOutcome {
+        todos,
+        message: model.status_message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::MockEditor;
+    use chrono::{Duration, TimeZone};
+    use indexmap::IndexMap;
+
+    fn todo(id: &str, due: Option<Due>) -> Todo {
+        Todo {
+            title: id.to_lowercase(),
+            comment: None,
+            expanded: false,
+            done: false,
+            selected: false,
+            due_date: due,
+            todo_id: Some(id.to_string()),
+            google_task_id: None,
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra: toml::Table::new(),
+        }
+    }
+
+    fn find<'a>(todos: &'a [Todo], id: &str) -> &'a Todo {
+        todos
+            .iter()
+            .find(|todo| todo.todo_id.as_deref() == Some(id))
+            .expect("todo present")
+    }
+
+    #[test]
+    fn apply_by_id_uses_reducer_semantics() {
+        let now = Utc.with_ymd_and_hms(2025, 1, 7, 12, 0, 0).unwrap();
+        let mut blocked = todo("T3", None);
+        blocked.blocked_by = vec!["T1".to_string()];
+        let todos = vec![
+            todo("T1", None),
+            todo("T2", Some(Due::At(now - Duration::days(3)))),
+            blocked,
+        ];
+
+        let ids = vec!["T1".to_string()];
+        let done = apply_by_id(todos, &ids, BatchOp::Done, now, Tz::UTC).unwrap();
+        assert!(find(&done.todos, "T1").done);
+        assert_eq!(find(&done.todos, "T1").completed_at, Some(now));
+        assert!(!find(&done.todos, "T2").done);
+        assert_eq!(done.message.as_deref(), Some("Unblocked: T3 t3"));
+
+        let again = apply_by_id(done.todos, &ids, BatchOp::Done, now, Tz::UTC).unwrap();
+        assert!(find(&again.todos, "T1").done, "done is idempotent");
+
+        let ids = vec!["T2".to_string()];
+        let snoozed =
+            apply_by_id(again.todos, &ids, BatchOp::Snooze { days: 1 }, now, Tz::UTC).unwrap();
+        assert_eq!(
+            find(&snoozed.todos, "T2").due_date,
+            Some(Due::At(now + Duration::days(1))),
+            "overdue todos snooze relative to now"
+        );
+        assert_eq!(find(&snoozed.todos, "T2").snooze_count, 1);
+        assert!(snoozed.todos.iter().all(|todo| !todo.selected));
+
+        let ids = vec!["T1".to_string()];
+        let undone = apply_by_id(snoozed.todos, &ids, BatchOp::Undone, now, Tz::UTC).unwrap();
+        assert_eq!(find(&undone.todos, "T1").completed_at, None);
+    }
+
+    #[test]
+    fn edit_by_id_rejects_edits_that_break_dependencies() {
+        let now = Utc.with_ymd_and_hms(2025, 1, 7, 12, 0, 0).unwrap();
+        let mut edited = todo("T1", None);
+        edited.blocked_by = vec!["T9".to_string()];
+        let editor = MockEditor::new(edited);
+
+        let err = edit_by_id(vec![todo("T1", None)], "T1", &editor, now, Tz::UTC)
+            .expect_err("dangling blocker");
+        assert!(err.to_string().contains("unknown todo 'T9'"));
+        assert!(edit_by_id(vec![todo("T1", None)], "T7", &editor, now, Tz::UTC).is_err());
+    }
+}
@@ -6,7 +6,8 @@
 //!
 //! The implementation resolves `$VISUAL`/`$EDITOR`, parses optional command-line
 //! arguments safely, writes a temporary TOML payload, and then reads the edited
-//! content back into a validated `Todo` value.
+//! content back into a validated `Todo` value. Custom fields appear in a trailing
+//! `[fields]` table and are checked against the store's field schema.
 //!
 //! The `TodoEditor` trait supports runtime and test implementations.
 
@@ -16,14 +17,20 @@ use tempfile::NamedTempFile;
 
 use crate::config::DEFAULT_EDITOR;
 use crate::error::{JugglerError, Result};
-use crate::store::{TodoItem, parse_due_date};
+use crate::fields::{FieldSchema, FieldType, fields_to_toml};
+use crate::store::{LogEntry, TodoItem, parse_due_date, parse_todo_id};
+
+const LOG_KEY: &str = "log";
+/// Keys juggler maintains itself; they are hidden from the payload and kept
+/// from the original todo.
+const BOOKKEEPING_KEYS: [&str; 3] = ["created_at", "completed_at", "snooze_count"];
 
 use super::todo::Todo;
 
 /// Editable TOML payload shown to users in external editors.
 ///
 /// `todo_id` is intentionally excluded so users cannot accidentally mutate the
-/// stable on-disk primary key.
+/// stable on-disk primary key. Unknown keys are shown and round-tripped as-is.
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 struct EditorTodoPayload {
     title: String,
@@ -35,6 +42,12 @@ struct EditorTodoPayload {
     due_date: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     google_task_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    blocked_by: Vec<String>,
+    #[serde(default, skip_serializing_if = "toml::Table::is_empty")]
+    fields: toml::Table,
+    #[serde(flatten)]
+    extra: toml::Table,
 }
 
 impl From<&TodoItem> for EditorTodoPayload {
@@ -43,22 +56,51 @@ impl From<&TodoItem> for EditorTodoPayload {
             title: item.title.clone(),
             comment: item.comment.clone(),
             done: item.done,
-            due_date: item.due_date.map(|date| date.to_rfc3339()),
+            due_date: item.due_date.map(|due| due.to_storage_string()),
             google_task_id: item.google_task_id.clone(),
+            blocked_by: item.blocked_by.clone(),
+            fields: fields_to_toml(&item.fields),
+            extra: item.extra.clone(),
         }
     }
 }
 
 /// Rebuild storage-facing data from user-edited TOML while preserving
-/// the original stable `todo_id`.
+/// the original stable `todo_id` and append-only `log`. Bookkeeping
+/// timestamps and counters start empty; the caller copies them over.
 fn todo_item_from_editor_payload(
     payload: EditorTodoPayload,
     original_todo_id: Option<String>,
+    original_log: Vec<LogEntry>,
+    field_schema: &FieldSchema,
 ) -> Result<TodoItem> {
+    if payload.extra.contains_key(LOG_KEY) {
+        return Err(JugglerError::config(
+            "The log is append-only; add entries with the TUI note action instead of editing them",
+        ));
+    }
+    if let Some(key) = BOOKKEEPING_KEYS
+        .iter()
+        .find(|key| payload.extra.contains_key(**key))
+    {
+        return Err(JugglerError::config(format!(
+            "'{key}' is maintained by juggler and cannot be edited"
+        )));
+    }
     let due_date = match payload.due_date {
         Some(raw) => Some(parse_due_date(&raw)?),
         None => None,
     };
+    if let Some(invalid) = payload
+        .blocked_by
+        .iter()
+        .find(|todo_id| parse_todo_id(todo_id).is_none())
+    {
+        return Err(JugglerError::config(format!(
+            "Invalid blocked_by id '{invalid}'; expected format T<N> with N >= 1"
+        )));
+    }
+    let fields = field_schema.validate_table(&payload.fields)?;
 
     Ok(TodoItem {
         todo_id: original_todo_id,
@@ -67,15 +109,31 @@ fn todo_item_from_editor_payload(
         done: payload.done,
         due_date,
         google_task_id: payload.google_task_id,
+        blocked_by: payload.blocked_by,
+        log: original_log,
+        created_at: None,
+        completed_at: None,
+        snooze_count: 0,
+        fields,
+        extra: payload.extra,
     })
 }
 
-/// Render editable TOML and append commented scaffolding for omitted optionals.
+/// Render editable TOML and add commented scaffolding for omitted optionals.
 ///
 /// The scaffolding exists purely as UX hints for humans; parsers ignore these
-/// comments and only read uncommented keys.
-fn render_editor_toml(todo_item: &TodoItem) -> Result<String> {
-    let payload = EditorTodoPayload::from(todo_item);
+/// comments and only read uncommented keys. Top-level hints are placed before
+/// any table headers so uncommenting them never lands inside a sub-table, and
+/// the `[fields]` table is always rendered last for the same reason.
+fn render_editor_toml(todo_item: &TodoItem, field_schema: &FieldSchema) -> Result<String> {
+    let mut payload = EditorTodoPayload::from(todo_item);
+    let fields = std::mem::take(&mut payload.fields);
+    let (extra_values, extra_tables): (toml::Table, toml::Table) =
+        std::mem::take(&mut payload.extra)
+            .into_iter()
+            .partition(|(_, value)| !is_table_like(value));
+    payload.extra = extra_values;
+
     let mut content = toml::to_string_pretty(&payload)?;
 
     let mut scaffold_lines: Vec<&str> = Vec::new();
@@ -83,22 +141,81 @@ fn render_editor_toml(todo_item: &TodoItem) -> Result<String> {
         scaffold_lines.push("# comment = \"Optional details\"");
     }
     if payload.due_date.is_none() {
-        scaffold_lines.push("# due_date = \"2025-01-07T09:00:00Z\"");
+        scaffold_lines
+            .push("# due_date = \"2025-01-07\"  # or \"2025-01-07T09:00:00Z\" for a timed due");
     }
     if payload.google_task_id.is_none() {
         scaffold_lines.push("# google_task_id = \"Set by sync\"");
     }
+    if payload.blocked_by.is_empty() {
+        scaffold_lines.push("# blocked_by = [\"T1\"]");
+    }
 
     if !scaffold_lines.is_empty() {
-        if !content.ends_with('\n') {
-            content.push('\n');
+        push_section(&mut content, &scaffold_lines.join("\n"));
+    }
+
+    if !todo_item.log.is_empty() {
+        let mut section = String::from("# Log (append-only, read-only here):");
+        for entry in &todo_item.log {
+            section.push_str(&format!("\n#   {} {}", entry.at.to_rfc3339(), entry.text));
+        }
+        push_section(&mut content, &section);
+    }
+
+    if !extra_tables.is_empty() {
+        push_section(&mut content, &toml::to_string_pretty(&extra_tables)?);
+    }
+
+    if !fields.is_empty() || !field_schema.is_empty() {
+        let mut section = String::from("[fields]\n");
+        section.push_str(&toml::to_string_pretty(&fields)?);
+        for (name, definition) in field_schema.iter() {
+            if !fields.contains_key(name) {
+                section.push_str(&format!("# {name} = {}\n", field_hint(definition)));
+            }
         }
+        push_section(&mut content, &section);
+    }
+
+    Ok(content)
+}
+
+fn push_section(content: &mut String, section: &str) {
+    if !content.ends_with('\n') {
         content.push('\n');
-        content.push_str(&scaffold_lines.join("\n"));
+    }
+    content.push('\n');
+    content.push_str(section);
+    if !content.ends_with('\n') {
         content.push('\n');
     }
+}
 
-    Ok(content)
+fn is_table_like(value: &toml::Value) -> bool {
+    match value {
+        toml::Value::Table(_) => true,
+        toml::Value::Array(items) => !items.is_empty() && items.iter().all(|item| item.is_table()),
+        _ => false,
+    }
+}
+
+fn field_hint(definition: &crate::fields::FieldDefinition) -> String {
+    match definition.field_type {
+        FieldType::String => "\"text\"".to_string(),
+        FieldType::Number => "0".to_string(),
+        FieldType::Date => "2025-01-07".to_string(),
+        FieldType::Bool => "false".to_string(),
+        FieldType::Enum => format!(
+            "\"{}\"  # one of: {}",
+            definition
+                .values
+                .first()
+                .map(String::as_str)
+                .unwrap_or_default(),
+            definition.values.join(", ")
+        ),
+    }
 }
 
 pub trait TodoEditor {
@@ -106,22 +223,42 @@ pub trait TodoEditor {
     fn needs_terminal_restoration(&self) -> bool;
 }
 
-pub struct ExternalEditor;
+/// Editor adapter that launches `$VISUAL`/`$EDITOR` on a temporary TOML file.
+pub struct ExternalEditor {
+    field_schema: FieldSchema,
+    default_command: String,
+}
+
+impl ExternalEditor {
+    pub fn new(field_schema: FieldSchema) -> Self {
+        Self {
+            field_schema,
+            default_command: DEFAULT_EDITOR.to_string(),
+        }
+    }
+
+    /// Command used when neither `$VISUAL` nor `$EDITOR` is set.
+    pub fn with_default_command(mut self, command: impl Into<String>) -> Self {
+        self.default_command = command.into();
+        self
+    }
+}
 
-fn choose_editor_command(visual: Option<&str>, editor: Option<&str>) -> String {
+pub fn choose_editor_command(visual: Option<&str>, editor: Option<&str>, default: &str) -> String {
     let visual = visual.filter(|value| !value.trim().is_empty());
     let editor = editor.filter(|value| !value.trim().is_empty());
-    visual.or(editor).unwrap_or(DEFAULT_EDITOR).to_string()
+    visual.or(editor).unwrap_or(default).to_string()
 }
 
-fn editor_command_from_env() -> String {
+fn editor_command_from_env(default: &str) -> String {
     choose_editor_command(
         env::var("VISUAL").ok().as_deref(),
         env::var("EDITOR").ok().as_deref(),
+        default,
     )
 }
 
-fn parse_editor_command(raw: &str) -> Result<(String, Vec<String>)> {
+pub fn parse_editor_command(raw: &str) -> Result<(String, Vec<String>)> {
     // Parse the editor command with shell-like splitting so args like `code -w`
     // or `vim -u NONE` work as expected without invoking a shell.
     let mut parts = shlex::split(raw).ok_or_else(|| {
@@ -142,22 +279,22 @@ fn parse_editor_command(raw: &str) -> Result<(String, Vec<String>)> {
     Ok((editor, parts))
 }
 
-fn resolve_editor_command() -> Result<(String, Vec<String>)> {
-    parse_editor_command(&editor_command_from_env())
+fn resolve_editor_command(default: &str) -> Result<(String, Vec<String>)> {
+    parse_editor_command(&editor_command_from_env(default))
 }
 
 impl TodoEditor for ExternalEditor {
     fn edit_todo(&self, todo: &Todo) -> Result<Todo> {
         let todo_item = TodoItem::from(todo);
 
-        let toml_content = render_editor_toml(&todo_item)?;
+        let toml_content = render_editor_toml(&todo_item, &self.field_schema)?;
 
         let mut temp_file = NamedTempFile::with_suffix(".toml")?;
         temp_file.write_all(toml_content.as_bytes())?;
         temp_file.flush()?;
 
         let temp_path = temp_file.path();
-        let (editor, args) = resolve_editor_command()?;
+        let (editor, args) = resolve_editor_command(&self.default_command)?;
 
         let status = Command::new(&editor).args(args).arg(temp_path).status()?;
         if !status.success() {
@@ -168,9 +305,17 @@ impl TodoEditor for ExternalEditor {
 
         let modified_content = fs::read_to_string(temp_path)?;
         let modified_payload: EditorTodoPayload = toml::from_str(&modified_content)?;
-        let modified_item = todo_item_from_editor_payload(modified_payload, todo_item.todo_id)?;
+        let modified_item = todo_item_from_editor_payload(
+            modified_payload,
+            todo_item.todo_id,
+            todo_item.log,
+            &self.field_schema,
+        )?;
 
         let mut updated_todo: Todo = modified_item.into();
+        updated_todo.created_at = todo.created_at;
+        updated_todo.completed_at = todo.completed_at;
+        updated_todo.snooze_count = todo.snooze_count;
         updated_todo.expanded = todo.expanded;
         updated_todo.selected = todo.selected;
 
@@ -225,12 +370,28 @@ mod tests {
         EditorTodoPayload, choose_editor_command, parse_editor_command, render_editor_toml,
         todo_item_from_editor_payload,
     };
+    use indexmap::IndexMap;
+
     use crate::config::DEFAULT_EDITOR;
-    use crate::store::TodoItem;
+    use crate::due::Due;
+    use crate::fields::{FieldSchema, FieldValue};
+    use crate::store::{LogEntry, TodoItem};
+    use chrono::{TimeZone, Utc};
+
+    fn schema() -> FieldSchema {
+        let table: toml::Value = toml::from_str(
+            r#"
+            ticket = { type = "string" }
+            size = { type = "enum", values = ["s", "m", "l"] }
+            "#,
+        )
+        .expect("parse schema");
+        FieldSchema::from_toml(Some(&table)).expect("valid schema")
+    }
 
     #[test]
     fn choose_editor_prefers_visual() {
-        let raw = choose_editor_command(Some("code -w"), Some("vim"));
+        let raw = choose_editor_command(Some("code -w"), Some("vim"), DEFAULT_EDITOR);
         let (editor, args) = parse_editor_command(&raw).expect("parse editor");
         assert_eq!(editor, "code");
         assert_eq!(args, vec!["-w"]);
@@ -238,7 +399,7 @@ mod tests {
 
     #[test]
     fn choose_editor_falls_back_to_editor() {
-        let raw = choose_editor_command(None, Some("vim -u NONE"));
+        let raw = choose_editor_command(None, Some("vim -u NONE"), DEFAULT_EDITOR);
         let (editor, args) = parse_editor_command(&raw).expect("parse editor");
         assert_eq!(editor, "vim");
         assert_eq!(args, vec!["-u", "NONE"]);
@@ -246,7 +407,7 @@ mod tests {
 
     #[test]
     fn choose_editor_ignores_blank_visual() {
-        let raw = choose_editor_command(Some("   "), Some("nano"));
+        let raw = choose_editor_command(Some("   "), Some("nano"), DEFAULT_EDITOR);
         let (editor, args) = parse_editor_command(&raw).expect("parse editor");
         assert_eq!(editor, "nano");
         assert!(args.is_empty());
@@ -254,8 +415,10 @@ mod tests {
 
     #[test]
     fn choose_editor_uses_default_when_unset() {
-        let raw = choose_editor_command(None, None);
+        let raw = choose_editor_command(None, None, DEFAULT_EDITOR);
         assert_eq!(raw, DEFAULT_EDITOR);
+        let raw = choose_editor_command(None, Some(" "), "hx");
+        assert_eq!(raw, "hx");
     }
 
     #[test]
@@ -290,17 +453,112 @@ mod tests {
             done: false,
             due_date: None,
             google_task_id: None,
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra: toml::Table::new(),
         };
 
-        let content = render_editor_toml(&item).expect("render toml");
+        let content = render_editor_toml(&item, &FieldSchema::default()).expect("render toml");
         assert!(content.contains("title = \"Test item\""));
         assert!(content.contains("done = false"));
         assert!(content.contains("# comment = \"Optional details\""));
-        assert!(content.contains("# due_date = \"2025-01-07T09:00:00Z\""));
+        assert!(content.contains("# due_date = \"2025-01-07\""));
         assert!(content.contains("# google_task_id = \"Set by sync\""));
         assert!(!content.contains("todo_id"));
     }
 
+    #[test]
+    fn editor_payload_roundtrip_preserves_unknown_keys() {
+        let mut extra = toml::Table::new();
+        extra.insert("priority".to_string(), toml::Value::Integer(3));
+        let item = TodoItem {
+            todo_id: Some("T1".to_string()),
+            title: "Annotated".to_string(),
+            comment: None,
+            done: false,
+            due_date: None,
+            google_task_id: None,
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra,
+        };
+
+        let content = render_editor_toml(&item, &FieldSchema::default()).expect("render toml");
+        assert!(content.contains("priority = 3"));
+
+        let payload: EditorTodoPayload = toml::from_str(&content).expect("parse payload");
+        let item = todo_item_from_editor_payload(
+            payload,
+            item.todo_id,
+            Vec::new(),
+            &FieldSchema::default(),
+        )
+        .expect("todo item");
+        assert_eq!(item.extra.get("priority"), Some(&toml::Value::Integer(3)));
+    }
+
+    #[test]
+    fn render_editor_toml_appends_fields_table_with_schema_hints() {
+        let mut extra = toml::Table::new();
+        extra.insert(
+            "external".to_string(),
+            toml::Value::Table(toml::Table::new()),
+        );
+        let mut fields = IndexMap::new();
+        fields.insert(
+            "ticket".to_string(),
+            FieldValue::String("OPS-1".to_string()),
+        );
+        let item = TodoItem {
+            todo_id: Some("T1".to_string()),
+            title: "Fields".to_string(),
+            comment: None,
+            done: false,
+            due_date: None,
+            google_task_id: None,
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields,
+            extra,
+        };
+
+        let content = render_editor_toml(&item, &schema()).expect("render toml");
+        let fields_section = content.split("[fields]\n").nth(1).expect("fields table");
+        assert!(fields_section.starts_with("ticket = \"OPS-1\"\n"));
+        assert!(fields_section.contains("# size = \"s\"  # one of: s, m, l"));
+        assert!(content.find("[external]") < content.find("[fields]"));
+        assert!(content.find("# comment") < content.find("[external]"));
+
+        let payload: EditorTodoPayload = toml::from_str(&content).expect("parse payload");
+        let parsed =
+            todo_item_from_editor_payload(payload, item.todo_id.clone(), Vec::new(), &schema())
+                .expect("todo");
+        assert_eq!(parsed.fields, item.fields);
+        assert_eq!(parsed.extra, item.extra);
+    }
+
+    #[test]
+    fn editor_payload_rejects_invalid_field_values() {
+        let payload: EditorTodoPayload =
+            toml::from_str("title = \"a\"\n\n[fields]\nsize = \"xl\"\n").expect("parse payload");
+
+        let err = todo_item_from_editor_payload(payload, None, Vec::new(), &schema())
+            .expect_err("enum value outside schema should fail")
+            .to_string();
+        assert!(err.contains("one of: s, m, l"), "{err}");
+    }
+
     #[test]
     fn editor_payload_roundtrip_preserves_todo_id() {
         let payload: EditorTodoPayload = toml::from_str(
@@ -312,17 +570,74 @@ due_date = "2025-01-01T00:00:00Z"
         )
         .expect("parse payload");
 
-        let item = todo_item_from_editor_payload(payload, Some("T42".to_string()))
-            .expect("payload to todo item");
+        let log = vec![LogEntry {
+            at: Utc.with_ymd_and_hms(2025, 1, 2, 9, 30, 0).unwrap(),
+            text: "Called support".to_string(),
+        }];
+        let item = todo_item_from_editor_payload(
+            payload,
+            Some("T42".to_string()),
+            log.clone(),
+            &FieldSchema::default(),
+        )
+        .expect("payload to todo item");
         assert_eq!(item.todo_id.as_deref(), Some("T42"));
+        assert_eq!(item.log, log);
         assert_eq!(item.title, "Updated");
         assert!(item.done);
         assert_eq!(item.comment.as_deref(), Some("Updated comment"));
         assert_eq!(
-            item.due_date
-                .expect("due date")
-                .to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
-            "2025-01-01T00:00:00Z"
+            item.due_date.expect("due date").to_storage_string(),
+            "2025-01-01T00:00:00+00:00"
         );
     }
+
+    #[test]
+    fn editor_payload_accepts_all_day_due_dates() {
+        let payload: EditorTodoPayload =
+            toml::from_str("title = \"a\"\ndue_date = \"2025-03-09\"\n").expect("parse payload");
+
+        let item =
+            todo_item_from_editor_payload(payload, None, Vec::new(), &FieldSchema::default())
+                .expect("payload to todo item");
+        assert_eq!(
+            item.due_date,
+            Some(Due::Date(
+                chrono::NaiveDate::from_ymd_opt(2025, 3, 9).unwrap()
+            ))
+        );
+    }
+
+    #[test]
+    fn editor_shows_log_read_only_and_rejects_log_edits() {
+        let item = TodoItem {
+            todo_id: Some("T1".to_string()),
+            title: "Vendor outage".to_string(),
+            comment: None,
+            done: false,
+            due_date: None,
+            google_task_id: None,
+            blocked_by: Vec::new(),
+            log: vec![LogEntry {
+                at: Utc.with_ymd_and_hms(2025, 1, 2, 9, 30, 0).unwrap(),
+                text: "Opened ticket".to_string(),
+            }],
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra: toml::Table::new(),
+        };
+
+        let rendered = render_editor_toml(&item, &FieldSchema::default()).expect("render");
+        assert!(rendered.contains("#   2025-01-02T09:30:00+00:00 Opened ticket"));
+
+        let payload: EditorTodoPayload = toml::from_str(
+            "title = \"a\"\n[[log]]\nat = \"2025-01-02T09:30:00Z\"\ntext = \"edited\"\n",
+        )
+        .expect("parse payload");
+        let err = todo_item_from_editor_payload(payload, None, item.log, &FieldSchema::default())
+            .expect_err("log edits should be rejected");
+        assert!(err.to_string().contains("append-only"));
+    }
 }
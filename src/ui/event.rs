@@ -3,29 +3,57 @@
 //! This module reads `crossterm` input events and maps key presses to reducer
 //! actions based on the current UI mode.
 //!
-//! Normal mode uses bindings from `keymap`; prompt mode handles text entry and
-//! prompt control keys.
+//! Normal mode uses bindings from `keymap`, collecting keys until they complete
+//! or break a sequence; prompt mode handles text entry and prompt control keys;
+//! any key closes the stats screen.
 
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 
 use crate::error::Result;
 
-use super::keymap::action_for_key;
+use super::keymap::{KeyMatch, KeyPress, Keymap};
 use super::model::AppMode;
 use super::update::Action;
 
-pub(super) fn map_key(mode: &AppMode, key: KeyEvent) -> Option<Action> {
+/// Normal-mode keys pressed so far that start a multi-key binding.
+pub(super) type PendingKeys = Vec<KeyPress>;
+
+pub(super) fn map_key(
+    mode: &AppMode,
+    keymap: &Keymap,
+    pending: &mut PendingKeys,
+    key: KeyEvent,
+) -> Option<Action> {
     match mode {
-        AppMode::Normal => map_normal_mode_key(key),
+        AppMode::Normal => map_normal_mode_key(keymap, pending, key),
         AppMode::Prompt(_) => map_prompt_mode_key(key),
+        AppMode::Stats => Some(Action::CloseStats),
     }
 }
 
-fn map_normal_mode_key(key: KeyEvent) -> Option<Action> {
-    if !matches!(key.modifiers, KeyModifiers::NONE | KeyModifiers::SHIFT) {
-        return None;
+fn map_normal_mode_key(
+    keymap: &Keymap,
+    pending: &mut PendingKeys,
+    key: KeyEvent,
+) -> Option<Action> {
+    pending.push(KeyPress::from_event(key));
+    match keymap.lookup(pending) {
+        KeyMatch::Action(action) => {
+            pending.clear();
+            Some(Action::Normal(action))
+        }
+        KeyMatch::Pending => None,
+        KeyMatch::Unbound => {
+            // A key that breaks a sequence may still start a new one.
+            let broke_sequence = pending.len() > 1;
+            pending.clear();
+            if broke_sequence {
+                map_normal_mode_key(keymap, pending, key)
+            } else {
+                None
+            }
+        }
     }
-    action_for_key(key.code).map(Action::Normal)
 }
 
 fn map_prompt_mode_key(key: KeyEvent) -> Option<Action> {
@@ -40,10 +68,14 @@ fn map_prompt_mode_key(key: KeyEvent) -> Option<Action> {
     }
 }
 
-pub(super) fn read_action(mode: &AppMode) -> Result<Option<Action>> {
+pub(super) fn read_action(
+    mode: &AppMode,
+    keymap: &Keymap,
+    pending: &mut PendingKeys,
+) -> Result<Option<Action>> {
     match event::read()? {
         Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
-            Ok(map_key(mode, key_event))
+            Ok(map_key(mode, keymap, pending, key_event))
         }
         _ => Ok(None),
     }
@@ -52,28 +84,64 @@ pub(super) fn read_action(mode: &AppMode) -> Result<Option<Action>> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::ui::keymap::{Action as NormalAction, key_for_action};
+    use crate::ui::keymap::Action as NormalAction;
     use crate::ui::model::{PromptAction, PromptOverlay};
+    use indexmap::IndexMap;
+
+    fn map(mode: &AppMode, key: KeyEvent) -> Option<Action> {
+        map_key(mode, &Keymap::default(), &mut PendingKeys::new(), key)
+    }
 
     #[test]
     fn normal_mode_maps_bound_keys() {
-        let key = KeyEvent::new(key_for_action(NormalAction::ToggleDone), KeyModifiers::NONE);
+        let key = KeyEvent::new(KeyCode::Char('e'), KeyModifiers::NONE);
+        assert!(matches!(
+            map(&AppMode::Normal, key),
+            Some(Action::Normal(NormalAction::ToggleDone))
+        ));
+    }
+
+    #[test]
+    fn normal_mode_collects_key_sequences() {
+        let overrides = IndexMap::from([
+            ("snooze_day".to_string(), "g s".to_string()),
+            ("toggle_done".to_string(), "ctrl-d".to_string()),
+        ]);
+        let keymap = Keymap::from_config(&overrides).unwrap();
+        let mut pending = PendingKeys::new();
+        let mut press = |c, modifiers| {
+            map_key(
+                &AppMode::Normal,
+                &keymap,
+                &mut pending,
+                KeyEvent::new(KeyCode::Char(c), modifiers),
+            )
+        };
+
+        assert!(press('g', KeyModifiers::NONE).is_none());
+        assert!(matches!(
+            press('s', KeyModifiers::NONE),
+            Some(Action::Normal(NormalAction::SnoozeDay))
+        ));
+        // An unbound second key drops the sequence and is looked up alone.
+        assert!(press('g', KeyModifiers::NONE).is_none());
         assert!(matches!(
-            map_key(&AppMode::Normal, key),
+            press('d', KeyModifiers::CONTROL),
             Some(Action::Normal(NormalAction::ToggleDone))
         ));
+        assert!(press('e', KeyModifiers::NONE).is_none());
     }
 
     #[test]
     fn normal_mode_ignores_unknown_keys() {
         let key = KeyEvent::new(KeyCode::F(1), KeyModifiers::NONE);
-        assert!(map_key(&AppMode::Normal, key).is_none());
+        assert!(map(&AppMode::Normal, key).is_none());
     }
 
     #[test]
     fn normal_mode_ignores_control_modified_keys() {
         let key = KeyEvent::new(KeyCode::Char('e'), KeyModifiers::CONTROL);
-        assert!(map_key(&AppMode::Normal, key).is_none());
+        assert!(map(&AppMode::Normal, key).is_none());
     }
 
     #[test]
@@ -85,19 +153,19 @@ mod tests {
         });
 
         assert!(matches!(
-            map_key(&mode, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)),
+            map(&mode, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)),
             Some(Action::PromptSubmit)
         ));
         assert!(matches!(
-            map_key(&mode, KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)),
+            map(&mode, KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)),
             Some(Action::PromptCancel)
         ));
         assert!(matches!(
-            map_key(&mode, KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE)),
+            map(&mode, KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE)),
             Some(Action::PromptBackspace)
         ));
         assert!(matches!(
-            map_key(&mode, KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE)),
+            map(&mode, KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE)),
             Some(Action::PromptInput('d'))
         ));
     }
@@ -110,6 +178,6 @@ mod tests {
             action: PromptAction::CustomDelay,
         });
         let key = KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL);
-        assert!(map_key(&mode, key).is_none());
+        assert!(map(&mode, key).is_none());
     }
 }
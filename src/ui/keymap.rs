@@ -1,13 +1,18 @@
-//! Canonical normal-mode key bindings.
+//! Normal-mode key bindings.
 //!
-//! This module defines the binding table used to map keys to normal-mode
-//! actions and to build footer help text.
+//! This module defines the default binding table, merges the `[keys]` table
+//! from `config.toml` over it, and builds footer help text from the result.
+//! A binding is one key or a sequence of keys, each optionally with Ctrl or
+//! Alt, written like `e`, `ctrl-d`, or `g s` in the config file.
 //!
 //! Tests in this module validate binding uniqueness and action/key round-trips.
 
-use std::sync::LazyLock;
+use std::fmt;
 
-use crossterm::event::KeyCode;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use indexmap::IndexMap;
+
+use crate::error::{JugglerError, Result};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub(super) enum Action {
@@ -25,126 +30,357 @@ pub(super) enum Action {
     PreponeWeek,
     Create,
     CustomDelay,
+    ToggleHideBlocked,
+    Filter,
+    AddNote,
+    ShowStats,
+}
+
+impl Action {
+    /// The action's name under `[keys]` in `config.toml`.
+    fn name(self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::QuitWithSync => "quit_with_sync",
+            Action::ToggleExpand => "toggle_expand",
+            Action::NextItem => "next_item",
+            Action::PreviousItem => "previous_item",
+            Action::ToggleDone => "toggle_done",
+            Action::Edit => "edit",
+            Action::ToggleSelect => "toggle_select",
+            Action::SnoozeDay => "snooze_day",
+            Action::UnsnoozeDay => "unsnooze_day",
+            Action::PostponeWeek => "postpone_week",
+            Action::PreponeWeek => "prepone_week",
+            Action::Create => "create",
+            Action::CustomDelay => "custom_delay",
+            Action::ToggleHideBlocked => "toggle_hide_blocked",
+            Action::Filter => "filter",
+            Action::AddNote => "add_note",
+            Action::ShowStats => "show_stats",
+        }
+    }
+
+    /// Footer help shown after the action's keys, as in `o-open`.
+    fn help_suffix(self) -> &'static str {
+        match self {
+            Action::Quit => "-quit",
+            Action::QuitWithSync => "-quit+sync",
+            Action::ToggleExpand => "-open",
+            Action::NextItem | Action::PreviousItem => "-nav",
+            Action::ToggleDone => "-done",
+            Action::Edit => "-edit",
+            Action::ToggleSelect => "-select",
+            Action::SnoozeDay => ":+1d",
+            Action::UnsnoozeDay => ":-1d",
+            Action::PostponeWeek => ":+7d",
+            Action::PreponeWeek => ":-7d",
+            Action::Create => "-new",
+            Action::CustomDelay => "-custom",
+            Action::ToggleHideBlocked => "-hide blocked",
+            Action::Filter => "-filter",
+            Action::AddNote => "-note",
+            Action::ShowStats => "-stats",
+        }
+    }
 }
 
+/// One key press. Shift is not tracked separately since it is already part
+/// of the character (`E` rather than `shift-e`).
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) struct KeyPress {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+const NAMED_KEYS: [(&str, KeyCode); 14] = [
+    ("space", KeyCode::Char(' ')),
+    ("enter", KeyCode::Enter),
+    ("tab", KeyCode::Tab),
+    ("backspace", KeyCode::Backspace),
+    ("esc", KeyCode::Esc),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+    ("delete", KeyCode::Delete),
+];
+
+impl KeyPress {
+    pub(super) fn from_event(key: KeyEvent) -> Self {
+        Self {
+            code: key.code,
+            modifiers: key.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT),
+        }
+    }
+
+    /// Parse one key such as `e`, `ctrl-d`, `alt-enter`, or `f2`.
+    fn parse(spec: &str) -> Result<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = spec;
+        while let Some((modifier, key)) = rest.split_once('-')
+            && !key.is_empty()
+        {
+            modifiers |= match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                _ => {
+                    return Err(JugglerError::config(format!(
+                        "Invalid key '{spec}': unknown modifier '{modifier}' (expected ctrl or alt)"
+                    )));
+                }
+            };
+            rest = key;
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => {
+                let name = rest.to_ascii_lowercase();
+                NAMED_KEYS
+                    .iter()
+                    .find(|(key_name, _)| *key_name == name)
+                    .map(|(_, code)| *code)
+                    .or_else(|| {
+                        let number: u8 = name.strip_prefix('f')?.parse().ok()?;
+                        (1..=12).contains(&number).then_some(KeyCode::F(number))
+                    })
+                    .ok_or_else(|| JugglerError::config(format!("Invalid key '{spec}'")))?
+            }
+        };
+        Ok(Self { code, modifiers })
+    }
+}
+
+impl fmt::Display for KeyPress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            f.write_str("ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            f.write_str("alt-")?;
+        }
+        match self.code {
+            KeyCode::F(number) => write!(f, "f{number}"),
+            KeyCode::Char(c) if c != ' ' => write!(f, "{c}"),
+            code => {
+                let name = NAMED_KEYS
+                    .iter()
+                    .find(|(_, named)| *named == code)
+                    .map_or("?", |(name, _)| name);
+                f.write_str(name)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub(super) struct KeyBinding {
     pub(super) action: Action,
-    pub(super) key_code: KeyCode,
-    pub(super) help_token: &'static str,
+    pub(super) keys: Vec<KeyPress>,
+}
+
+impl KeyBinding {
+    fn keys_text(&self) -> String {
+        let keys: Vec<String> = self.keys.iter().map(KeyPress::to_string).collect();
+        keys.join(" ")
+    }
 }
 
 const HELP_SUFFIX: &str = "Ops affect selected; if none, the cursored item.";
 
-const KEY_BINDINGS: [KeyBinding; 14] = [
-    KeyBinding {
-        action: Action::ToggleExpand,
-        key_code: KeyCode::Char('o'),
-        help_token: "o-open",
-    },
-    KeyBinding {
-        action: Action::NextItem,
-        key_code: KeyCode::Char('j'),
-        help_token: "j/k-nav",
-    },
-    KeyBinding {
-        action: Action::PreviousItem,
-        key_code: KeyCode::Char('k'),
-        help_token: "j/k-nav",
-    },
-    KeyBinding {
-        action: Action::ToggleSelect,
-        key_code: KeyCode::Char('x'),
-        help_token: "x-select",
-    },
-    KeyBinding {
-        action: Action::ToggleDone,
-        key_code: KeyCode::Char('e'),
-        help_token: "e-done",
-    },
-    KeyBinding {
-        action: Action::Edit,
-        key_code: KeyCode::Char('E'),
-        help_token: "E-edit",
-    },
-    KeyBinding {
-        action: Action::Create,
-        key_code: KeyCode::Char('c'),
-        help_token: "c-new",
-    },
-    KeyBinding {
-        action: Action::SnoozeDay,
-        key_code: KeyCode::Char('s'),
-        help_token: "s:+1d",
-    },
-    KeyBinding {
-        action: Action::UnsnoozeDay,
-        key_code: KeyCode::Char('S'),
-        help_token: "S:-1d",
-    },
-    KeyBinding {
-        action: Action::PostponeWeek,
-        key_code: KeyCode::Char('p'),
-        help_token: "p:+7d",
-    },
-    KeyBinding {
-        action: Action::PreponeWeek,
-        key_code: KeyCode::Char('P'),
-        help_token: "P:-7d",
-    },
-    KeyBinding {
-        action: Action::CustomDelay,
-        key_code: KeyCode::Char('t'),
-        help_token: "t-custom",
-    },
-    KeyBinding {
-        action: Action::Quit,
-        key_code: KeyCode::Char('q'),
-        help_token: "q-quit",
-    },
-    KeyBinding {
-        action: Action::QuitWithSync,
-        key_code: KeyCode::Char('Q'),
-        help_token: "Q-quit+sync",
-    },
+/// Default bindings, in footer help order. Every action appears exactly once.
+const DEFAULT_KEY_BINDINGS: [(Action, &str); 18] = [
+    (Action::ToggleExpand, "o"),
+    (Action::NextItem, "j"),
+    (Action::PreviousItem, "k"),
+    (Action::ToggleSelect, "x"),
+    (Action::ToggleDone, "e"),
+    (Action::Edit, "E"),
+    (Action::Create, "c"),
+    (Action::SnoozeDay, "s"),
+    (Action::UnsnoozeDay, "S"),
+    (Action::PostponeWeek, "p"),
+    (Action::PreponeWeek, "P"),
+    (Action::CustomDelay, "t"),
+    (Action::AddNote, "n"),
+    (Action::ToggleHideBlocked, "b"),
+    (Action::Filter, "/"),
+    (Action::ShowStats, "i"),
+    (Action::Quit, "q"),
+    (Action::QuitWithSync, "Q"),
 ];
 
-static HELP_TEXT: LazyLock<String> = LazyLock::new(|| {
-    let mut tokens: Vec<&'static str> = Vec::new();
-    for binding in KEY_BINDINGS {
-        if !tokens.contains(&binding.help_token) {
-            tokens.push(binding.help_token);
+/// What the keys pressed so far resolve to.
+#[derive(Debug, PartialEq, Eq)]
+pub(super) enum KeyMatch {
+    Action(Action),
+    /// The keys start a longer sequence.
+    Pending,
+    Unbound,
+}
+
+/// The active normal-mode bindings and the footer help built from them.
+#[derive(Clone, Debug)]
+pub struct Keymap {
+    bindings: Vec<KeyBinding>,
+    help: String,
+}
+
+impl Keymap {
+    /// Apply `overrides` (action name to key spec, from `[keys]` in
+    /// `config.toml`) over the defaults. Fails on unknown actions, unparsable
+    /// keys, and sequences that are bound twice or shadow a longer one.
+    pub fn from_config(overrides: &IndexMap<String, String>) -> Result<Self> {
+        let mut specs: Vec<(Action, &str)> = DEFAULT_KEY_BINDINGS.to_vec();
+        for (name, spec) in overrides {
+            let Some(entry) = specs.iter_mut().find(|(action, _)| action.name() == *name) else {
+                let names: Vec<&str> = DEFAULT_KEY_BINDINGS
+                    .iter()
+                    .map(|(action, _)| action.name())
+                    .collect();
+                return Err(JugglerError::config(format!(
+                    "Unknown key binding action '{name}' (expected one of: {})",
+                    names.join(", ")
+                )));
+            };
+            entry.1 = spec;
+        }
+
+        let mut bindings = Vec::with_capacity(specs.len());
+        for (action, spec) in specs {
+            let keys = spec
+                .split_whitespace()
+                .map(KeyPress::parse)
+                .collect::<Result<Vec<_>>>()?;
+            if keys.is_empty() {
+                return Err(JugglerError::config(format!(
+                    "Key binding for '{}' is empty",
+                    action.name()
+                )));
+            }
+            bindings.push(KeyBinding { action, keys });
         }
+        validate_bindings(&bindings)?;
+
+        let help = build_help_text(&bindings);
+        Ok(Self { bindings, help })
     }
 
-    let joined_tokens = tokens.join(", ");
-    format!("{joined_tokens}. {HELP_SUFFIX}")
-});
+    pub(super) fn lookup(&self, keys: &[KeyPress]) -> KeyMatch {
+        let mut pending = false;
+        for binding in &self.bindings {
+            if binding.keys == keys {
+                return KeyMatch::Action(binding.action);
+            }
+            pending |= binding.keys.starts_with(keys);
+        }
+        if pending {
+            KeyMatch::Pending
+        } else {
+            KeyMatch::Unbound
+        }
+    }
 
-pub(super) fn action_for_key(key_code: KeyCode) -> Option<Action> {
-    KEY_BINDINGS
-        .iter()
-        .find(|binding| binding.key_code == key_code)
-        .map(|binding| binding.action)
+    pub(super) fn help_text(&self) -> &str {
+        &self.help
+    }
+
+    #[cfg(test)]
+    pub(super) fn keys_for_action(&self, action: Action) -> &[KeyPress] {
+        self.bindings
+            .iter()
+            .find(|binding| binding.action == action)
+            .map(|binding| binding.keys.as_slice())
+            .expect("all actions must have a key binding")
+    }
 }
 
-#[cfg(test)]
-pub(super) fn key_for_action(action: Action) -> KeyCode {
-    KEY_BINDINGS
-        .iter()
-        .find(|binding| binding.action == action)
-        .map(|binding| binding.key_code)
-        .expect("all actions must have a key binding")
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::from_config(&IndexMap::new()).expect("default key bindings are valid")
+    }
+}
+
+/// Every key sequence must pick exactly one action: no sequence may be bound
+/// twice or be the start of another, which could then never be completed.
+fn validate_bindings(bindings: &[KeyBinding]) -> Result<()> {
+    for (i, first) in bindings.iter().enumerate() {
+        for second in &bindings[i + 1..] {
+            let (shorter, longer) = if first.keys.len() <= second.keys.len() {
+                (first, second)
+            } else {
+                (second, first)
+            };
+            if !longer.keys.starts_with(&shorter.keys) {
+                continue;
+            }
+            let message = if shorter.keys.len() == longer.keys.len() {
+                format!(
+                    "Duplicate key binding '{}' for both '{}' and '{}'",
+                    shorter.keys_text(),
+                    first.action.name(),
+                    second.action.name()
+                )
+            } else {
+                format!(
+                    "Key binding '{}' for '{}' is a prefix of '{}' for '{}'",
+                    shorter.keys_text(),
+                    shorter.action.name(),
+                    longer.keys_text(),
+                    longer.action.name()
+                )
+            };
+            return Err(JugglerError::config(message));
+        }
+    }
+    Ok(())
 }
 
-pub(super) fn help_text() -> &'static str {
-    HELP_TEXT.as_str()
+fn build_help_text(bindings: &[KeyBinding]) -> String {
+    let keys_for = |action: Action| {
+        bindings
+            .iter()
+            .find(|binding| binding.action == action)
+            .map(KeyBinding::keys_text)
+            .unwrap_or_default()
+    };
+    let tokens: Vec<String> = bindings
+        .iter()
+        .filter_map(|binding| match binding.action {
+            // Navigation shares one token, e.g. `j/k-nav`.
+            Action::PreviousItem => None,
+            Action::NextItem => Some(format!(
+                "{}/{}{}",
+                binding.keys_text(),
+                keys_for(Action::PreviousItem),
+                binding.action.help_suffix()
+            )),
+            action => Some(format!("{}{}", binding.keys_text(), action.help_suffix())),
+        })
+        .collect();
+
+    let joined_tokens = tokens.join(", ");
+    format!("{joined_tokens}. {HELP_SUFFIX}")
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn keymap_with(overrides: &[(&str, &str)]) -> Result<Keymap> {
+        let overrides: IndexMap<String, String> = overrides
+            .iter()
+            .map(|(name, spec)| (name.to_string(), spec.to_string()))
+            .collect();
+        Keymap::from_config(&overrides)
+    }
+
     #[test]
     fn all_actions_round_trip_through_key_lookup() {
         let all_actions = [
@@ -162,32 +398,92 @@ mod tests {
             Action::PreponeWeek,
             Action::Create,
             Action::CustomDelay,
+            Action::ToggleHideBlocked,
+            Action::Filter,
+            Action::AddNote,
+            Action::ShowStats,
         ];
 
+        let keymap = Keymap::default();
         for action in all_actions {
-            assert_eq!(action_for_key(key_for_action(action)), Some(action));
+            assert_eq!(
+                keymap.lookup(keymap.keys_for_action(action)),
+                KeyMatch::Action(action)
+            );
         }
     }
 
     #[test]
     fn key_bindings_have_unique_key_codes() {
-        let mut seen: Vec<KeyCode> = Vec::new();
+        let mut seen: Vec<&str> = Vec::new();
 
-        for binding in KEY_BINDINGS {
-            assert!(
-                !seen.contains(&binding.key_code),
-                "duplicate key binding for {:?}",
-                binding.key_code
-            );
-            seen.push(binding.key_code);
+        for (_, spec) in DEFAULT_KEY_BINDINGS {
+            assert!(!seen.contains(&spec), "duplicate key binding for {spec:?}");
+            seen.push(spec);
         }
+        assert!(validate_bindings(&Keymap::default().bindings).is_ok());
     }
 
     #[test]
     fn help_text_matches_expected_footer() {
         assert_eq!(
-            help_text(),
-            "o-open, j/k-nav, x-select, e-done, E-edit, c-new, s:+1d, S:-1d, p:+7d, P:-7d, t-custom, q-quit, Q-quit+sync. Ops affect selected; if none, the cursored item."
+            Keymap::default().help_text(),
+            "o-open, j/k-nav, x-select, e-done, E-edit, c-new, s:+1d, S:-1d, p:+7d, P:-7d, t-custom, n-note, b-hide blocked, /-filter, i-stats, q-quit, Q-quit+sync. Ops affect selected; if none, the cursored item."
+        );
+    }
+
+    #[test]
+    fn config_remaps_keys_with_modifiers_and_sequences() {
+        let keymap = keymap_with(&[
+            ("toggle_done", "d"),
+            ("quit", "ctrl-c"),
+            ("snooze_day", "g  S"),
+            ("unsnooze_day", "alt-F2"),
+        ])
+        .unwrap();
+
+        let press = |code, modifiers| KeyPress { code, modifiers };
+        let d = press(KeyCode::Char('d'), KeyModifiers::NONE);
+        let g = press(KeyCode::Char('g'), KeyModifiers::NONE);
+        let big_s = press(KeyCode::Char('S'), KeyModifiers::NONE);
+        assert_eq!(keymap.lookup(&[d]), KeyMatch::Action(Action::ToggleDone));
+        assert_eq!(
+            keymap.lookup(&[press(KeyCode::Char('c'), KeyModifiers::CONTROL)]),
+            KeyMatch::Action(Action::Quit)
+        );
+        assert_eq!(keymap.lookup(&[g]), KeyMatch::Pending);
+        assert_eq!(
+            keymap.lookup(&[g, big_s]),
+            KeyMatch::Action(Action::SnoozeDay)
+        );
+        assert_eq!(
+            keymap.lookup(&[press(KeyCode::F(2), KeyModifiers::ALT)]),
+            KeyMatch::Action(Action::UnsnoozeDay)
+        );
+        let e = press(KeyCode::Char('e'), KeyModifiers::NONE);
+        assert_eq!(keymap.lookup(&[e]), KeyMatch::Unbound);
+        assert_eq!(
+            keymap.help_text(),
+            "o-open, j/k-nav, x-select, d-done, E-edit, c-new, g S:+1d, alt-f2:-1d, p:+7d, P:-7d, t-custom, n-note, b-hide blocked, /-filter, i-stats, ctrl-c-quit, Q-quit+sync. Ops affect selected; if none, the cursored item."
+        );
+    }
+
+    #[test]
+    fn config_rejects_conflicting_and_unknown_bindings() {
+        let error = |overrides: &[(&str, &str)]| keymap_with(overrides).unwrap_err().to_string();
+
+        assert!(
+            error(&[("toggle_done", "x")])
+                .contains("Duplicate key binding 'x' for both 'toggle_select' and 'toggle_done'")
+        );
+        assert!(
+            error(&[("create", "g"), ("snooze_day", "g s")])
+                .contains("Key binding 'g' for 'create' is a prefix of 'g s' for 'snooze_day'")
         );
+        assert!(error(&[("finish", "d")]).contains("Unknown key binding action 'finish'"));
+        assert!(error(&[("quit", "hyper-q")]).contains("unknown modifier 'hyper'"));
+        assert!(error(&[("quit", "f13")]).contains("Invalid key 'f13'"));
+        assert!(error(&[("quit", " ")]).contains("Key binding for 'quit' is empty"));
+        assert!(keymap_with(&[("toggle_done", "ctrl--")]).is_ok());
     }
 }
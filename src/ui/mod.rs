@@ -6,6 +6,7 @@
 //! The loop renders state, reads input, maps input to actions, runs the
 //! reducer, and executes requested side effects such as external editing.
 
+mod batch;
 mod editor;
 mod event;
 mod keymap;
@@ -15,18 +16,24 @@ mod update;
 mod view;
 mod widgets;
 
+use chrono::Duration;
+use chrono_tz::Tz;
 use ratatui::DefaultTerminal;
 
 use crate::error::Result;
+use crate::fields::FieldSchema;
+use crate::hooks::Hooks;
 use crate::time::{SharedClock, system_clock};
 
-pub use editor::{ExternalEditor, TodoEditor};
+pub use batch::{BatchOp, apply_by_id, edit_by_id};
+pub use editor::{ExternalEditor, TodoEditor, choose_editor_command, parse_editor_command};
 #[cfg(test)]
 pub use editor::{MockEditor, NoOpEditor};
-pub use todo::Todo;
+pub use keymap::Keymap;
+pub use todo::{DueDateUrgency, Todo, format_duration_compact, parse_relative_duration};
 
-use event::read_action;
-use model::AppModel;
+use event::{PendingKeys, read_action};
+use model::{AppModel, TodoItems};
 use update::{Action, SideEffect, update};
 use view::draw;
 
@@ -34,6 +41,8 @@ pub struct App {
     model: AppModel,
     editor: Box<dyn TodoEditor>,
     clock: SharedClock,
+    hooks: Hooks,
+    pending_keys: PendingKeys,
 }
 
 /// The main application state and controller for the Juggler TUI.
@@ -46,7 +55,8 @@ pub struct App {
 ///
 /// ```ignore
 /// let todos = store::load_todos(&path);
-/// let mut app = App::new(todos, Box::new(ExternalEditor));
+/// let mut app = App::new(todos, Box::new(ExternalEditor::new(schema.clone())))
+///     .with_field_schema(schema);
 /// let mut terminal = ratatui::init();
 /// app.run(&mut terminal)?;
 /// let updated_todos = app.items();
@@ -65,9 +75,43 @@ impl App {
             model: AppModel::new(items),
             editor,
             clock,
+            hooks: Hooks::default(),
+            pending_keys: PendingKeys::new(),
         }
     }
 
+    /// Show the schema's column fields next to each todo title.
+    pub fn with_field_schema(mut self, field_schema: FieldSchema) -> Self {
+        self.model.field_schema = field_schema;
+        self
+    }
+
+    /// Interpret all-day dues in `timezone` for ordering, display, and snoozing.
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.model.timezone = timezone;
+        self.model.items = TodoItems::new(self.model.items.to_vec(), timezone);
+        self
+    }
+
+    /// Run `on-add`/`on-done`/`on-modify` hooks after each action that
+    /// changes todos.
+    pub fn with_hooks(mut self, hooks: Hooks) -> Self {
+        self.hooks = hooks;
+        self
+    }
+
+    /// Flag todos whose deadline is within `due_soon` as due soon.
+    pub fn with_due_soon(mut self, due_soon: Duration) -> Self {
+        self.model.due_soon = due_soon;
+        self
+    }
+
+    /// Use `keymap` for normal-mode keys and the help line.
+    pub fn with_keymap(mut self, keymap: Keymap) -> Self {
+        self.model.keymap = keymap;
+        self
+    }
+
     pub fn items(&self) -> Vec<Todo> {
         self.model.items.to_vec()
     }
@@ -81,7 +125,9 @@ impl App {
             let now = self.clock.now();
             terminal.draw(|frame| draw(frame, &self.model, now))?;
 
-            if let Some(action) = read_action(&self.model.mode)? {
+            if let Some(action) =
+                read_action(&self.model.mode, &self.model.keymap, &mut self.pending_keys)?
+            {
                 self.process_action(action, Some(terminal));
             }
         }
@@ -89,9 +135,17 @@ impl App {
     }
 
     fn process_action(&mut self, action: Action, terminal: Option<&mut DefaultTerminal>) {
+        let before = self.hooks.is_enabled().then(|| self.model.items.to_vec());
         if let Some(side_effect) = update(&mut self.model, action, self.clock.now()) {
             self.handle_side_effect(side_effect, terminal);
         }
+        if let Some(before) = before
+            && let Err(e) = self
+                .hooks
+                .notify_changes(&before, &self.model.items.to_vec())
+        {
+            self.model.status_message = Some(e.to_string());
+        }
     }
 
     fn handle_side_effect(
@@ -155,6 +209,7 @@ mod tests {
     use super::*;
     use crate::error::{JugglerError, Result};
     use crate::ui::keymap::Action as NormalAction;
+    use indexmap::IndexMap;
 
     fn todo(title: &str) -> Todo {
         Todo {
@@ -166,6 +221,13 @@ mod tests {
             due_date: None,
             todo_id: None,
             google_task_id: None,
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra: toml::Table::new(),
         }
     }
 
@@ -212,6 +274,13 @@ mod tests {
             due_date: None,
             todo_id: None,
             google_task_id: None,
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra: toml::Table::new(),
         };
         let mut app = App::new(vec![todo("original")], Box::new(MockEditor::new(updated)));
 
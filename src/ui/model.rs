@@ -5,10 +5,22 @@
 //! overlays. It does not perform I/O, rendering, or side-effect execution.
 //!
 //! `TodoItems` and `UiState` hold the list partitions and cursor/focus indexes
-//! used by reducer and rendering code.
+//! used by reducer and rendering code. `TodoItems` also tracks which pending
+//! todos are blocked by other open todos and can park them, and todos outside
+//! the active filter query, out of view.
 
-use chrono::{DateTime, Utc};
+use std::collections::HashSet;
 
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
+
+use crate::config::DUE_SOON_THRESHOLD_SECS;
+use crate::error::{JugglerError, Result};
+use crate::fields::FieldSchema;
+use crate::query::Query;
+use crate::store::{format_todo_id, parse_todo_id, validate_blocked_by};
+
+use super::keymap::Keymap;
 use super::todo::Todo;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -20,6 +32,8 @@ pub(super) enum Section {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(super) enum PromptAction {
     CustomDelay,
+    AddNote,
+    Filter,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -33,17 +47,28 @@ pub(super) struct PromptOverlay {
 pub(super) enum AppMode {
     Normal,
     Prompt(PromptOverlay),
+    /// Full-screen statistics; any key returns to the list.
+    Stats,
 }
 
 #[derive(Debug, Clone)]
 pub(super) struct TodoItems {
     pub(super) pending: Vec<Todo>,
     pub(super) done: Vec<Todo>,
+    /// Todos parked out of view: blocked pending ones while `hide_blocked` is
+    /// set, and any that do not match `filter`.
+    pub(super) hidden: Vec<Todo>,
+    pub(super) hide_blocked: bool,
+    pub(super) filter: Option<Query>,
 }
 
 impl TodoItems {
-    pub(super) fn new(mut items: Vec<Todo>) -> Self {
-        items.sort_by_key(|todo| todo.due_date.unwrap_or(DateTime::<Utc>::MAX_UTC));
+    pub(super) fn new(mut items: Vec<Todo>, tz: Tz) -> Self {
+        items.sort_by_key(|todo| {
+            todo.due_date
+                .map(|due| due.starts_at(tz))
+                .unwrap_or(DateTime::<Utc>::MAX_UTC)
+        });
 
         let mut pending = Vec::new();
         let mut done = Vec::new();
@@ -56,7 +81,13 @@ impl TodoItems {
             }
         }
 
-        Self { pending, done }
+        Self {
+            pending,
+            done,
+            hidden: Vec::new(),
+            hide_blocked: false,
+            filter: None,
+        }
     }
 
     pub(super) fn get(&self, section: Section, index: usize) -> Option<&Todo> {
@@ -81,12 +112,14 @@ impl TodoItems {
         self.done.len()
     }
 
-    pub(super) fn toggle_done(&mut self, section: Section, index: usize) {
+    /// Move a todo to the other section, stamping or clearing `completed_at`.
+    pub(super) fn toggle_done(&mut self, section: Section, index: usize, now: DateTime<Utc>) {
         match section {
             Section::Pending => {
                 if index < self.pending.len() {
                     let mut item = self.pending.remove(index);
                     item.done = true;
+                    item.completed_at = Some(now);
                     item.expanded = false;
                     item.selected = false;
                     self.done.push(item);
@@ -96,6 +129,7 @@ impl TodoItems {
                 if index < self.done.len() {
                     let mut item = self.done.remove(index);
                     item.done = false;
+                    item.completed_at = None;
                     item.selected = false;
                     self.pending.push(item);
                 }
@@ -106,11 +140,144 @@ impl TodoItems {
     pub(super) fn to_vec(&self) -> Vec<Todo> {
         self.pending
             .iter()
+            .chain(self.hidden.iter())
             .chain(self.done.iter())
             .cloned()
             .collect()
     }
 
+    fn all(&self) -> impl Iterator<Item = &Todo> {
+        self.pending
+            .iter()
+            .chain(self.hidden.iter())
+            .chain(self.done.iter())
+    }
+
+    /// The id a new todo gets: one past the highest id in use, as on save.
+    pub(super) fn next_todo_id(&self) -> String {
+        let highest = self
+            .all()
+            .filter_map(|todo| todo.todo_id.as_deref().and_then(parse_todo_id))
+            .max()
+            .unwrap_or_default();
+        format_todo_id(highest.saturating_add(1))
+    }
+
+    fn open(&self) -> impl Iterator<Item = &Todo> {
+        self.pending
+            .iter()
+            .chain(self.hidden.iter().filter(|todo| !todo.done))
+    }
+
+    fn open_ids(&self) -> HashSet<&str> {
+        self.open()
+            .filter_map(|todo| todo.todo_id.as_deref())
+            .collect()
+    }
+
+    /// A todo is blocked while any todo it names in `blocked_by` is still open.
+    pub(super) fn is_blocked(&self, todo: &Todo) -> bool {
+        let open_ids = self.open_ids();
+        todo.blocked_by
+            .iter()
+            .any(|blocker| open_ids.contains(blocker.as_str()))
+    }
+
+    /// Ids of open todos that are currently blocked.
+    pub(super) fn blocked_ids(&self) -> HashSet<String> {
+        let open_ids = self.open_ids();
+        self.open()
+            .filter(|todo| {
+                todo.blocked_by
+                    .iter()
+                    .any(|blocker| open_ids.contains(blocker.as_str()))
+            })
+            .filter_map(|todo| todo.todo_id.clone())
+            .collect()
+    }
+
+    /// Open todos whose id is not in `blocked_ids`, but was blocked before.
+    pub(super) fn newly_unblocked(&self, previously_blocked: &HashSet<String>) -> Vec<&Todo> {
+        let still_blocked = self.blocked_ids();
+        self.open()
+            .filter(|todo| {
+                todo.todo_id.as_ref().is_some_and(|todo_id| {
+                    previously_blocked.contains(todo_id) && !still_blocked.contains(todo_id)
+                })
+            })
+            .collect()
+    }
+
+    /// Check that every `blocked_by` reference names a known todo and that the
+    /// references do not form a cycle.
+    pub(super) fn validate_dependencies(&self) -> Result<()> {
+        let known_ids: HashSet<&str> = self
+            .all()
+            .filter_map(|todo| todo.todo_id.as_deref())
+            .collect();
+        for todo in self.all().filter(|todo| todo.todo_id.is_none()) {
+            if let Some(missing) = todo
+                .blocked_by
+                .iter()
+                .find(|blocker| !known_ids.contains(blocker.as_str()))
+            {
+                return Err(JugglerError::config(format!(
+                    "Todo '{}' is blocked by unknown todo '{missing}'",
+                    todo.title
+                )));
+            }
+        }
+
+        validate_blocked_by(self.all().filter_map(|todo| {
+            todo.todo_id
+                .as_deref()
+                .map(|todo_id| (todo_id, todo.blocked_by.as_slice()))
+        }))
+    }
+
+    /// Park blocked todos while `hide_blocked` is set and todos outside
+    /// `filter`, and bring back any that no longer qualify.
+    pub(super) fn refresh_hidden(&mut self) {
+        let blocked = self.blocked_ids();
+        let pending = std::mem::take(&mut self.pending);
+        let done = std::mem::take(&mut self.done);
+        let parked = std::mem::take(&mut self.hidden);
+        let is_hidden = |todo: &Todo| {
+            let is_blocked = todo
+                .todo_id
+                .as_ref()
+                .is_some_and(|todo_id| blocked.contains(todo_id));
+            (self.hide_blocked && is_blocked)
+                || self
+                    .filter
+                    .as_ref()
+                    .is_some_and(|filter| !filter.matches(todo, &blocked))
+        };
+
+        let (mut hidden, pending): (Vec<Todo>, Vec<Todo>) =
+            pending.into_iter().partition(|todo| is_hidden(todo));
+        let (hidden_done, done): (Vec<Todo>, Vec<Todo>) =
+            done.into_iter().partition(|todo| is_hidden(todo));
+        let (still_hidden, revealed): (Vec<Todo>, Vec<Todo>) =
+            parked.into_iter().partition(|todo| is_hidden(todo));
+
+        hidden.extend(hidden_done);
+        for todo in &mut hidden {
+            todo.selected = false;
+        }
+        self.pending = pending;
+        self.done = done;
+        self.hidden = still_hidden;
+        self.hidden.extend(hidden);
+        for todo in revealed {
+            self.push(todo);
+        }
+    }
+
+    pub(super) fn hidden_count(&self) -> usize {
+        self.hidden.len()
+    }
+
     pub(super) fn pending_iter(&self) -> impl Iterator<Item = (usize, &Todo)> {
         self.pending.iter().enumerate()
     }
@@ -255,11 +422,20 @@ pub(super) struct AppModel {
     pub(super) items: TodoItems,
     pub(super) ui_state: UiState,
     pub(super) mode: AppMode,
+    pub(super) field_schema: FieldSchema,
+    /// Timezone that all-day dues are interpreted in.
+    pub(super) timezone: Tz,
+    /// How close to its deadline a todo is flagged as due soon.
+    pub(super) due_soon: Duration,
+    /// One-shot footer message shown instead of the help line until the next action.
+    pub(super) status_message: Option<String>,
+    /// Active normal-mode bindings; the help line is built from them.
+    pub(super) keymap: Keymap,
 }
 
 impl AppModel {
     pub(super) fn new(items: Vec<Todo>) -> Self {
-        let items = TodoItems::new(items);
+        let items = TodoItems::new(items, Tz::UTC);
         let ui_state = UiState::new(items.pending_count());
         Self {
             exit: false,
@@ -267,6 +443,11 @@ impl AppModel {
             items,
             ui_state,
             mode: AppMode::Normal,
+            field_schema: FieldSchema::default(),
+            timezone: Tz::UTC,
+            due_soon: Duration::seconds(DUE_SOON_THRESHOLD_SECS),
+            status_message: None,
+            keymap: Keymap::default(),
         }
     }
 }
@@ -8,10 +8,13 @@
 //! used by prompt-driven scheduling, and contains due-date formatting helpers.
 
 use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
+use indexmap::IndexMap;
 use ratatui::style::Color;
 
-use crate::config::DUE_SOON_THRESHOLD_SECS;
-use crate::store::TodoItem;
+use crate::due::{Due, today};
+use crate::fields::FieldValue;
+use crate::store::{LogEntry, TodoItem};
 
 #[derive(Debug, Clone)]
 pub struct Todo {
@@ -20,33 +23,61 @@ pub struct Todo {
     pub expanded: bool,
     pub done: bool,
     pub selected: bool,
-    pub due_date: Option<DateTime<Utc>>,
+    pub due_date: Option<Due>,
     pub todo_id: Option<String>,
     pub google_task_id: Option<String>,
+    /// Ids of todos that must be done before this one can start.
+    pub blocked_by: Vec<String>,
+    /// Append-only progress notes, oldest first.
+    pub log: Vec<LogEntry>,
+    /// When the todo was first recorded; unknown for todos created before
+    /// juggler tracked it.
+    pub created_at: Option<DateTime<Utc>>,
+    /// When the todo was last marked done; cleared when it is reopened.
+    pub completed_at: Option<DateTime<Utc>>,
+    /// How many times the todo has been pushed later.
+    pub snooze_count: u32,
+    pub fields: IndexMap<String, FieldValue>,
+    /// Record keys juggler does not interpret, preserved across saves.
+    pub extra: toml::Table,
 }
 
 impl Todo {
-    pub fn format_relative_time(&self, now: DateTime<Utc>) -> Option<String> {
+    /// Compact due column text: calendar words or day counts for all-day dues,
+    /// a signed duration for timed ones.
+    pub fn format_relative_time(&self, now: DateTime<Utc>, tz: Tz) -> Option<String> {
         self.due_date.map(|due| {
-            let duration = due.signed_duration_since(now);
-            let time_str = format_duration_compact(duration);
+            let time_str = match due {
+                Due::Date(date) => match (date - today(now, tz)).num_days() {
+                    0 => "today".to_string(),
+                    1 => "tomorrow".to_string(),
+                    days => format!("{days}d"),
+                },
+                Due::At(instant) => format_duration_compact(instant.signed_duration_since(now)),
+            };
             format!("{time_str:>4}")
         })
     }
 
-    pub fn due_date_urgency(&self, now: DateTime<Utc>) -> Option<DueDateUrgency> {
-        self.due_date.map(|due| {
-            let duration = due.signed_duration_since(now);
-            let total_seconds = duration.num_seconds();
-
-            if total_seconds < 0 {
-                DueDateUrgency::Overdue
-            } else if total_seconds <= DUE_SOON_THRESHOLD_SECS {
-                DueDateUrgency::DueSoon
-            } else {
-                DueDateUrgency::Normal
-            }
-        })
+    /// Overdue past the deadline, due soon within `due_soon` of it.
+    pub fn due_date_urgency(
+        &self,
+        now: DateTime<Utc>,
+        tz: Tz,
+        due_soon: Duration,
+    ) -> Option<DueDateUrgency> {
+        self.due_date
+            .map(|due| DueDateUrgency::of(due, now, tz, due_soon))
+    }
+
+    /// Pending and past its deadline.
+    pub fn is_overdue(&self, now: DateTime<Utc>, tz: Tz) -> bool {
+        !self.done && self.seconds_until_due(now, tz).is_some_and(|secs| secs < 0)
+    }
+
+    fn seconds_until_due(&self, now: DateTime<Utc>, tz: Tz) -> Option<i64> {
+        self.due_date
+            .map(|due| due.deadline(tz).signed_duration_since(now).num_seconds())
     }
 
     pub fn has_comment(&self) -> bool {
@@ -55,6 +86,11 @@ impl Todo {
             .map(|c| !c.trim().is_empty())
             .unwrap_or(false)
     }
+
+    /// Whether expanding the todo reveals anything beyond the title line.
+    pub fn has_details(&self) -> bool {
+        self.has_comment() || !self.log.is_empty()
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -65,6 +101,17 @@ pub enum DueDateUrgency {
 }
 
 impl DueDateUrgency {
+    pub fn of(due: Due, now: DateTime<Utc>, tz: Tz, due_soon: Duration) -> Self {
+        let total_seconds = due.deadline(tz).signed_duration_since(now).num_seconds();
+        if total_seconds < 0 {
+            DueDateUrgency::Overdue
+        } else if total_seconds <= due_soon.num_seconds() {
+            DueDateUrgency::DueSoon
+        } else {
+            DueDateUrgency::Normal
+        }
+    }
+
     pub fn color(&self) -> Color {
         match self {
             DueDateUrgency::Overdue => Color::Red,
@@ -85,6 +132,13 @@ impl From<TodoItem> for Todo {
             due_date: item.due_date,
             todo_id: item.todo_id,
             google_task_id: item.google_task_id,
+            blocked_by: item.blocked_by,
+            log: item.log,
+            created_at: item.created_at,
+            completed_at: item.completed_at,
+            snooze_count: item.snooze_count,
+            fields: item.fields,
+            extra: item.extra,
         }
     }
 }
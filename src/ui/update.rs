@@ -5,10 +5,19 @@
 //! the next state and optionally requests a single side effect.
 //!
 //! Reducer actions cover navigation, completion toggles, due-date adjustments,
-//! selection semantics, and prompt input handling. External work such as editor
-//! launch is represented as `SideEffect` data, not executed inline.
+//! selection semantics, blocked-todo visibility, the filter query, progress
+//! notes, and prompt input handling. External work such as editor launch is represented as
+//! `SideEffect` data, not executed inline.
+
+use std::collections::HashSet;
 
 use chrono::{DateTime, Duration, Utc};
+use indexmap::IndexMap;
+
+use crate::due::{Due, today};
+use crate::error::Result;
+use crate::query::Query;
+use crate::store::LogEntry;
 
 use super::keymap::Action as NormalAction;
 use super::model::{AppMode, AppModel, PromptAction, PromptOverlay, Section};
@@ -29,6 +38,12 @@ pub(super) enum Action {
     ApplyCreatedItem {
         created_item: Todo,
     },
+    /// Snooze by a whole number of days, as the fixed-offset keys do.
+    Snooze {
+        days: i64,
+    },
+    SetDue(Option<Due>),
+    CloseStats,
 }
 
 #[derive(Debug, Clone)]
@@ -48,6 +63,7 @@ pub(super) fn update(
     action: Action,
     now: DateTime<Utc>,
 ) -> Option<SideEffect> {
+    model.status_message = None;
     match action {
         Action::Normal(action) => update_normal_action(model, action, now),
         Action::PromptSubmit => {
@@ -75,11 +91,23 @@ pub(super) fn update(
             index,
             updated_item,
         } => {
-            apply_edited_item(model, section, index, updated_item);
+            apply_edited_item(model, section, index, updated_item, now);
             None
         }
         Action::ApplyCreatedItem { created_item } => {
-            apply_created_item(model, created_item);
+            apply_created_item(model, created_item, now);
+            None
+        }
+        Action::Snooze { days } => {
+            snooze(model, days, now);
+            None
+        }
+        Action::SetDue(due) => {
+            apply_to_selected_or_cursor(model, |item| item.due_date = due);
+            None
+        }
+        Action::CloseStats => {
+            model.mode = AppMode::Normal;
             None
         }
     }
@@ -119,7 +147,7 @@ fn update_normal_action(
             None
         }
         NormalAction::ToggleDone => {
-            toggle_done(model);
+            toggle_done(model, now);
             None
         }
         NormalAction::Edit => request_edit(model),
@@ -130,19 +158,19 @@ fn update_normal_action(
             None
         }
         NormalAction::SnoozeDay => {
-            snooze(model, Duration::days(1), now);
+            snooze(model, 1, now);
             None
         }
         NormalAction::UnsnoozeDay => {
-            snooze(model, Duration::days(-1), now);
+            snooze(model, -1, now);
             None
         }
         NormalAction::PostponeWeek => {
-            snooze(model, Duration::days(7), now);
+            snooze(model, 7, now);
             None
         }
         NormalAction::PreponeWeek => {
-            snooze(model, Duration::days(-7), now);
+            snooze(model, -7, now);
             None
         }
         NormalAction::Create => Some(SideEffect::CreateItem {
@@ -152,6 +180,22 @@ fn update_normal_action(
             open_custom_delay_prompt(model);
             None
         }
+        NormalAction::ToggleHideBlocked => {
+            toggle_hide_blocked(model);
+            None
+        }
+        NormalAction::Filter => {
+            open_filter_prompt(model);
+            None
+        }
+        NormalAction::AddNote => {
+            open_add_note_prompt(model);
+            None
+        }
+        NormalAction::ShowStats => {
+            model.mode = AppMode::Stats;
+            None
+        }
     }
 }
 
@@ -179,6 +223,13 @@ fn empty_todo() -> Todo {
         due_date: None,
         todo_id: None,
         google_task_id: None,
+        blocked_by: Vec::new(),
+        log: Vec::new(),
+        created_at: None,
+        completed_at: None,
+        snooze_count: 0,
+        fields: IndexMap::new(),
+        extra: toml::Table::new(),
     }
 }
 
@@ -188,27 +239,59 @@ fn sorted_indices<I: Iterator<Item = usize>>(iter: I) -> Vec<usize> {
     v
 }
 
-fn toggle_done(model: &mut AppModel) {
+fn toggle_done(model: &mut AppModel, now: DateTime<Utc>) {
+    let previously_blocked = model.items.blocked_ids();
     let pending_selected = sorted_indices(model.items.pending_selected_indices());
     let done_selected = sorted_indices(model.items.done_selected_indices());
 
     if !pending_selected.is_empty() || !done_selected.is_empty() {
         for i in pending_selected.into_iter().rev() {
-            model.items.toggle_done(Section::Pending, i);
+            model.items.toggle_done(Section::Pending, i, now);
         }
         for i in done_selected.into_iter().rev() {
-            model.items.toggle_done(Section::Done, i);
+            model.items.toggle_done(Section::Done, i, now);
         }
     } else {
         let section = model.ui_state.current_section;
         let index = model.ui_state.current_index();
-        model.items.toggle_done(section, index);
+        model.items.toggle_done(section, index, now);
     }
 
     adjust_indices_after_toggle(model);
+    announce_unblocked(model, &previously_blocked);
+}
+
+fn toggle_hide_blocked(model: &mut AppModel) {
+    model.items.hide_blocked = !model.items.hide_blocked;
+    adjust_indices_after_toggle(model);
+    model.status_message = Some(if model.items.hide_blocked {
+        format!("Hiding {} blocked todo(s)", model.items.blocked_ids().len())
+    } else {
+        "Showing blocked todos".to_string()
+    });
+}
+
+/// Tell the user which todos the last change unblocked, if any.
+fn announce_unblocked(model: &mut AppModel, previously_blocked: &HashSet<String>) {
+    let unblocked: Vec<String> = model
+        .items
+        .newly_unblocked(previously_blocked)
+        .into_iter()
+        .map(|todo| {
+            format!(
+                "{} {}",
+                todo.todo_id.as_deref().unwrap_or_default(),
+                todo.title
+            )
+        })
+        .collect();
+    if !unblocked.is_empty() {
+        model.status_message = Some(format!("Unblocked: {}", unblocked.join(", ")));
+    }
 }
 
 fn adjust_indices_after_toggle(model: &mut AppModel) {
+    model.items.refresh_hidden();
     model
         .ui_state
         .adjust_indices(model.items.pending_count(), model.items.done_count());
@@ -237,25 +320,32 @@ where
     }
 }
 
-fn snooze(model: &mut AppModel, duration: Duration, now: DateTime<Utc>) {
+/// Move dues by whole calendar days. Overdue items are snoozed relative to now
+/// (or today, for all-day dues) rather than to their stale due. Pushing a due
+/// later counts towards the todo's `snooze_count`.
+fn snooze(model: &mut AppModel, days: i64, now: DateTime<Utc>) {
+    let tz = model.timezone;
     apply_to_selected_or_cursor(model, |item| {
-        let new_due = if let Some(current_due) = item.due_date {
-            if current_due <= now {
-                now + duration
-            } else {
-                current_due + duration
-            }
-        } else {
-            now + duration
+        let base = match item.due_date {
+            Some(Due::Date(date)) if date < today(now, tz) => Due::Date(today(now, tz)),
+            Some(Due::At(instant)) if instant <= now => Due::At(now),
+            Some(due) => due,
+            None => Due::At(now),
         };
-        item.due_date = Some(new_due);
+        item.due_date = Some(base.shift_days(days, tz));
+        if days > 0 {
+            item.snooze_count += 1;
+        }
     });
 }
 
 fn delay_from_now(model: &mut AppModel, duration: Duration, now: DateTime<Utc>) {
     let target_due = now + duration;
     apply_to_selected_or_cursor(model, |item| {
-        item.due_date = Some(target_due);
+        item.due_date = Some(Due::At(target_due));
+        if duration > Duration::zero() {
+            item.snooze_count += 1;
+        }
     });
 }
 
@@ -267,6 +357,65 @@ fn open_custom_delay_prompt(model: &mut AppModel) {
     });
 }
 
+fn open_add_note_prompt(model: &mut AppModel) {
+    model.mode = AppMode::Prompt(PromptOverlay {
+        message: "Note: ".to_string(),
+        buffer: String::new(),
+        action: PromptAction::AddNote,
+    });
+}
+
+/// Prefilled with the active query so it can be refined or cleared.
+fn open_filter_prompt(model: &mut AppModel) {
+    model.mode = AppMode::Prompt(PromptOverlay {
+        message: "Filter (empty shows all): ".to_string(),
+        buffer: model
+            .items
+            .filter
+            .as_ref()
+            .map(|filter| filter.source().to_string())
+            .unwrap_or_default(),
+        action: PromptAction::Filter,
+    });
+}
+
+/// Show only todos matching `input`; an empty query clears the filter. A
+/// query that does not parse leaves the current filter in place.
+fn apply_filter(model: &mut AppModel, input: &str, now: DateTime<Utc>) {
+    let input = input.trim();
+    if input.is_empty() {
+        model.items.filter = None;
+        adjust_indices_after_toggle(model);
+        model.status_message = Some("Filter cleared".to_string());
+        return;
+    }
+    match Query::parse(input, &model.field_schema, now, model.timezone) {
+        Ok(query) => {
+            model.items.filter = Some(query);
+            adjust_indices_after_toggle(model);
+            model.status_message = Some(format!(
+                "Showing {} matching todo(s)",
+                model.items.pending_count() + model.items.done_count()
+            ));
+        }
+        Err(e) => model.status_message = Some(format!("Filter rejected: {e}")),
+    }
+}
+
+/// Append a timestamped log entry; the log is never rewritten in place.
+fn add_note(model: &mut AppModel, text: &str, now: DateTime<Utc>) {
+    let text = text.trim();
+    if text.is_empty() {
+        return;
+    }
+    apply_to_selected_or_cursor(model, |item| {
+        item.log.push(LogEntry {
+            at: now,
+            text: text.to_string(),
+        });
+    });
+}
+
 fn submit_prompt(model: &mut AppModel, now: DateTime<Utc>) {
     if let AppMode::Prompt(overlay) = &model.mode {
         let action = overlay.action;
@@ -278,15 +427,58 @@ fn submit_prompt(model: &mut AppModel, now: DateTime<Utc>) {
                     delay_from_now(model, duration, now);
                 }
             }
+            PromptAction::AddNote => add_note(model, &buffer, now),
+            PromptAction::Filter => apply_filter(model, &buffer, now),
         }
     }
 }
 
-fn apply_edited_item(model: &mut AppModel, section: Section, index: usize, updated_item: Todo) {
+fn apply_edited_item(
+    model: &mut AppModel,
+    section: Section,
+    index: usize,
+    updated_item: Todo,
+    now: DateTime<Utc>,
+) {
     if updated_item.title.trim().is_empty() {
         return;
     }
 
+    if let Err(e) = try_apply_edited_item(model, section, index, updated_item, now) {
+        model.status_message = Some(format!("Edit rejected: {e}"));
+    }
+}
+
+/// Replace the item at `section`/`index`, leaving the model untouched if the
+/// result breaks the dependency graph.
+pub(super) fn try_apply_edited_item(
+    model: &mut AppModel,
+    section: Section,
+    index: usize,
+    updated_item: Todo,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    let previous_items = model.items.clone();
+    let previously_blocked = model.items.blocked_ids();
+    replace_item(model, section, index, updated_item, now);
+
+    if let Err(e) = model.items.validate_dependencies() {
+        model.items = previous_items;
+        adjust_indices_after_toggle(model);
+        return Err(e);
+    }
+    adjust_indices_after_toggle(model);
+    announce_unblocked(model, &previously_blocked);
+    Ok(())
+}
+
+fn replace_item(
+    model: &mut AppModel,
+    section: Section,
+    index: usize,
+    mut updated_item: Todo,
+    now: DateTime<Utc>,
+) {
     let Some(done_changed) = model
         .items
         .get(section, index)
@@ -296,24 +488,42 @@ fn apply_edited_item(model: &mut AppModel, section: Section, index: usize, updat
     };
 
     if done_changed {
+        updated_item.completed_at = updated_item.done.then_some(now);
         let _removed = match section {
             Section::Pending => model.items.pending.remove(index),
             Section::Done => model.items.done.remove(index),
         };
         model.items.push(updated_item);
-        adjust_indices_after_toggle(model);
     } else if let Some(existing) = model.items.get_mut(section, index) {
         *existing = updated_item;
     }
 }
 
-fn apply_created_item(model: &mut AppModel, created_item: Todo) {
+fn apply_created_item(model: &mut AppModel, mut created_item: Todo, now: DateTime<Utc>) {
     if created_item.title.trim().is_empty() {
         return;
     }
+    created_item.created_at.get_or_insert(now);
+    // Assign the id now rather than on save so hooks can refer to the todo.
+    if created_item.todo_id.is_none() {
+        created_item.todo_id = Some(model.items.next_todo_id());
+    }
+    if created_item.done {
+        created_item.completed_at.get_or_insert(now);
+    }
 
     let is_done = created_item.done;
     model.items.push(created_item);
+    if let Err(e) = model.items.validate_dependencies() {
+        if is_done {
+            model.items.done.pop();
+        } else {
+            model.items.pending.pop();
+        }
+        model.status_message = Some(format!("New todo rejected: {e}"));
+        return;
+    }
+    model.items.refresh_hidden();
 
     if !is_done {
         model.ui_state.current_section = Section::Pending;
@@ -339,6 +549,13 @@ mod tests {
             due_date: None,
             todo_id: None,
             google_task_id: None,
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra: toml::Table::new(),
         }
     }
 
@@ -454,9 +671,9 @@ mod tests {
         let past = base - Duration::days(2);
         let future = base + Duration::days(3);
         let mut overdue = selected_todo("overdue");
-        overdue.due_date = Some(past);
+        overdue.due_date = Some(Due::At(past));
         let mut upcoming = selected_todo("upcoming");
-        upcoming.due_date = Some(future);
+        upcoming.due_date = Some(Due::At(future));
         let mut none = selected_todo("none");
         none.due_date = None;
 
@@ -488,12 +705,31 @@ mod tests {
             .find(|t| t.title == "untouched")
             .unwrap();
 
-        assert_eq!(overdue.due_date, Some(base + Duration::days(1)));
-        assert_eq!(upcoming.due_date, Some(future + Duration::days(1)));
-        assert_eq!(none.due_date, Some(base + Duration::days(1)));
+        assert_eq!(overdue.due_date, Some(Due::At(base + Duration::days(1))));
+        assert_eq!(upcoming.due_date, Some(Due::At(future + Duration::days(1))));
+        assert_eq!(none.due_date, Some(Due::At(base + Duration::days(1))));
         assert_eq!(untouched.due_date, None);
     }
 
+    #[test]
+    fn snooze_moves_all_day_dues_by_calendar_days() {
+        let tz: chrono_tz::Tz = "America/New_York".parse().unwrap();
+        // 2025-03-08 20:00 in New York, the evening before DST starts.
+        let now = chrono::TimeZone::with_ymd_and_hms(&Utc, 2025, 3, 9, 1, 0, 0).unwrap();
+        let day = |d| Due::Date(chrono::NaiveDate::from_ymd_opt(2025, 3, d).unwrap());
+        let mut stale = selected_todo("stale");
+        stale.due_date = Some(day(1));
+        let mut upcoming = selected_todo("upcoming");
+        upcoming.due_date = Some(day(8));
+
+        let mut model = AppModel::new(vec![stale, upcoming]);
+        model.timezone = tz;
+        update(&mut model, Action::Normal(NormalAction::SnoozeDay), now);
+
+        assert_eq!(model.items.pending[0].due_date, Some(day(9)));
+        assert_eq!(model.items.pending[1].due_date, Some(day(9)));
+    }
+
     #[test]
     fn due_date_operations_keep_selection_state() {
         let base = Utc::now();
@@ -507,6 +743,42 @@ mod tests {
         assert!(model.items.pending[0].selected);
         assert!(model.items.pending[1].selected);
         assert!(!model.items.pending[2].selected);
+        assert_eq!(
+            model.items.pending[0].snooze_count, 2,
+            "only pushing a due later counts as a snooze"
+        );
+        assert_eq!(model.items.pending[2].snooze_count, 0);
+    }
+
+    #[test]
+    fn toggle_done_and_create_record_timestamps() {
+        let base = Utc::now();
+        let mut model = AppModel::new(vec![todo("a")]);
+
+        update(&mut model, Action::Normal(NormalAction::ToggleDone), base);
+        assert_eq!(model.items.done[0].completed_at, Some(base));
+        update(&mut model, Action::Normal(NormalAction::ToggleDone), base);
+        assert_eq!(model.items.pending[0].completed_at, None);
+
+        update(
+            &mut model,
+            Action::ApplyCreatedItem {
+                created_item: todo("new"),
+            },
+            base,
+        );
+        assert_eq!(model.items.pending[1].created_at, Some(base));
+    }
+
+    #[test]
+    fn stats_screen_opens_and_closes() {
+        let base = Utc::now();
+        let mut model = AppModel::new(vec![todo("a")]);
+
+        update(&mut model, Action::Normal(NormalAction::ShowStats), base);
+        assert_eq!(model.mode, AppMode::Stats);
+        update(&mut model, Action::CloseStats, base);
+        assert_eq!(model.mode, AppMode::Normal);
     }
 
     #[test]
@@ -524,7 +796,7 @@ mod tests {
         assert!(matches!(model.mode, AppMode::Normal));
         assert_eq!(
             model.items.pending[0].due_date,
-            Some(base + Duration::days(1))
+            Some(Due::At(base + Duration::days(1)))
         );
 
         update(&mut model, Action::Normal(NormalAction::CustomDelay), base);
@@ -533,6 +805,26 @@ mod tests {
         assert!(matches!(model.mode, AppMode::Normal));
     }
 
+    #[test]
+    fn add_note_prompt_appends_timestamped_log_entry() {
+        let base = Utc::now();
+        let mut model = AppModel::new(vec![todo("a")]);
+
+        for text in ["first", "  ", "second"] {
+            update(&mut model, Action::Normal(NormalAction::AddNote), base);
+            for c in text.chars() {
+                update(&mut model, Action::PromptInput(c), base);
+            }
+            update(&mut model, Action::PromptSubmit, base);
+        }
+
+        let log = &model.items.pending[0].log;
+        assert_eq!(log.len(), 2, "blank notes are ignored");
+        assert_eq!(log[0].text, "first");
+        assert_eq!(log[1].text, "second");
+        assert_eq!(log[1].at, base);
+    }
+
     #[test]
     fn prompt_backspace_updates_buffer() {
         let base = Utc::now();
@@ -648,4 +940,127 @@ mod tests {
         assert_eq!(model.items.pending_count(), 1);
         assert_eq!(model.items.pending[0].title, "existing");
     }
+
+    fn todo_with_id(todo_id: &str, title: &str, blocked_by: &[&str]) -> Todo {
+        let mut item = todo(title);
+        item.todo_id = Some(todo_id.to_string());
+        item.blocked_by = blocked_by.iter().map(|id| id.to_string()).collect();
+        item
+    }
+
+    #[test]
+    fn completing_a_blocker_reports_unblocked_todos() {
+        let base = Utc::now();
+        let mut model = AppModel::new(vec![
+            todo_with_id("T1", "blocker", &[]),
+            todo_with_id("T2", "waiting", &["T1"]),
+        ]);
+
+        update(&mut model, Action::Normal(NormalAction::ToggleDone), base);
+
+        assert_eq!(
+            model.status_message.as_deref(),
+            Some("Unblocked: T2 waiting")
+        );
+
+        update(&mut model, Action::Normal(NormalAction::NextItem), base);
+        assert_eq!(model.status_message, None);
+    }
+
+    #[test]
+    fn hide_blocked_parks_blocked_todos_until_unblocked() {
+        let base = Utc::now();
+        let mut model = AppModel::new(vec![
+            todo_with_id("T1", "blocker", &[]),
+            todo_with_id("T2", "waiting", &["T1"]),
+        ]);
+
+        update(
+            &mut model,
+            Action::Normal(NormalAction::ToggleHideBlocked),
+            base,
+        );
+        assert_eq!(model.items.pending_count(), 1);
+        assert_eq!(model.items.hidden_count(), 1);
+        assert_eq!(model.items.to_vec().len(), 2);
+
+        update(&mut model, Action::Normal(NormalAction::ToggleDone), base);
+        assert_eq!(model.items.hidden_count(), 0);
+        assert_eq!(model.items.pending[0].title, "waiting");
+    }
+
+    #[test]
+    fn filter_prompt_hides_non_matching_todos_until_cleared() {
+        let base = Utc::now();
+        let mut done = todo_with_id("T3", "deploy docs", &[]);
+        done.done = true;
+        let mut model = AppModel::new(vec![
+            todo_with_id("T1", "deploy api", &[]),
+            todo_with_id("T2", "write notes", &[]),
+            done,
+        ]);
+        let submit = |model: &mut AppModel, query: &str| {
+            update(model, Action::Normal(NormalAction::Filter), base);
+            if let AppMode::Prompt(overlay) = &mut model.mode {
+                overlay.buffer.clear();
+            }
+            for c in query.chars() {
+                update(model, Action::PromptInput(c), base);
+            }
+            update(model, Action::PromptSubmit, base);
+        };
+
+        submit(&mut model, "\"deploy\" in title");
+        assert_eq!(model.items.pending_count(), 1);
+        assert_eq!(model.items.done_count(), 1);
+        assert_eq!(model.items.hidden_count(), 1);
+
+        update(&mut model, Action::Normal(NormalAction::Filter), base);
+        assert!(matches!(
+            &model.mode,
+            AppMode::Prompt(overlay) if overlay.buffer == "\"deploy\" in title"
+        ));
+        update(&mut model, Action::PromptCancel, base);
+
+        submit(&mut model, "done and");
+        assert_eq!(
+            model.status_message.as_deref(),
+            Some("Filter rejected: expected a condition but found end of query at column 9")
+        );
+        assert_eq!(model.items.hidden_count(), 1);
+
+        submit(&mut model, "");
+        assert_eq!(model.items.pending_count(), 2);
+        assert_eq!(model.items.done_count(), 1);
+        assert_eq!(model.items.to_vec().len(), 3);
+    }
+
+    #[test]
+    fn apply_edited_item_rejects_dependency_cycles() {
+        let base = Utc::now();
+        let mut model = AppModel::new(vec![
+            todo_with_id("T1", "first", &[]),
+            todo_with_id("T2", "second", &["T1"]),
+        ]);
+
+        update(
+            &mut model,
+            Action::ApplyEditedItem {
+                section: Section::Pending,
+                index: 0,
+                updated_item: todo_with_id("T1", "first", &["T2"]),
+            },
+            base,
+        );
+
+        assert!(model.items.pending[0].blocked_by.is_empty());
+        assert!(
+            model
+                .status_message
+                .as_deref()
+                .is_some_and(|message| message.contains("T1 -> T2 -> T1")),
+            "{:?}",
+            model.status_message
+        );
+    }
 }
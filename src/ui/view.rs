@@ -5,7 +5,9 @@
 //! footer rendering, but never mutates model state.
 //!
 //! Rendering is structured around the pending/done partition and cursor/focus
-//! state in `AppModel`, with prompt mode rendered in the footer area.
+//! state in `AppModel`, with prompt mode and one-shot status messages rendered
+//! in the footer area. Blocked todos are dimmed and marked with a lock. The
+//! stats screen replaces both sections with sparklines and a summary.
 
 use chrono::{DateTime, Utc};
 use ratatui::{
@@ -13,15 +15,18 @@ use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Span, Text},
-    widgets::{Block, Borders, List, ListState, Paragraph},
+    widgets::{Block, Borders, List, ListState, Paragraph, Sparkline},
 };
 
-use crate::config::COMMENT_INDENT;
+use crate::config::{COMMENT_INDENT, STATS_SCREEN_DAYS};
+use crate::stats::{StatsPeriod, compute_stats};
 
-use super::keymap::help_text;
 use super::model::{AppMode, AppModel, Section};
 use super::widgets::PromptWidget;
 
+const BLOCKED_MARKER: &str = "🔒 ";
+const LOG_TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+
 pub(super) fn draw(frame: &mut Frame, model: &AppModel, now: DateTime<Utc>) {
     let area = frame.area();
     let chunks = Layout::default()
@@ -32,6 +37,12 @@ pub(super) fn draw(frame: &mut Frame, model: &AppModel, now: DateTime<Utc>) {
     let main_area = chunks[0];
     let help_area = chunks[1];
 
+    if model.mode == AppMode::Stats {
+        render_stats_screen(frame, main_area, model, now);
+        render_help_or_prompt(frame, help_area, model);
+        return;
+    }
+
     let sections = Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Percentage(75), Constraint::Percentage(25)])
@@ -67,7 +78,12 @@ fn render_pending_section(model: &AppModel, now: DateTime<Utc>) -> List<'_> {
         })
         .collect();
 
-    List::new(pending_items).block(Block::default().title("Pending").borders(Borders::ALL))
+    let title = match (&model.items.filter, model.items.hidden_count()) {
+        (None, 0) => "Pending".to_string(),
+        (None, hidden) => format!("Pending ({hidden} blocked hidden)"),
+        (Some(filter), hidden) => format!("Pending [{}] ({hidden} hidden)", filter.source()),
+    };
+    List::new(pending_items).block(Block::default().title(title).borders(Borders::ALL))
 }
 
 fn render_done_section(model: &AppModel, now: DateTime<Utc>) -> List<'_> {
@@ -88,15 +104,80 @@ fn render_done_section(model: &AppModel, now: DateTime<Utc>) -> List<'_> {
     List::new(done_items).block(Block::default().title("Done").borders(Borders::ALL))
 }
 
+fn render_stats_screen(frame: &mut Frame, area: Rect, model: &AppModel, now: DateTime<Utc>) {
+    let stats = compute_stats(
+        &model.items.to_vec(),
+        StatsPeriod::Day,
+        STATS_SCREEN_DAYS,
+        now,
+        model.timezone,
+    );
+    let summary = stats.summary_lines();
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(6),
+            Constraint::Length(6),
+            Constraint::Min(summary.len() as u16 + 2),
+        ])
+        .split(area);
+
+    let created: Vec<u64> = stats
+        .periods
+        .iter()
+        .map(|period| period.created as u64)
+        .collect();
+    let completed: Vec<u64> = stats
+        .periods
+        .iter()
+        .map(|period| period.completed as u64)
+        .collect();
+    let heading = stats.periods_heading();
+    frame.render_widget(
+        Sparkline::default()
+            .block(
+                Block::default()
+                    .title(format!("Created ({heading})"))
+                    .borders(Borders::ALL),
+            )
+            .data(&created)
+            .style(Style::default().fg(Color::Cyan)),
+        rows[0],
+    );
+    frame.render_widget(
+        Sparkline::default()
+            .block(Block::default().title("Completed").borders(Borders::ALL))
+            .data(&completed)
+            .style(Style::default().fg(Color::Green)),
+        rows[1],
+    );
+    frame.render_widget(
+        Paragraph::new(summary.join("\n"))
+            .block(Block::default().title("Summary").borders(Borders::ALL)),
+        rows[2],
+    );
+}
+
 fn render_help_or_prompt(frame: &mut Frame, area: Rect, model: &AppModel) {
     match &model.mode {
+        AppMode::Stats => {
+            frame.render_widget(
+                Paragraph::new("Press any key to return.")
+                    .block(Block::default().borders(Borders::TOP)),
+                area,
+            );
+        }
         AppMode::Prompt(prompt) => {
             frame.render_widget(PromptWidget::new(&prompt.message, &prompt.buffer), area);
         }
         AppMode::Normal => {
-            let help_widget =
-                Paragraph::new(help_text()).block(Block::default().borders(Borders::TOP));
-            frame.render_widget(help_widget, area);
+            let footer = match &model.status_message {
+                Some(message) => {
+                    Paragraph::new(message.as_str()).style(Style::default().fg(Color::Green))
+                }
+                None => Paragraph::new(model.keymap.help_text()),
+            };
+            frame.render_widget(footer.block(Block::default().borders(Borders::TOP)), area);
         }
     }
 }
@@ -124,9 +205,9 @@ pub(super) fn display_text(
     first_line_spans.push(Span::raw(cursor_prefix));
     first_line_spans.push(Span::raw(status_box));
 
-    if let Some(relative_time) = todo.format_relative_time(now) {
+    if let Some(relative_time) = todo.format_relative_time(now, model.timezone) {
         let color = todo
-            .due_date_urgency(now)
+            .due_date_urgency(now, model.timezone, model.due_soon)
             .map(|u| u.color())
             .unwrap_or(Color::White);
         first_line_spans.push(Span::styled(
@@ -135,17 +216,30 @@ pub(super) fn display_text(
         ));
     }
 
-    if is_cursored {
+    for column in model.field_schema.columns() {
+        let width = column_width(model, column);
+        let value = todo
+            .fields
+            .get(column)
+            .map(ToString::to_string)
+            .unwrap_or_default();
         first_line_spans.push(Span::styled(
-            &todo.title,
-            Style::default().add_modifier(Modifier::BOLD),
+            format!("{value:<width$} "),
+            Style::default().fg(Color::Cyan),
         ));
-    } else {
-        first_line_spans.push(Span::raw(&todo.title));
     }
 
-    let has_comment = todo.has_comment();
-    if has_comment {
+    let mut title_style = Style::default();
+    if !todo.done && model.items.is_blocked(todo) {
+        title_style = title_style.fg(Color::DarkGray).add_modifier(Modifier::DIM);
+        first_line_spans.push(Span::styled(BLOCKED_MARKER, title_style));
+    }
+    if is_cursored {
+        title_style = title_style.add_modifier(Modifier::BOLD);
+    }
+    first_line_spans.push(Span::styled(&todo.title, title_style));
+
+    if todo.has_details() {
         if todo.expanded {
             first_line_spans.push(Span::raw(" >>>"));
         } else {
@@ -154,14 +248,26 @@ pub(super) fn display_text(
     }
 
     let mut lines = vec![ratatui::text::Line::from(first_line_spans)];
-    if todo.expanded
-        && has_comment
-        && let Some(comment) = &todo.comment
-    {
-        for line in comment.lines() {
+    if todo.expanded {
+        if todo.has_comment()
+            && let Some(comment) = &todo.comment
+        {
+            for line in comment.lines() {
+                lines.push(ratatui::text::Line::from(vec![
+                    Span::raw(COMMENT_INDENT),
+                    Span::raw(line),
+                ]));
+            }
+        }
+        for entry in &todo.log {
+            let stamp = entry
+                .at
+                .with_timezone(&model.timezone)
+                .format(LOG_TIME_FORMAT);
             lines.push(ratatui::text::Line::from(vec![
                 Span::raw(COMMENT_INDENT),
-                Span::raw(line),
+                Span::styled(format!("{stamp} "), Style::default().fg(Color::DarkGray)),
+                Span::raw(&entry.text),
             ]));
         }
     }
@@ -169,6 +275,18 @@ pub(super) fn display_text(
     Text::from(lines)
 }
 
+/// Widest rendered value of a column field across both sections.
+fn column_width(model: &AppModel, column: &str) -> usize {
+    model
+        .items
+        .pending_iter()
+        .chain(model.items.done_iter())
+        .filter_map(|(_, todo)| todo.fields.get(column))
+        .map(|value| value.to_string().chars().count())
+        .max()
+        .unwrap_or(0)
+}
+
 #[cfg(test)]
 mod tests {
     use chrono::{Duration, Utc};
@@ -178,7 +296,12 @@ mod tests {
         text::{Span, Text},
     };
 
+    use indexmap::IndexMap;
+
     use super::*;
+    use crate::due::Due;
+    use crate::fields::{FieldSchema, FieldValue};
+    use crate::store::LogEntry;
     use crate::ui::model::AppModel;
     use crate::ui::todo::Todo;
 
@@ -207,6 +330,13 @@ mod tests {
             due_date: None,
             todo_id: None,
             google_task_id: None,
+            blocked_by: Vec::new(),
+            log: Vec::new(),
+            created_at: None,
+            completed_at: None,
+            snooze_count: 0,
+            fields: IndexMap::new(),
+            extra: toml::Table::new(),
         }
     }
 
@@ -228,11 +358,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn display_text_lists_log_entries_when_expanded() {
+        let mut item = todo("outage");
+        item.log = vec![LogEntry {
+            at: chrono::TimeZone::with_ymd_and_hms(&Utc, 2025, 1, 2, 9, 30, 0).unwrap(),
+            text: "Opened ticket".to_string(),
+        }];
+        let mut model = AppModel::new(vec![item]);
+        model.timezone = chrono_tz::Tz::Asia__Tokyo;
+
+        assert_eq!(
+            text_to_string(&display_text(&model, Section::Pending, 0, Utc::now())),
+            "▶ [ ] outage (...)"
+        );
+
+        model.items.pending[0].expanded = true;
+        assert_eq!(
+            text_to_string(&display_text(&model, Section::Pending, 0, Utc::now())),
+            "▶ [ ] outage >>>\n           2025-01-02 18:30 Opened ticket"
+        );
+    }
+
     #[test]
     fn display_text_shows_relative_due_time() {
         let base = Utc::now();
         let mut item = todo("future task");
-        item.due_date = Some(base + Duration::hours(50));
+        item.due_date = Some(Due::At(base + Duration::hours(50)));
         let model = AppModel::new(vec![item]);
 
         assert_eq!(
@@ -241,6 +393,104 @@ mod tests {
         );
     }
 
+    #[test]
+    fn display_text_names_all_day_dues_by_local_day() {
+        let tz: chrono_tz::Tz = "Asia/Tokyo".parse().unwrap();
+        // 2025-01-01 20:00 UTC is already 2025-01-02 in Tokyo.
+        let now = chrono::TimeZone::with_ymd_and_hms(&Utc, 2025, 1, 1, 20, 0, 0).unwrap();
+        let day = |d| Due::Date(chrono::NaiveDate::from_ymd_opt(2025, 1, d).unwrap());
+        let mut today = todo("today");
+        today.due_date = Some(day(2));
+        let mut tomorrow = todo("tomorrow");
+        tomorrow.due_date = Some(day(3));
+        let mut overdue = todo("overdue");
+        overdue.due_date = Some(day(1));
+        let mut model = AppModel::new(vec![today, tomorrow, overdue]);
+        model.timezone = tz;
+
+        let rendered: Vec<String> = (0..3)
+            .map(|i| text_to_string(&display_text(&model, Section::Pending, i, now)))
+            .collect();
+        assert!(rendered.iter().any(|line| line.ends_with("today today")));
+        assert!(
+            rendered
+                .iter()
+                .any(|line| line.ends_with("tomorrow tomorrow"))
+        );
+        assert!(rendered.iter().any(|line| line.ends_with("  -1d overdue")));
+
+        let overdue = model
+            .items
+            .pending
+            .iter()
+            .find(|t| t.title == "overdue")
+            .unwrap();
+        let today = model
+            .items
+            .pending
+            .iter()
+            .find(|t| t.title == "today")
+            .unwrap();
+        assert_eq!(
+            overdue.due_date_urgency(now, tz, model.due_soon),
+            Some(crate::ui::todo::DueDateUrgency::Overdue)
+        );
+        assert_eq!(
+            today.due_date_urgency(now, tz, model.due_soon),
+            Some(crate::ui::todo::DueDateUrgency::DueSoon)
+        );
+    }
+
+    #[test]
+    fn display_text_aligns_custom_field_columns() {
+        let schema: toml::Value = toml::from_str(
+            r#"
+            priority = { type = "enum", values = ["high", "low"], column = true }
+            owner = { type = "string" }
+            "#,
+        )
+        .unwrap();
+        let mut first = todo("a");
+        first.fields.insert(
+            "priority".to_string(),
+            FieldValue::String("high".to_string()),
+        );
+        first
+            .fields
+            .insert("owner".to_string(), FieldValue::String("sam".to_string()));
+        let second = todo("b");
+        let mut model = AppModel::new(vec![first, second]);
+        model.field_schema = FieldSchema::from_toml(Some(&schema)).unwrap();
+
+        assert_eq!(
+            text_to_string(&display_text(&model, Section::Pending, 0, Utc::now())),
+            "▶ [ ] high a"
+        );
+        assert_eq!(
+            text_to_string(&display_text(&model, Section::Pending, 1, Utc::now())),
+            "  [ ]      b"
+        );
+    }
+
+    #[test]
+    fn display_text_marks_blocked_items_with_lock() {
+        let mut blocker = todo("blocker");
+        blocker.todo_id = Some("T1".to_string());
+        let mut waiting = todo("waiting");
+        waiting.todo_id = Some("T2".to_string());
+        waiting.blocked_by = vec!["T1".to_string()];
+        let model = AppModel::new(vec![blocker, waiting]);
+
+        let text = display_text(&model, Section::Pending, 1, Utc::now());
+        assert_eq!(text_to_string(&text), "  [ ] 🔒 waiting");
+        assert!(
+            text.lines[0].spans[2]
+                .style
+                .add_modifier
+                .contains(Modifier::DIM)
+        );
+    }
+
     #[test]
     fn display_text_marks_selected_items() {
         let mut item = todo("first");
@@ -254,11 +504,11 @@ mod tests {
 
     #[test]
     fn draw_renders_help_footer_in_normal_mode() {
-        let help = help_text();
+        let model = AppModel::new(Vec::new());
+        let help = model.keymap.help_text();
         let width = (help.len() as u16).saturating_add(2);
         let backend = TestBackend::new(width, 10);
         let mut terminal = Terminal::new(backend).unwrap();
-        let model = AppModel::new(Vec::new());
 
         terminal
             .draw(|frame| draw(frame, &model, Utc::now()))
@@ -293,4 +543,36 @@ mod tests {
         });
         assert!(found);
     }
+
+    #[test]
+    fn draw_renders_stats_screen_in_stats_mode() {
+        let backend = TestBackend::new(80, 24);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let now = Utc::now();
+        let mut item = todo("fresh");
+        item.created_at = Some(now);
+        let mut model = AppModel::new(vec![item]);
+        model.mode = AppMode::Stats;
+
+        terminal.draw(|frame| draw(frame, &model, now)).unwrap();
+        let buf = terminal.backend().buffer();
+        let screen: Vec<String> = (0..buf.area.height)
+            .map(|y| (0..buf.area.width).map(|x| buf[(x, y)].symbol()).collect())
+            .collect();
+        assert!(
+            screen
+                .iter()
+                .any(|line| line.contains("Created vs completed per day"))
+        );
+        assert!(
+            screen
+                .iter()
+                .any(|line| line.contains("Pending: 1, average age 0s"))
+        );
+        assert!(
+            screen
+                .iter()
+                .any(|line| line.starts_with("Press any key to return."))
+        );
+    }
 }
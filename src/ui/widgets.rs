@@ -39,13 +39,11 @@ impl Widget for PromptWidget {
             self.text
         };
 
-        let mut x = area.x;
         let y = area.y;
-        for ch in content.chars() {
+        for (x, ch) in (area.x..).zip(content.chars()) {
             let cell = &mut buf[(x, y)];
             cell.set_symbol(ch.encode_utf8(&mut [0; 4]));
             cell.set_style(Style::default());
-            x += 1;
         }
     }
 }